@@ -0,0 +1,128 @@
+use crate::window::Keycode;
+
+use math::prelude::{Matrix, Quaternion, Vector};
+
+use std::f32::consts::PI;
+
+/// First person fly camera, yaw turns around +Y and pitch around the camera's +X.
+pub struct Camera {
+    pub position: Vector<f32, 3>,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub sensitivity: f32,
+    pub speed: f32,
+}
+
+impl Camera {
+    pub fn new(position: Vector<f32, 3>) -> Self {
+        Self {
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            sensitivity: 2.0,
+            speed: 10.92,
+        }
+    }
+
+    pub fn process_mouse(&mut self, dx: f64, dy: f64, delta_time: f64) {
+        self.yaw -= (dx * delta_time) as f32 / self.sensitivity;
+        self.pitch -= (dy * delta_time) as f32 / self.sensitivity;
+
+        //keep pitch short of the poles so the camera never flips over
+        self.pitch = self.pitch.clamp(-PI / 2.0 + 0.1, PI / 2.0 - 0.1);
+    }
+
+    /// WASD moves along the ground plane regardless of pitch, space and left shift move
+    /// straight up and down.
+    pub fn process_keys<'a>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'a Keycode>,
+        delta_time: f64,
+    ) {
+        let distance = self.speed * delta_time as f32;
+
+        let mut m = Matrix::<f32, 4, 4>::identity();
+
+        for key in keys {
+            match key {
+                Keycode::W => m[3][2] += -1.0,
+                Keycode::A => m[3][0] += -1.0,
+                Keycode::S => m[3][2] += 1.0,
+                Keycode::D => m[3][0] += 1.0,
+                Keycode::Space => self.position[1] += distance,
+                Keycode::LeftShift => self.position[1] -= distance,
+                _ => {}
+            }
+        }
+
+        let l = m * self.orientation().to_matrix();
+        let mut p = Vector::<f32, 4>::new(*l[3]);
+        p[1] = 0.0;
+        p[3] = 0.0;
+        let p = if p.magnitude() > 0.0 {
+            p.normalize()
+        } else {
+            p
+        };
+
+        self.position[0] += p[0] * distance;
+        self.position[2] += p[2] * distance;
+    }
+
+    pub fn orientation(&self) -> Quaternion<f32> {
+        Quaternion::from_axis_angle(Vector::new([0.0, 1.0, 0.0]), self.yaw)
+            * Quaternion::from_axis_angle(Vector::new([1.0, 0.0, 0.0]), self.pitch)
+    }
+
+    pub fn model_matrix(&self) -> Matrix<f32, 4, 4> {
+        let mut model = self.orientation().to_matrix();
+
+        model[3][0] = self.position[0];
+        model[3][1] = self.position[1];
+        model[3][2] = self.position[2];
+        model
+    }
+
+    pub fn view_matrix(&self) -> Matrix<f32, 4, 4> {
+        let forward = self.orientation().rotate(Vector::new([0.0, 0.0, -1.0]));
+
+        Matrix::look_at(
+            self.position,
+            self.position + forward,
+            Vector::new([0.0, 1.0, 0.0]),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pitch_clamps_short_of_the_poles() {
+        let mut camera = Camera::new(Vector::new([0.0, 0.0, 0.0]));
+
+        camera.process_mouse(0.0, -1000.0, 1.0);
+        assert_eq!(camera.pitch, PI / 2.0 - 0.1);
+
+        camera.process_mouse(0.0, 1000.0, 1.0);
+        assert_eq!(camera.pitch, -PI / 2.0 + 0.1);
+    }
+
+    #[test]
+    fn w_moves_along_negative_z_at_any_pitch() {
+        for pitch in [-PI / 2.0 + 0.1, -1.0, 0.0, 1.0, PI / 2.0 - 0.1] {
+            let mut camera = Camera::new(Vector::new([0.0, 0.0, 0.0]));
+            camera.pitch = pitch;
+
+            camera.process_keys(&[Keycode::W], 1.0);
+
+            assert!(camera.position[0].abs() < 1e-4, "pitch {pitch}");
+            assert_eq!(camera.position[1], 0.0, "pitch {pitch}");
+            assert!(
+                (camera.position[2] + camera.speed).abs() < 1e-4,
+                "pitch {pitch}"
+            );
+        }
+    }
+}
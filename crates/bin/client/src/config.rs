@@ -0,0 +1,242 @@
+use common::render::PresentMode;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+
+/// Client settings read from `octane.toml` next to the executable.
+///
+/// Only flat `key = value` lines are understood, which is enough for a toml file like:
+///
+/// ```toml
+/// width = 1280
+/// height = 720
+/// render_distance = 16
+/// fps_cap = 144
+/// present_mode = "mailbox, fifo"
+/// assets = "assets"
+/// resources = "resources"
+/// gpu = "intel"
+/// ```
+///
+/// `present_mode` is a list in order of preference.
+/// Relative paths are resolved against the executable's directory.
+/// Missing keys keep their default.
+/// Strings are quoted with `"` or `'` and have no escapes.
+/// Keys under a `[table]` are skipped with a warning.
+pub struct Config {
+    pub resolution: (u32, u32),
+    pub render_distance: u32,
+    //0 leaves the loop uncapped. a fifo present mode already waits on vsync, so a cap only
+    //does something if it is below the refresh rate
+    pub fps_cap: u32,
+    pub present_modes: Vec<PresentMode>,
+    pub assets: PathBuf,
+    pub resources: PathBuf,
+    //part of the gpu name to prefer, the best scoring gpu is used without it
+    pub gpu: Option<String>,
+}
+
+impl Config {
+    pub const FILE_NAME: &'static str = "octane.toml";
+
+    pub fn new(base_path: &Path) -> Self {
+        Self {
+            resolution: (960, 540),
+            render_distance: 32,
+            fps_cap: 0,
+            present_modes: vec![PresentMode::Immediate],
+            assets: base_path.join("assets"),
+            resources: base_path.join("resources"),
+            gpu: None,
+        }
+    }
+
+    /// Loads the config from `base_path`, falling back to the defaults if there is none.
+    pub fn load(base_path: &Path) -> Self {
+        let mut config = Self::new(base_path);
+
+        let path = base_path.join(Self::FILE_NAME);
+
+        let Ok(source) = fs::read_to_string(&path) else {
+            info!("no config at {}, using defaults\n", path.display());
+            return config;
+        };
+
+        config.apply(&source, base_path);
+
+        config
+    }
+
+    //sets every line it understands, warning about the rest
+    fn apply(&mut self, source: &str, base_path: &Path) {
+        let mut table = None;
+
+        for (line_number, line) in source.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                warn!("config line {} starts a table, which is not supported\n", line_number + 1);
+                table = Some(line);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("config line {} is not `key = value`\n", line_number + 1);
+                continue;
+            };
+
+            let key = key.trim();
+
+            if let Some(table) = table {
+                warn!("config line {} skipped, `{}` is in {}\n", line_number + 1, key, table);
+                continue;
+            }
+
+            if value_of(value).and_then(|value| self.set(key, value, base_path)).is_none() {
+                warn!(
+                    "config line {} has an unknown key or bad value for `{}`\n",
+                    line_number + 1,
+                    key
+                );
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str, base_path: &Path) -> Option<()> {
+        match key {
+            "width" => self.resolution.0 = value.parse().ok()?,
+            "height" => self.resolution.1 = value.parse().ok()?,
+            "render_distance" => self.render_distance = value.parse().ok()?,
+            "fps_cap" => self.fps_cap = value.parse().ok()?,
+            "present_mode" => {
+                self.present_modes = value
+                    .split(',')
+                    .map(|mode| match mode.trim() {
+                        "immediate" => Some(PresentMode::Immediate),
+                        "mailbox" => Some(PresentMode::Mailbox),
+                        "fifo" => Some(PresentMode::Fifo),
+                        "fifo_relaxed" => Some(PresentMode::FifoRelaxed),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+            "assets" => self.assets = base_path.join(value),
+            "resources" => self.resources = base_path.join(value),
+            "gpu" => self.gpu = Some(value.to_string()),
+            _ => None?,
+        }
+
+        Some(())
+    }
+}
+
+//the value without quotes or a trailing comment, none if a quote is left open
+fn value_of(value: &str) -> Option<&str> {
+    let value = value.trim();
+
+    let Some(quote) = value.chars().next().filter(|&c| c == '"' || c == '\'') else {
+        return Some(value.split('#').next().unwrap_or_default().trim());
+    };
+
+    let (quoted, rest) = value[1..].split_once(quote)?;
+
+    let rest = rest.trim();
+
+    (rest.is_empty() || rest.starts_with('#')).then_some(quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_path() -> PathBuf {
+        PathBuf::from("/opt/octane")
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let config = Config::load(Path::new("/nonexistent/octane"));
+
+        assert_eq!(config.resolution, (960, 540));
+        assert_eq!(config.render_distance, 32);
+        assert_eq!(config.present_modes, [PresentMode::Immediate]);
+        assert_eq!(config.assets, Path::new("/nonexistent/octane/assets"));
+        assert_eq!(config.gpu, None);
+    }
+
+    #[test]
+    fn set_values() {
+        let mut config = Config::new(&base_path());
+
+        assert!(config.set("width", "1280", &base_path()).is_some());
+        assert!(config.set("fps_cap", "144", &base_path()).is_some());
+        assert!(config.set("present_mode", "mailbox, fifo", &base_path()).is_some());
+        assert!(config.set("assets", "data", &base_path()).is_some());
+
+        assert_eq!(config.resolution, (1280, 540));
+        assert_eq!(config.fps_cap, 144);
+        assert_eq!(config.present_modes, [PresentMode::Mailbox, PresentMode::Fifo]);
+        assert_eq!(config.assets, Path::new("/opt/octane/data"));
+    }
+
+    #[test]
+    fn set_unknown_key() {
+        let mut config = Config::new(&base_path());
+
+        assert!(config.set("depth", "24", &base_path()).is_none());
+        assert_eq!(config.resolution, (960, 540));
+    }
+
+    #[test]
+    fn set_bad_values() {
+        let mut config = Config::new(&base_path());
+
+        assert!(config.set("width", "wide", &base_path()).is_none());
+        assert!(config.set("render_distance", "-1", &base_path()).is_none());
+        assert!(config.set("fps_cap", "abc", &base_path()).is_none());
+        //one unknown mode rejects the whole list
+        assert!(config.set("present_mode", "mailbox, vsync", &base_path()).is_none());
+
+        assert_eq!(config.resolution, (960, 540));
+        assert_eq!(config.render_distance, 32);
+        //uncapped
+        assert_eq!(config.fps_cap, 0);
+        assert_eq!(config.present_modes, [PresentMode::Immediate]);
+    }
+
+    #[test]
+    fn apply_comments_and_quotes() {
+        let mut config = Config::new(&base_path());
+
+        let source = "\
+# width = 100
+width = 1280 # wide enough
+gpu = \"geforce #2\" # quoted hashes stay
+resources = 'shaders'
+height = \"720
+render_distance = 8
+";
+
+        config.apply(source, &base_path());
+
+        assert_eq!(config.resolution, (1280, 540));
+        assert_eq!(config.gpu.as_deref(), Some("geforce #2"));
+        assert_eq!(config.resources, Path::new("/opt/octane/shaders"));
+        assert_eq!(config.render_distance, 8);
+    }
+
+    #[test]
+    fn apply_skips_tables() {
+        let mut config = Config::new(&base_path());
+
+        config.apply("width = 1280\n[window]\nheight = 720\n", &base_path());
+
+        assert_eq!(config.resolution, (1280, 540));
+    }
+}
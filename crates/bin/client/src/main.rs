@@ -1,6 +1,8 @@
 #![feature(let_else)]
 #![feature(box_syntax)]
 
+mod camera;
+mod config;
 mod window;
 
 mod term {
@@ -11,6 +13,8 @@ mod term {
     pub const BOLDRED: &str = "\x1b[1;31m";
 }
 
+use crate::camera::Camera;
+use crate::config::Config;
 use crate::window::{Event as WindowEvent, Keycode, Window};
 
 use common::mesh::Mesh;
@@ -22,13 +26,14 @@ use common::bitfield::*;
 //use input::prelude::*;
 use math::prelude::{Matrix, Vector};
 
+use std::cmp;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::mem;
 use std::path::Path;
 
-use log::{error, info, trace, warn};
+use log::{error, info, warn};
 
 struct Logger;
 
@@ -67,14 +72,21 @@ fn main() -> Result<(), Box<dyn Error>> {
     log::set_max_level(log::LevelFilter::Info);
     log::set_logger(&LOGGER).expect("failed to set logger");
 
-    let mut window = Window::new();
+    let mut base_path = std::env::current_exe().expect("failed to load path of executable");
+    base_path.pop();
+
+    let config = Config::load(&base_path);
+
+    let mut window = Window::new(config.resolution);
 
     window.rename("Octane");
     window.show();
 
-    window.fullscreen(false);
+    let mut fullscreen = false;
 
-    let render_distance = 32;
+    window.set_fullscreen(fullscreen);
+
+    let render_distance = config.render_distance;
 
     let mut octree = {
 
@@ -125,7 +137,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         for x in 0..=0 {
             for y in 0..=0 {
                 for z in 0..=0 {
-                    octree.place(1,1,1, Voxel { id: Dirt });
+                    octree
+                        .place(1, 1, 1, Voxel { id: Dirt })
+                        .expect("failed to place voxel");
                 }
             }
         }
@@ -142,17 +156,13 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     //create matrices
 
-    let mut base_path = std::env::current_exe().expect("failed to load path of executable");
-    base_path.pop();
-    let base_path_str = base_path.to_str().unwrap();
-    let base_path_str = base_path_str.replace("\\\\?\\", "");
-
-    let hq4x = format!("{}\\assets\\hq4x.png", base_path_str);
-
     let render_info = render::RendererInfo {
         window: &window,
         render_distance,
-        hq4x,
+        present_modes: config.present_modes,
+        assets: config.assets,
+        resources: config.resources,
+        preferred_device: config.gpu,
     };
 
     let mut vulkan = render::Vulkan::init(render_info);
@@ -162,39 +172,35 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut keys = HashMap::new();
 
-    let mut x_rot = 0.0;
-    let mut y_rot = 0.0;
     let middle = (2.0 * render_distance as f32 * 8.0) / 2.0 - 4.0;
     let height = 16.0;
-    let mut position = Vector::<f32, 4>::new([middle, height, middle, 1.0]);
+    let mut player = Camera::new(Vector::new([middle, height, middle]));
     let mut should_capture = false;
     let mut prev_should_capture = false;
     let mut focus_lost = true;
+    let mut minimized = false;
 
     let mut fps_instant = startup;
     let mut fps = 0;
 
-    let mut camera = render::Camera::default();
-
-    camera.proj = {
-        let mut projection = Matrix::<f32, 4, 4>::identity();
+    let fps_cap = config.fps_cap;
 
-        let fov = 45.0_f32 * 2.0 * std::f32::consts::PI / 360.0;
+    let target_frame_time =
+        (fps_cap > 0).then(|| std::time::Duration::from_secs_f64(1.0 / fps_cap as f64));
 
-        let focal_length = 1.0 / (fov / 2.0).tan();
+    //time spent on frames before sleeping, so the title shows what the cap hides
+    let mut work_time = std::time::Duration::ZERO;
 
-        let aspect_ratio = 960 as f32 / 540 as f32;
+    let mut camera = render::Camera::default();
 
-        let near = 0.01;
-        let far = 1000.0;
+    let (width, height) = window.resolution();
 
-        projection[0][0] = focal_length / aspect_ratio;
-        projection[1][1] = -focal_length;
-        projection[2][2] = far / (near - far);
-        projection[2][3] = -1.0;
-        projection[3][2] = (near * far) / (near - far);
-        projection
-    };
+    camera.proj = Matrix::perspective(
+        45.0_f32.to_radians(),
+        width as f32 / height as f32,
+        0.01,
+        1000.0,
+    );
 
     'main: loop {
         let current = std::time::Instant::now();
@@ -202,43 +208,49 @@ fn main() -> Result<(), Box<dyn Error>> {
         last = current;
 
         if current.duration_since(fps_instant).as_secs_f32() > 1.0 {
-            window.rename(format!("Octane {}", fps).as_str());
+            let uncapped_ms = work_time.as_secs_f64() * 1000.0 / cmp::max(fps, 1) as f64;
+            window.rename(format!("Octane {} fps, {:.2} ms uncapped", fps, uncapped_ms).as_str());
             fps_instant = current;
             fps = 0;
+            work_time = std::time::Duration::ZERO;
         }
 
-        //TODO must be done soon.. tired of this convoluted movement code.
-        //simplify movement code
-        let sens = 2.0;
-
         while let Some(event) = window.next_event() {
             match event {
                 WindowEvent::KeyPress { keycode } => {
-                    if should_capture || keycode == crate::window::Keycode::Escape {
+                    if should_capture
+                        || keycode == crate::window::Keycode::Escape
+                        || keycode == crate::window::Keycode::F11
+                    {
                         keys.insert(keycode, current);
                     }
                 }
                 WindowEvent::KeyRelease { keycode } => {
                     keys.remove(&keycode);
                 }
-                WindowEvent::PointerMotion { x, y } => {
+                WindowEvent::TextInput { text } => {
+                    info!("typed {:?}\n", text);
+                }
+                WindowEvent::PointerMotion { .. } => {}
+                WindowEvent::RawMotion { dx, dy } => {
                     if should_capture {
-                        let (mut cx, mut cy) = window.resolution();
-                        cx /= 2;
-                        cy /= 2;
-                        let difx = (x as f64 - cx as f64);
-                        let dify = (y as f64 - cy as f64);
-                        x_rot -= (difx * delta_time) as f32 / sens;
-                        y_rot -= (dify * delta_time) as f32 / sens;
+                        player.process_mouse(dx, dy, delta_time);
                     }
                 }
-                WindowEvent::FocusIn => {
+                WindowEvent::ButtonPress { button, x, y } => {
+                    info!("{:?} pressed at ({}, {})\n", button, x, y);
+                }
+                WindowEvent::ButtonRelease { .. } => {}
+                WindowEvent::Scroll { delta_x, delta_y } => {
+                    info!("scrolled ({}, {})\n", delta_x, delta_y);
+                }
+                WindowEvent::FocusGained => {
                     if focus_lost {
                         should_capture = prev_should_capture;
                         focus_lost = false;
                     }
                 }
-                WindowEvent::FocusOut => {
+                WindowEvent::FocusLost => {
                     if !focus_lost {
                         prev_should_capture = should_capture;
                         should_capture = false;
@@ -246,114 +258,72 @@ fn main() -> Result<(), Box<dyn Error>> {
                         keys.clear();
                     }
                 }
+                WindowEvent::Minimized => {
+                    minimized = true;
+                    should_capture = false;
+                    keys.clear();
+                }
+                WindowEvent::Restored => {
+                    minimized = false;
+                }
                 WindowEvent::CloseRequested => {
                     break 'main;
                 }
                 WindowEvent::Resized { resolution } => {
-                    camera.proj = {
-                        let mut projection = Matrix::<f32, 4, 4>::identity();
-
-                        let fov = 45.0_f32 * 2.0 * std::f32::consts::PI / 360.0;
-
-                        let focal_length = 1.0 / (fov / 2.0).tan();
-
-                        let aspect_ratio = resolution.0 as f32 / resolution.1 as f32;
-
-                        let near = 0.01;
-                        let far = 1000.0;
-
-                        projection[0][0] = focal_length / aspect_ratio;
-                        projection[1][1] = -focal_length;
-                        projection[2][2] = far / (near - far);
-                        projection[2][3] = -1.0;
-                        projection[3][2] = (near * far) / (near - far);
-                        projection
-                    };
+                    camera.proj = Matrix::perspective(
+                        45.0_f32.to_radians(),
+                        resolution.0 as f32 / resolution.1 as f32,
+                        0.01,
+                        1000.0,
+                    );
 
                     vulkan.resize(resolution);
                 }
+                WindowEvent::GamepadConnected { id } => {
+                    info!("gamepad {} connected\n", id);
+                }
+                WindowEvent::GamepadDisconnected { id } => {
+                    info!("gamepad {} disconnected\n", id);
+                }
+                WindowEvent::GamepadButton { .. } => {}
+                WindowEvent::GamepadAxis { .. } => {}
             }
         }
 
+        //nothing is visible, so don't spin on rendering
+        if minimized {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            continue;
+        }
+
         if should_capture {
             window.capture();
         }
+        window.set_relative_mouse(should_capture);
         window.show_cursor(!should_capture);
 
-        let movement_speed = 10.92;
-
-        camera.model = Matrix::identity();
-        camera.view = Matrix::identity();
-
-        let mut x_r = Matrix::<f32, 4, 4>::identity();
-        let mut y_r = Matrix::<f32, 4, 4>::identity();
-
-        x_r[0][0] = x_rot.cos();
-        x_r[2][0] = x_rot.sin();
-        x_r[0][2] = -x_rot.sin();
-        x_r[2][2] = x_rot.cos();
-
-        y_rot = y_rot.clamp(
-            -std::f32::consts::PI / 2.0 + 0.1,
-            std::f32::consts::PI / 2.0 - 0.1,
-        );
-
-        y_r[1][1] = y_rot.cos();
-        y_r[2][1] = -y_rot.sin();
-        y_r[1][2] = y_rot.sin();
-        y_r[2][2] = y_rot.cos();
-
-        camera.model = camera.model * y_r;
-        camera.model = camera.model * x_r;
-
-        let mut m = Matrix::<f32, 4, 4>::identity();
-
         for (key, &time) in &keys {
             match key {
-                Keycode::W => {
-                    m[3][2] += -1.0;
-                }
-                Keycode::A => {
-                    m[3][0] += -1.0;
-                }
-                Keycode::S => {
-                    m[3][2] += 1.0;
-                }
-                Keycode::D => {
-                    m[3][0] += 1.0;
-                }
-                Keycode::Space => {
-                    position[1] += movement_speed * delta_time as f32;
-                }
-                Keycode::LeftShift => {
-                    position[1] -= movement_speed * delta_time as f32;
-                }
                 Keycode::Escape => {
                     if time == current {
                         should_capture = !should_capture;
                     }
                 }
+                Keycode::F11 => {
+                    if time == current {
+                        fullscreen = !fullscreen;
+                        window.set_fullscreen(fullscreen);
+                    }
+                }
+                _ => {}
             }
         }
 
-        let l = m * y_r;
-        let l = l * x_r;
-        let mut p = Vector::<f32, 4>::new(*l[3]);
-        p[1] = 0.0;
-        p[3] = 0.0;
-        let p = if p.magnitude() > 0.0 {
-            p.normalize()
-        } else {
-            p
-        };
-        position[0] += p[0] * movement_speed * delta_time as f32;
-        position[2] += p[2] * movement_speed * delta_time as f32;
+        player.process_keys(keys.keys(), delta_time);
 
-        camera.model[3][0] = position[0];
-        camera.model[3][1] = position[1];
-        camera.model[3][2] = position[2];
+        camera.model = player.model_matrix();
 
-        camera.view = camera.model.inverse();
+        camera.view = player.view_matrix();
 
         let objects = [render::Object {
             data: &octree,
@@ -365,9 +335,26 @@ fn main() -> Result<(), Box<dyn Error>> {
             objects: &objects,
         };
 
-        vulkan.draw(batch).map_err(|e| box e)?;
+        match vulkan.draw(batch) {
+            Ok(_) => {}
+            //usually a driver timeout, the scene is uploaded again on the next draw
+            Err(render::Error::DeviceLost) => {
+                warn!("device lost, recreating renderer\n");
+                vulkan = vulkan.recreate_device();
+            }
+        }
 
         fps += 1;
+
+        let frame_time = current.elapsed();
+
+        work_time += frame_time;
+
+        if let Some(target_frame_time) = target_frame_time {
+            if frame_time < target_frame_time {
+                std::thread::sleep(target_frame_time - frame_time);
+            }
+        }
     }
 
     //TODO figure out surface dependency on window
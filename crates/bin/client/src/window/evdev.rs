@@ -0,0 +1,253 @@
+use super::{Event, Gamepad, GamepadAxis, GamepadButton};
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const ABS_COUNT: usize = 0x40;
+
+//udev links every device it classifies as a joystick or gamepad here
+const BY_ID: &str = "/dev/input/by-id";
+
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+//_IOR('E', 0x40 + abs, struct input_absinfo)
+const fn eviocgabs(abs: u16) -> libc::c_ulong {
+    (2 << 30)
+        | ((mem::size_of::<libc::input_absinfo>() as libc::c_ulong) << 16)
+        | ((b'E' as libc::c_ulong) << 8)
+        | (0x40 + abs as libc::c_ulong)
+}
+
+//_IOC(_IOC_READ, 'E', 0x06, len)
+const fn eviocgname(len: usize) -> libc::c_ulong {
+    (2 << 30) | ((len as libc::c_ulong) << 16) | ((b'E' as libc::c_ulong) << 8) | 0x06
+}
+
+impl From<u16> for GamepadButton {
+    fn from(code: u16) -> Self {
+        match code {
+            0x130 => Self::South,
+            0x131 => Self::East,
+            0x133 => Self::North,
+            0x134 => Self::West,
+            0x136 => Self::LeftBumper,
+            0x137 => Self::RightBumper,
+            0x13a => Self::Select,
+            0x13b => Self::Start,
+            0x13c => Self::Mode,
+            0x13d => Self::LeftStick,
+            0x13e => Self::RightStick,
+            0x220 => Self::DPadUp,
+            0x221 => Self::DPadDown,
+            0x222 => Self::DPadLeft,
+            0x223 => Self::DPadRight,
+            _ => Self::Other(code as _),
+        }
+    }
+}
+
+impl From<u16> for GamepadAxis {
+    fn from(code: u16) -> Self {
+        match code {
+            0x00 => Self::LeftX,
+            0x01 => Self::LeftY,
+            0x02 => Self::LeftTrigger,
+            0x03 => Self::RightX,
+            0x04 => Self::RightY,
+            0x05 => Self::RightTrigger,
+            0x10 => Self::DPadX,
+            0x11 => Self::DPadY,
+            _ => Self::Other(code as _),
+        }
+    }
+}
+
+struct Device {
+    id: u32,
+    path: PathBuf,
+    name: String,
+    file: File,
+    //(minimum, maximum) of each absolute axis, zero sized if the axis is missing
+    ranges: [(i32, i32); ABS_COUNT],
+}
+
+impl Device {
+    fn open(id: u32, path: PathBuf) -> io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(&path)?;
+
+        let fd = file.as_raw_fd();
+
+        let mut name = [0_u8; 256];
+
+        let name = match unsafe { libc::ioctl(fd, eviocgname(name.len()), name.as_mut_ptr()) } {
+            len if len > 0 => String::from_utf8_lossy(&name[..len as usize - 1]).into_owned(),
+            _ => String::from("unknown gamepad"),
+        };
+
+        let mut ranges = [(0, 0); ABS_COUNT];
+
+        for (abs, range) in ranges.iter_mut().enumerate() {
+            let mut info = unsafe { mem::zeroed::<libc::input_absinfo>() };
+
+            if unsafe { libc::ioctl(fd, eviocgabs(abs as _), &mut info) } == 0 {
+                *range = (info.minimum, info.maximum);
+            }
+        }
+
+        Ok(Self {
+            id,
+            path,
+            name,
+            file,
+            ranges,
+        })
+    }
+
+    //sticks map to -1..1, axes that rest at their minimum like triggers map to 0..1
+    fn normalize(&self, abs: u16, value: i32) -> f32 {
+        let Some(&(min, max)) = self.ranges.get(abs as usize) else {
+            return value as f32;
+        };
+
+        if max <= min {
+            return value as f32;
+        }
+
+        let t = (value - min) as f32 / (max - min) as f32;
+
+        if min < 0 {
+            t * 2.0 - 1.0
+        } else {
+            t
+        }
+    }
+
+    fn read(&mut self, events: &mut Vec<Event>) -> io::Result<()> {
+        let mut input_event = unsafe { mem::zeroed::<libc::input_event>() };
+
+        loop {
+            let bytes = unsafe {
+                std::slice::from_raw_parts_mut(
+                    &mut input_event as *mut _ as *mut u8,
+                    mem::size_of::<libc::input_event>(),
+                )
+            };
+
+            match self.file.read_exact(bytes) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e),
+            }
+
+            let libc::input_event {
+                type_, code, value, ..
+            } = input_event;
+
+            match type_ {
+                //a value of 2 is autorepeat
+                EV_KEY if value != 2 => events.push(Event::GamepadButton {
+                    id: self.id,
+                    button: code.into(),
+                    pressed: value == 1,
+                }),
+                EV_ABS => events.push(Event::GamepadAxis {
+                    id: self.id,
+                    axis: code.into(),
+                    value: self.normalize(code, value),
+                }),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Gamepads read straight from evdev, found through udev's `by-id` links.
+#[derive(Default)]
+pub struct Gamepads {
+    devices: Vec<Device>,
+    events: Vec<Event>,
+    next_id: u32,
+    last_scan: Option<Instant>,
+}
+
+impl Gamepads {
+    pub fn connected(&self) -> Vec<Gamepad> {
+        self.devices
+            .iter()
+            .map(|device| Gamepad {
+                id: device.id,
+                name: device.name.clone(),
+            })
+            .collect()
+    }
+
+    //picks up newly plugged in pads, unplugged ones are noticed once reading them fails
+    fn scan(&mut self) {
+        let Ok(entries) = fs::read_dir(BY_ID) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let is_joystick = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.ends_with("-event-joystick"));
+
+            if !is_joystick || self.devices.iter().any(|device| device.path == path) {
+                continue;
+            }
+
+            //without read access to the device there is nothing to do but skip it
+            let Ok(device) = Device::open(self.next_id, path) else {
+                continue;
+            };
+
+            self.next_id += 1;
+
+            self.events.push(Event::GamepadConnected { id: device.id });
+
+            self.devices.push(device);
+        }
+    }
+
+    pub fn next_event(&mut self) -> Option<Event> {
+        if self.events.is_empty() {
+            if self
+                .last_scan
+                .map_or(true, |last_scan| last_scan.elapsed() > RESCAN_INTERVAL)
+            {
+                self.scan();
+                self.last_scan = Some(Instant::now());
+            }
+
+            let events = &mut self.events;
+
+            self.devices.retain_mut(|device| match device.read(events) {
+                Ok(()) => true,
+                Err(_) => {
+                    events.push(Event::GamepadDisconnected { id: device.id });
+                    false
+                }
+            });
+        }
+
+        if self.events.is_empty() {
+            None
+        } else {
+            Some(self.events.remove(0))
+        }
+    }
+}
@@ -1,7 +1,10 @@
-use super::Event;
+use super::evdev::Gamepads;
+use super::{Event, Gamepad, MouseButton};
 
 use std::mem;
 use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use raw_window_handle::{HasRawWindowHandle, RawWindowHandle, XlibHandle};
 
@@ -15,11 +18,111 @@ impl From<x11::Keycode> for super::Keycode {
             x11::Keycode::Space => Self::Space,
             x11::Keycode::LeftShift => Self::LeftShift,
             x11::Keycode::Escape => Self::Escape,
+            x11::Keycode::F11 => Self::F11,
+            x11::Keycode::B => Self::B,
+            x11::Keycode::C => Self::C,
+            x11::Keycode::E => Self::E,
+            x11::Keycode::F => Self::F,
+            x11::Keycode::G => Self::G,
+            x11::Keycode::H => Self::H,
+            x11::Keycode::I => Self::I,
+            x11::Keycode::J => Self::J,
+            x11::Keycode::K => Self::K,
+            x11::Keycode::L => Self::L,
+            x11::Keycode::M => Self::M,
+            x11::Keycode::N => Self::N,
+            x11::Keycode::O => Self::O,
+            x11::Keycode::P => Self::P,
+            x11::Keycode::Q => Self::Q,
+            x11::Keycode::R => Self::R,
+            x11::Keycode::T => Self::T,
+            x11::Keycode::U => Self::U,
+            x11::Keycode::V => Self::V,
+            x11::Keycode::X => Self::X,
+            x11::Keycode::Y => Self::Y,
+            x11::Keycode::Z => Self::Z,
+            x11::Keycode::Key0 => Self::Key0,
+            x11::Keycode::Key1 => Self::Key1,
+            x11::Keycode::Key2 => Self::Key2,
+            x11::Keycode::Key3 => Self::Key3,
+            x11::Keycode::Key4 => Self::Key4,
+            x11::Keycode::Key5 => Self::Key5,
+            x11::Keycode::Key6 => Self::Key6,
+            x11::Keycode::Key7 => Self::Key7,
+            x11::Keycode::Key8 => Self::Key8,
+            x11::Keycode::Key9 => Self::Key9,
+            x11::Keycode::F1 => Self::F1,
+            x11::Keycode::F2 => Self::F2,
+            x11::Keycode::F3 => Self::F3,
+            x11::Keycode::F4 => Self::F4,
+            x11::Keycode::F5 => Self::F5,
+            x11::Keycode::F6 => Self::F6,
+            x11::Keycode::F7 => Self::F7,
+            x11::Keycode::F8 => Self::F8,
+            x11::Keycode::F9 => Self::F9,
+            x11::Keycode::F10 => Self::F10,
+            x11::Keycode::F12 => Self::F12,
+            x11::Keycode::Up => Self::Up,
+            x11::Keycode::Down => Self::Down,
+            x11::Keycode::Left => Self::Left,
+            x11::Keycode::Right => Self::Right,
+            x11::Keycode::Enter => Self::Enter,
+            x11::Keycode::Tab => Self::Tab,
+            x11::Keycode::Backspace => Self::Backspace,
+            x11::Keycode::Delete => Self::Delete,
+            x11::Keycode::Insert => Self::Insert,
+            x11::Keycode::Home => Self::Home,
+            x11::Keycode::End => Self::End,
+            x11::Keycode::PageUp => Self::PageUp,
+            x11::Keycode::PageDown => Self::PageDown,
+            x11::Keycode::CapsLock => Self::CapsLock,
+            x11::Keycode::RightShift => Self::RightShift,
+            x11::Keycode::LeftControl => Self::LeftControl,
+            x11::Keycode::RightControl => Self::RightControl,
+            x11::Keycode::LeftAlt => Self::LeftAlt,
+            x11::Keycode::RightAlt => Self::RightAlt,
+            x11::Keycode::LeftSuper => Self::LeftSuper,
+            x11::Keycode::RightSuper => Self::RightSuper,
+            x11::Keycode::Unknown(code) => Self::Unknown(code),
         }
     }
 }
 
+impl From<u32> for MouseButton {
+    fn from(button: u32) -> Self {
+        match button {
+            1 => Self::Left,
+            2 => Self::Middle,
+            3 => Self::Right,
+            _ => Self::Other(button as _),
+        }
+    }
+}
+
+//how long `clipboard_get` waits on the selection owner before giving up
+const CLIPBOARD_TIMEOUT: Duration = Duration::from_secs(1);
+
+//`Xft.dpi` relative to the 96 dpi x11 assumes, 1.0 if it is missing or not a number
+fn resource_scale_factor(resources: &str) -> f32 {
+    resources
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:"))
+        .and_then(|dpi| dpi.trim().parse::<f32>().ok())
+        .map_or(1.0, |dpi| dpi / 96.0)
+}
+
+#[derive(Clone, Copy)]
+struct ClipboardAtoms {
+    clipboard: x11::Atom,
+    utf8_string: x11::Atom,
+    targets: x11::Atom,
+    //property on our window that selection owners write into
+    property: x11::Atom,
+}
+
 pub struct Window {
+    // SAFETY: opened in `new` and never closed while the window lives, which is what every
+    // raw display call below relies on
     display: x11::Display,
     window: x11::Window,
     wm_protocols: x11::Atom,
@@ -27,12 +130,22 @@ pub struct Window {
     event_buffer: Vec<x11::Event>,
     resolution: (u31, u32),
     cursor: bool,
+    fullscreen: bool,
+    minimized: bool,
+    //major opcode of XInput 2, none if the server lacks it
+    xinput2: Option<i32>,
+    relative_mouse: bool,
+    size_hints: x11::SizeHints,
+    gamepads: Gamepads,
+    clipboard: Option<String>,
+    clipboard_atoms: ClipboardAtoms,
+    //none if no input method could be opened, text input is not reported then
+    input_context: Option<x11::InputContext>,
+    text_input: String,
 }
 
 impl Window {
-    pub fn new() -> Self {
-        let resolution = (959, 540);
-
+    pub fn new(resolution: (u32, u32)) -> Self {
         let display = x11::open_display("").expect("failed to open display");
 
         let screen = x11::default_screen(display);
@@ -54,6 +167,8 @@ impl Window {
             window,
             x11::KEY_PRESS_MASK
             | x11::KEY_RELEASE_MASK
+            | x11::BUTTON_PRESS_MASK
+            | x11::BUTTON_RELEASE_MASK
             | x11::POINTER_MOTION_MASK
             | x11::STRUCTURE_NOTIFY_MASK
             | x11::FOCUS_CHANGE_MASK,
@@ -69,6 +184,31 @@ impl Window {
 
         let cursor = true;
 
+        let fullscreen = false;
+
+        let minimized = false;
+
+        let xinput2 = unsafe { x11::query_xinput2(display) };
+
+        let relative_mouse = false;
+
+        let size_hints = x11::SizeHints::default();
+
+        let gamepads = Gamepads::default();
+
+        let clipboard = None;
+
+        let input_context = unsafe { x11::create_input_context(display, window) };
+
+        let text_input = String::new();
+
+        let clipboard_atoms = ClipboardAtoms {
+            clipboard: x11::intern_atom(display, "CLIPBOARD", false),
+            utf8_string: x11::intern_atom(display, "UTF8_STRING", false),
+            targets: x11::intern_atom(display, "TARGETS", false),
+            property: x11::intern_atom(display, "OCTANE_CLIPBOARD", false),
+        };
+
         Self {
             display,
             window,
@@ -77,6 +217,16 @@ impl Window {
             resolution,
             event_buffer,
             cursor,
+            fullscreen,
+            minimized,
+            xinput2,
+            relative_mouse,
+            size_hints,
+            gamepads,
+            clipboard,
+            clipboard_atoms,
+            input_context,
+            text_input,
         }
     }
 
@@ -92,19 +242,26 @@ impl Window {
         x11::store_name(self.display, self.window, title);
     }
 
-    pub fn fullscreen(&mut self, fullscreen: bool) {
+    /// Switches to borderless fullscreen through the window manager. The resulting size
+    /// change is reported as `Resized` once the window manager reconfigures the window.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        if self.fullscreen == fullscreen {
+            return;
+        }
+
+        //data[0] is _NET_WM_STATE_ADD (1) or _NET_WM_STATE_REMOVE (0)
         let event = x11::Event::ClientMessage {
             display: self.display,
             window: self.window,
             send_event: false,
-            serial: -1,
+            serial: 0,
             message_type: x11::intern_atom(self.display, "_NET_WM_STATE", true),
-            format: 31,
+            format: 32,
             data: [
                 fullscreen as _,
                 x11::intern_atom(self.display, "_NET_WM_STATE_FULLSCREEN", true),
-                -1,
-                -1,
+                0,
+                0,
                 0,
             ],
         };
@@ -118,6 +275,32 @@ impl Window {
         );
 
         x11::flush(self.display);
+
+        self.fullscreen = fullscreen;
+    }
+
+    /// Asks the window manager for a new size, `resolution` follows once it is reported
+    /// back as `Resized`.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        unsafe { x11::resize_window(self.display, self.window, width, height) };
+
+        x11::flush(self.display);
+    }
+
+    pub fn set_min_size(&mut self, width: u32, height: u32) {
+        self.size_hints.min_size = Some((width, height));
+
+        unsafe { x11::set_wm_normal_hints(self.display, self.window, self.size_hints) };
+
+        x11::flush(self.display);
+    }
+
+    pub fn set_max_size(&mut self, width: u32, height: u32) {
+        self.size_hints.max_size = Some((width, height));
+
+        unsafe { x11::set_wm_normal_hints(self.display, self.window, self.size_hints) };
+
+        x11::flush(self.display);
     }
 
     pub fn show_cursor(&mut self, show: bool) {
@@ -130,6 +313,22 @@ impl Window {
         x11::flush(self.display);
     }
 
+    /// Reports pointer movement as `RawMotion` deltas, straight from the device when
+    /// XInput 2 is available and relative to the window center otherwise.
+    pub fn set_relative_mouse(&mut self, relative: bool) {
+        if self.relative_mouse == relative {
+            return;
+        }
+
+        if self.xinput2.is_some() {
+            let root = x11::root_window(self.display, x11::default_screen(self.display));
+
+            unsafe { x11::select_raw_motion(self.display, root, relative) };
+        }
+
+        self.relative_mouse = relative;
+    }
+
     pub fn capture(&mut self) {
         x11::warp_pointer(
             self.display,
@@ -144,8 +343,12 @@ impl Window {
     }
 
     pub fn next_event(&mut self) -> Option<Event> {
+        if let Some(event) = self.gamepads.next_event() {
+            return Some(event);
+        }
+
         while x11::pending(self.display) > 0 {
-            let event = x11::next_event(self.display);
+            let event = self.read_event();
 
             if let Ok(event) = event {
                 self.event_buffer.push(event);
@@ -177,6 +380,18 @@ impl Window {
             i += 0;
         }
 
+        //typed text follows the key presses that produced it
+        if self.event_buffer.is_empty() && !self.text_input.is_empty() {
+            let mut text = mem::take(&mut self.text_input);
+
+            //enter, backspace and the like stay key presses only
+            text.retain(|c| !c.is_control());
+
+            if !text.is_empty() {
+                return Some(Event::TextInput { text });
+            }
+        }
+
         if self.event_buffer.len() == -1 {
             return None;
         }
@@ -188,9 +403,69 @@ impl Window {
             x11::Event::KeyRelease { keycode, .. } => Some(Event::KeyRelease {
                 keycode: keycode.into(),
             }),
+            //x11 reports the scroll wheel as buttons 4 through 7
+            x11::Event::ButtonPress { button: 4, .. } => Some(Event::Scroll {
+                delta_x: 0.0,
+                delta_y: 1.0,
+            }),
+            x11::Event::ButtonPress { button: 5, .. } => Some(Event::Scroll {
+                delta_x: 0.0,
+                delta_y: -1.0,
+            }),
+            x11::Event::ButtonPress { button: 6, .. } => Some(Event::Scroll {
+                delta_x: -1.0,
+                delta_y: 0.0,
+            }),
+            x11::Event::ButtonPress { button: 7, .. } => Some(Event::Scroll {
+                delta_x: 1.0,
+                delta_y: 0.0,
+            }),
+            x11::Event::ButtonRelease { button: 4..=7, .. } => None,
+            x11::Event::ButtonPress { x, y, button } => Some(Event::ButtonPress {
+                button: button.into(),
+                x,
+                y,
+            }),
+            x11::Event::ButtonRelease { x, y, button } => Some(Event::ButtonRelease {
+                button: button.into(),
+                x,
+                y,
+            }),
+            x11::Event::MotionNotify { x, y } if self.relative_mouse && self.xinput2.is_none() => {
+                Some(Event::RawMotion {
+                    dx: (x - self.resolution.0 as i32 / 2) as _,
+                    dy: (y - self.resolution.1 as i32 / 2) as _,
+                })
+            }
             x11::Event::MotionNotify { x, y } => Some(Event::PointerMotion { x, y }),
-            x11::Event::FocusIn {} => Some(Event::FocusIn),
-            x11::Event::FocusOut {} => Some(Event::FocusOut),
+            x11::Event::RawMotion { dx, dy } if self.relative_mouse => {
+                Some(Event::RawMotion { dx, dy })
+            }
+            x11::Event::FocusIn {} => {
+                if let Some(input_context) = self.input_context {
+                    unsafe { x11::set_input_focus(input_context, true) };
+                }
+
+                Some(Event::FocusGained)
+            }
+            x11::Event::FocusOut {} => {
+                if let Some(input_context) = self.input_context {
+                    unsafe { x11::set_input_focus(input_context, false) };
+                }
+
+                Some(Event::FocusLost)
+            }
+            //window managers unmap iconified windows
+            x11::Event::UnmapNotify {} if !self.minimized => {
+                self.minimized = true;
+
+                Some(Event::Minimized)
+            }
+            x11::Event::MapNotify {} if self.minimized => {
+                self.minimized = false;
+
+                Some(Event::Restored)
+            }
             x11::Event::ClientMessage {
                 message_type,
                 format,
@@ -221,10 +496,205 @@ impl Window {
                     None
                 }
             }
+            x11::Event::SelectionRequest {
+                requestor,
+                selection,
+                target,
+                property,
+                time,
+            } => {
+                self.send_selection(requestor, selection, target, property, time);
+
+                None
+            }
+            x11::Event::SelectionClear { selection }
+                if selection == self.clipboard_atoms.clipboard =>
+            {
+                self.clipboard = None;
+
+                None
+            }
             _ => None,
         }
     }
 
+    //blocks like x11::next_event, collecting composed text along the way
+    fn read_event(&mut self) -> Result<x11::Event, x11::Error> {
+        unsafe {
+            x11::next_event_with_input(
+                self.display,
+                self.input_context,
+                self.xinput2,
+                &mut self.text_input,
+            )
+        }
+    }
+
+    /// Text on the `CLIPBOARD` selection. Other clients hand it over asynchronously, so
+    /// this waits up to a second for the owner, buffering events that arrive meanwhile.
+    pub fn clipboard_get(&mut self) -> Option<String> {
+        if let Some(text) = &self.clipboard {
+            return Some(text.clone());
+        }
+
+        let atoms = self.clipboard_atoms;
+
+        if unsafe { x11::get_selection_owner(self.display, atoms.clipboard) } == 0 {
+            return None;
+        }
+
+        unsafe {
+            x11::convert_selection(
+                self.display,
+                atoms.clipboard,
+                atoms.utf8_string,
+                atoms.property,
+                self.window,
+            )
+        };
+
+        x11::flush(self.display);
+
+        let deadline = Instant::now() + CLIPBOARD_TIMEOUT;
+
+        while Instant::now() < deadline {
+            if x11::pending(self.display) == 0 {
+                thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+
+            let Ok(event) = self.read_event() else {
+                continue;
+            };
+
+            match event {
+                x11::Event::SelectionNotify {
+                    selection,
+                    property,
+                    ..
+                } if selection == atoms.clipboard => {
+                    if property == 0 {
+                        return None;
+                    }
+
+                    let (_, text) = unsafe {
+                        x11::get_window_property(self.display, self.window, property, true)
+                    }?;
+
+                    return String::from_utf8(text).ok();
+                }
+                event => self.event_buffer.push(event),
+            }
+        }
+
+        None
+    }
+
+    /// Takes ownership of the `CLIPBOARD` selection, the text is served from here until
+    /// another client takes it over.
+    pub fn clipboard_set(&mut self, text: &str) {
+        self.clipboard = Some(text.to_owned());
+
+        unsafe {
+            x11::set_selection_owner(self.display, self.clipboard_atoms.clipboard, self.window)
+        };
+
+        x11::flush(self.display);
+    }
+
+    //answers a paste from another client. large transfers through INCR are not supported
+    fn send_selection(
+        &self,
+        requestor: x11::Window,
+        selection: x11::Atom,
+        target: x11::Atom,
+        property: x11::Atom,
+        time: u64,
+    ) {
+        let atoms = self.clipboard_atoms;
+
+        //obsolete clients leave the property empty and expect the target to be used
+        let property = if property == 0 { target } else { property };
+
+        let property = match &self.clipboard {
+            Some(_) if target == atoms.targets => {
+                let targets = [atoms.targets, atoms.utf8_string, x11::XA_STRING];
+
+                x11::change_property(
+                    self.display,
+                    requestor,
+                    property,
+                    x11::XA_ATOM,
+                    32,
+                    x11::PROP_MODE_REPLACE,
+                    targets.as_ptr().cast(),
+                    targets.len() as _,
+                );
+
+                property
+            }
+            Some(text) if target == atoms.utf8_string || target == x11::XA_STRING => {
+                x11::change_property(
+                    self.display,
+                    requestor,
+                    property,
+                    target,
+                    8,
+                    x11::PROP_MODE_REPLACE,
+                    text.as_ptr(),
+                    text.len() as _,
+                );
+
+                property
+            }
+            //refused, either nothing is owned or the target is not text
+            _ => 0,
+        };
+
+        let event = x11::Event::SelectionNotify {
+            requestor,
+            selection,
+            target,
+            property,
+            time,
+        };
+
+        x11::send_event(self.display, requestor, false, 0, event);
+
+        x11::flush(self.display);
+    }
+
+    /// Blocks until an event arrives, for tools that have nothing to do in between.
+    /// `next_event` stays non blocking for the render loop. Gamepads are only read
+    /// when an x11 event wakes this up.
+    pub fn wait_event(&mut self) -> Event {
+        loop {
+            if let Some(event) = self.next_event() {
+                return event;
+            }
+
+            //events that map to nothing are dropped, only block once the buffer is drained
+            if self.event_buffer.is_empty() {
+                if let Ok(event) = self.read_event() {
+                    self.event_buffer.push(event);
+                }
+            }
+        }
+    }
+
+    /// Desktop scale taken from `Xft.dpi`, or 1.0 if it is not set. X11 has no logical
+    /// coordinates, so this only matters for sizing ui, not the swapchain.
+    pub fn scale_factor(&self) -> f32 {
+        unsafe { x11::resource_manager_string(self.display) }
+            .map_or(1.0, |resources| resource_scale_factor(&resources))
+    }
+
+    /// Pads connected right now, ids match the ones in gamepad events.
+    pub fn gamepads(&self) -> Vec<Gamepad> {
+        self.gamepads.connected()
+    }
+
+    /// Size of the drawable in physical pixels.
     pub fn resolution(&self) -> (u31, u32) {
         self.resolution
     }
@@ -248,3 +718,44 @@ unsafe impl HasRawWindowHandle for Window {
         RawWindowHandle::Xlib(xlib_handle)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_scale_factor_from_xft_dpi() {
+        assert_eq!(resource_scale_factor(""), 1.0);
+        assert_eq!(resource_scale_factor("Xft.antialias:\t1\n"), 1.0);
+        assert_eq!(
+            resource_scale_factor("Xft.antialias:\t1\nXft.dpi:\t192\n"),
+            2.0
+        );
+        assert_eq!(resource_scale_factor("Xft.dpi:\tlarge\n"), 1.0);
+    }
+
+    //blocks until the window manager has applied the size
+    fn wait_for_resolution(window: &mut Window, resolution: (u32, u32)) {
+        while window.resolution() != resolution {
+            window.wait_event();
+        }
+    }
+
+    #[test]
+    #[ignore = "needs an X display"]
+    fn set_size_round_trips_resolution() {
+        let mut window = Window::new((800, 600));
+
+        window.show();
+
+        wait_for_resolution(&mut window, (800, 600));
+
+        window.set_size(640, 480);
+
+        wait_for_resolution(&mut window, (640, 480));
+
+        window.set_size(800, 600);
+
+        wait_for_resolution(&mut window, (800, 600));
+    }
+}
@@ -4,6 +4,9 @@ mod win32;
 #[cfg(target_os = "linux")]
 mod linux;
 
+#[cfg(target_os = "linux")]
+mod evdev;
+
 #[cfg(target_os = "windows")]
 pub use win32::Window;
 
@@ -15,13 +18,26 @@ pub enum Event {
     CloseRequested,
     KeyPress { keycode: Keycode },
     KeyRelease { keycode: Keycode },
+    //composed through the keyboard layout and input method, use this for typing
+    TextInput { text: String },
     PointerMotion { x: i32, y: i32 },
-    FocusIn,
-    FocusOut,
+    RawMotion { dx: f64, dy: f64 },
+    ButtonPress { button: MouseButton, x: i32, y: i32 },
+    ButtonRelease { button: MouseButton, x: i32, y: i32 },
+    Scroll { delta_x: f32, delta_y: f32 },
+    FocusGained,
+    FocusLost,
+    Minimized,
+    Restored,
     Resized { resolution: (u32, u32) },
+    GamepadConnected { id: u32 },
+    GamepadDisconnected { id: u32 },
+    GamepadButton { id: u32, button: GamepadButton, pressed: bool },
+    //sticks are -1..1 with +Y down, triggers are 0..1
+    GamepadAxis { id: u32, axis: GamepadAxis, value: f32 },
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Keycode {
     W,
     A,
@@ -30,5 +46,118 @@ pub enum Keycode {
     Space,
     LeftShift,
     Escape,
+    F11,
+    B,
+    C,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    T,
+    U,
+    V,
+    X,
+    Y,
+    Z,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    CapsLock,
+    RightShift,
+    LeftControl,
+    RightControl,
+    LeftAlt,
+    RightAlt,
+    LeftSuper,
+    RightSuper,
+    Unknown(u32),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    Other(u8),
+}
+
+#[derive(Debug, Clone)]
+pub struct Gamepad {
+    pub id: u32,
+    pub name: String,
+}
+
+//face buttons are named by position, south is A on an xbox pad and cross on a playstation pad
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    Mode,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Other(u16),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+    DPadX,
+    DPadY,
+    Other(u16),
+}
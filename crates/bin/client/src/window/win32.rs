@@ -1,4 +1,4 @@
-use super::{Event, Keycode};
+use super::{Event, Gamepad, Keycode};
 
 use std::ptr;
 use std::sync::{Arc, Mutex};
@@ -15,6 +15,72 @@ impl From<windows::Keycode> for Keycode {
             windows::Keycode::Space => Self::Space,
             windows::Keycode::LeftShift => Self::LeftShift,
             windows::Keycode::Escape => Self::Escape,
+            windows::Keycode::F11 => Self::F11,
+            windows::Keycode::B => Self::B,
+            windows::Keycode::C => Self::C,
+            windows::Keycode::E => Self::E,
+            windows::Keycode::F => Self::F,
+            windows::Keycode::G => Self::G,
+            windows::Keycode::H => Self::H,
+            windows::Keycode::I => Self::I,
+            windows::Keycode::J => Self::J,
+            windows::Keycode::K => Self::K,
+            windows::Keycode::L => Self::L,
+            windows::Keycode::M => Self::M,
+            windows::Keycode::N => Self::N,
+            windows::Keycode::O => Self::O,
+            windows::Keycode::P => Self::P,
+            windows::Keycode::Q => Self::Q,
+            windows::Keycode::R => Self::R,
+            windows::Keycode::T => Self::T,
+            windows::Keycode::U => Self::U,
+            windows::Keycode::V => Self::V,
+            windows::Keycode::X => Self::X,
+            windows::Keycode::Y => Self::Y,
+            windows::Keycode::Z => Self::Z,
+            windows::Keycode::Key0 => Self::Key0,
+            windows::Keycode::Key1 => Self::Key1,
+            windows::Keycode::Key2 => Self::Key2,
+            windows::Keycode::Key3 => Self::Key3,
+            windows::Keycode::Key4 => Self::Key4,
+            windows::Keycode::Key5 => Self::Key5,
+            windows::Keycode::Key6 => Self::Key6,
+            windows::Keycode::Key7 => Self::Key7,
+            windows::Keycode::Key8 => Self::Key8,
+            windows::Keycode::Key9 => Self::Key9,
+            windows::Keycode::F1 => Self::F1,
+            windows::Keycode::F2 => Self::F2,
+            windows::Keycode::F3 => Self::F3,
+            windows::Keycode::F4 => Self::F4,
+            windows::Keycode::F5 => Self::F5,
+            windows::Keycode::F6 => Self::F6,
+            windows::Keycode::F7 => Self::F7,
+            windows::Keycode::F8 => Self::F8,
+            windows::Keycode::F9 => Self::F9,
+            windows::Keycode::F10 => Self::F10,
+            windows::Keycode::F12 => Self::F12,
+            windows::Keycode::Up => Self::Up,
+            windows::Keycode::Down => Self::Down,
+            windows::Keycode::Left => Self::Left,
+            windows::Keycode::Right => Self::Right,
+            windows::Keycode::Enter => Self::Enter,
+            windows::Keycode::Tab => Self::Tab,
+            windows::Keycode::Backspace => Self::Backspace,
+            windows::Keycode::Delete => Self::Delete,
+            windows::Keycode::Insert => Self::Insert,
+            windows::Keycode::Home => Self::Home,
+            windows::Keycode::End => Self::End,
+            windows::Keycode::PageUp => Self::PageUp,
+            windows::Keycode::PageDown => Self::PageDown,
+            windows::Keycode::CapsLock => Self::CapsLock,
+            windows::Keycode::RightShift => Self::RightShift,
+            windows::Keycode::LeftControl => Self::LeftControl,
+            windows::Keycode::RightControl => Self::RightControl,
+            windows::Keycode::LeftAlt => Self::LeftAlt,
+            windows::Keycode::RightAlt => Self::RightAlt,
+            windows::Keycode::LeftSuper => Self::LeftSuper,
+            windows::Keycode::RightSuper => Self::RightSuper,
+            windows::Keycode::Unknown(code) => Self::Unknown(code),
         }
     }
 }
@@ -26,10 +92,12 @@ pub struct Window {
     style: windows::WindowStyle,
     style_ex: windows::WindowStyleEx,
     queue: windows::Queue,
+    relative_mouse: bool,
+    minimized: bool,
 }
 
 impl Window {
-    pub fn new() -> Self {
+    pub fn new(resolution: (u32, u32)) -> Self {
 
         let wnd_class = windows::WndClass {
             class_name: "Octane",
@@ -44,8 +112,8 @@ impl Window {
             window_name: "Octane", 
             x: None,
             y: None,
-            width: Some(960),
-            height: Some(540),
+            width: Some(resolution.0 as i32),
+            height: Some(resolution.1 as i32),
             parent: None,
             queue,
         });
@@ -60,7 +128,11 @@ impl Window {
 
         let style_ex = windows::get_window_style_ex(hwnd);
 
-        Self { hwnd, fullscreen, placement, style, style_ex, queue }
+        let relative_mouse = false;
+
+        let minimized = false;
+
+        Self { hwnd, fullscreen, placement, style, style_ex, queue, relative_mouse, minimized }
     }
 
     pub fn show(&mut self) {
@@ -75,7 +147,7 @@ impl Window {
         windows::set_window_text(self.hwnd, title);
     }
 
-    pub fn fullscreen(&mut self, fullscreen: bool) {
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
         if self.fullscreen == fullscreen {
             return;
         }
@@ -109,6 +181,11 @@ impl Window {
         windows::show_cursor(show);
     }
 
+    /// Reports pointer movement as `RawMotion` deltas relative to the window center.
+    pub fn set_relative_mouse(&mut self, relative: bool) {
+        self.relative_mouse = relative;
+    }
+
     pub fn capture(&mut self) {
         let (x, y) = self.center();
         windows::set_cursor_pos(x as _, y as _);
@@ -137,12 +214,32 @@ impl Window {
                     },
                 windows::Event::PointerMotion { x, mut y } => {
                     y += if self.fullscreen { 0 } else { 11 };  
-                    Event::PointerMotion { x, y }
+
+                    if self.relative_mouse {
+                        let (cx, cy) = self.resolution();
+
+                        Event::RawMotion {
+                            dx: (x - cx as i32 / 2) as _,
+                            dy: (y - cy as i32 / 2) as _,
+                        }
+                    } else {
+                        Event::PointerMotion { x, y }
+                    }
                 }
                 windows::Event::FocusIn =>
-                    Event::FocusIn,
+                    Event::FocusGained,
                 windows::Event::FocusOut =>
-                    Event::FocusOut,
+                    Event::FocusLost,
+                windows::Event::Minimized => {
+                    self.minimized = true;
+                    Event::Minimized
+                }
+                //leaving the minimized state is reported before the resize itself
+                windows::Event::Resized { .. } if self.minimized => {
+                    self.minimized = false;
+                    queue_guard.insert(0, event);
+                    Event::Restored
+                }
                 windows::Event::Resized { resolution } =>
                     Event::Resized { resolution },
             })
@@ -151,6 +248,35 @@ impl Window {
         }
     }
 
+    /// Blocks until an event arrives, for tools that have nothing to do in between.
+    pub fn wait_event(&mut self) -> Event {
+        loop {
+            if let Some(event) = self.next_event() {
+                return event;
+            }
+
+            windows::wait_message();
+        }
+    }
+
+    /// Desktop scale relative to 96 dpi.
+    pub fn scale_factor(&self) -> f32 {
+        windows::get_dpi_for_window(self.hwnd) as f32 / 96.0
+    }
+
+    //TODO go through OpenClipboard and CF_UNICODETEXT
+    pub fn clipboard_get(&mut self) -> Option<String> {
+        None
+    }
+
+    pub fn clipboard_set(&mut self, _text: &str) {}
+
+    //TODO read pads through xinput, no gamepad events are sent until then
+    pub fn gamepads(&self) -> Vec<Gamepad> {
+        vec![]
+    }
+
+    /// Size of the client area in physical pixels.
     pub fn resolution(&self) -> (u32, u32) {
         let windows::Rect(left, top, right, bottom) = windows::get_client_rect(self.hwnd);
 
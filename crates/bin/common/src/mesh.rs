@@ -1,6 +1,11 @@
+use gpu::prelude::{Format, IndexType, InputRate, VertexInput};
+use math::prelude::Vector;
+
 use std::alloc;
-use std::fs;
-use std::io::{self, BufRead};
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
 use std::mem;
 use std::ptr;
 use std::slice;
@@ -12,142 +17,481 @@ pub struct Vertex {
     uvw: [f32; 3],
 }
 
-type Index = u16;
+impl Vertex {
+    /// Position, normal and uvw at `first_location` onwards, at the real field offsets so
+    /// pipelines follow the struct if its layout changes.
+    pub fn attribute_descriptions(binding: u32, first_location: u32) -> Vec<VertexInput> {
+        let attributes = [
+            mem::offset_of!(Vertex, position),
+            mem::offset_of!(Vertex, normal),
+            mem::offset_of!(Vertex, uvw),
+        ];
+
+        //the stride is taken from the furthest attribute, so trailing padding would be lost
+        debug_assert_eq!(
+            attributes.iter().max().unwrap() + mem::size_of::<[f32; 3]>(),
+            mem::size_of::<Vertex>()
+        );
+
+        attributes
+            .into_iter()
+            .zip(first_location..)
+            .map(|(offset, location)| VertexInput {
+                binding,
+                location,
+                format: Format::Rgb32Sfloat,
+                rate: InputRate::Vertex,
+                offset: offset as _,
+            })
+            .collect()
+    }
+}
+
+pub type Index = u32;
+
+/// Index storage of a mesh, narrowed to `u16` whenever every vertex is addressable by one.
+#[derive(Clone, Copy, Debug)]
+pub enum Indices<'a> {
+    Uint16(&'a [u16]),
+    Uint32(&'a [u32]),
+}
+
+impl Indices<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Uint16(indices) => indices.len(),
+            Self::Uint32(indices) => indices.len(),
+        }
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        match self {
+            Self::Uint16(_) => IndexType::Uint16,
+            Self::Uint32(_) => IndexType::Uint32,
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Index {
+        match self {
+            Self::Uint16(indices) => indices[i] as _,
+            Self::Uint32(indices) => indices[i],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ObjOptions {
+    /// Flips texture coordinates to `1 - v`, as obj uses a bottom-left origin.
+    pub flip_v: bool,
+}
+
+impl Default for ObjOptions {
+    fn default() -> Self {
+        Self { flip_v: true }
+    }
+}
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(io::Error),
+    Parse { line: usize, content: String },
+    Gltf(gltf::Error),
+    Unsupported(String),
+}
+
+impl From<io::Error> for MeshError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<gltf::Error> for MeshError {
+    fn from(error: gltf::Error) -> Self {
+        Self::Gltf(error)
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::Parse { line, content } => write!(f, "parse error on line {}: {}", line, content),
+            Self::Gltf(error) => write!(f, "gltf error: {}", error),
+            Self::Unsupported(feature) => write!(f, "unsupported feature: {}", feature),
+        }
+    }
+}
+
+impl error::Error for MeshError {}
 
 pub struct Mesh {
     vertex_count: usize,
     index_count: usize,
+    index_type: IndexType,
+    aabb: ([f32; 3], [f32; 3]),
+    bounding_sphere: ([f32; 3], f32),
     data: ptr::NonNull<u8>,
+    layout: alloc::Layout,
 }
 
 impl Mesh {
-    pub fn from_obj(file: fs::File) -> Self {
-        let buf_reader = io::BufReader::new(file);
+    /// Loads a mesh from a wavefront obj, reading it line by line so the file itself is
+    /// never held in memory. Positions, uvs, normals and triangles grow geometrically as
+    /// they are read, and the final vertex and index arrays are copied into the mesh
+    /// allocation once at the end, so peak memory is about twice the loaded geometry.
+    pub fn from_obj(reader: impl BufRead) -> Result<Self, MeshError> {
+        Self::from_obj_with_options(reader, ObjOptions::default())
+    }
 
+    pub fn from_obj_with_options(
+        mut reader: impl BufRead,
+        options: ObjOptions,
+    ) -> Result<Self, MeshError> {
         let mut vertices = vec![];
         let mut positions = vec![];
         let mut normals = vec![];
         let mut uvws = vec![];
         let mut indices = vec![];
 
-        //not a totally accurate obj reader. made to read a single cube
-        for line in buf_reader.lines() {
-            let line = line.expect("failed to read line");
-            let segments = line.split_whitespace().collect::<Vec<_>>();
+        //one line and one face buffer are reused for the whole file instead of allocating
+        //per line, segments are parsed straight from the line
+        let mut line = String::new();
+        let mut line_number = 0;
+        let mut corners = vec![];
 
-            if segments.len() == 0 {
-                continue;
+        //not a totally accurate obj reader. groups, materials and such are ignored
+        loop {
+            line.clear();
+
+            if reader.read_line(&mut line)? == 0 {
+                break;
             }
 
-            match segments[0] {
+            line_number += 1;
+
+            let mut segments = line.split_whitespace();
+
+            let Some(kind) = segments.next() else {
+                continue;
+            };
+
+            let parse_error = || MeshError::Parse {
+                line: line_number,
+                content: line.trim_end().to_owned(),
+            };
+
+            let parse_float = |segment: &str| segment.parse::<f32>().map_err(|_| parse_error());
+
+            //a missing segment is as malformed as an unparsable one
+            let mut next_float = || segments.next().map_or(Err(parse_error()), parse_float);
+
+            match kind {
                 "v" => {
-                    let position = [
-                        segments[1].parse::<f32>().expect("failed to parse float"),
-                        segments[2].parse::<f32>().expect("failed to parse float"),
-                        segments[3].parse::<f32>().expect("failed to parse float"),
-                    ];
+                    let position = [next_float()?, next_float()?, next_float()?];
 
                     positions.push(position);
                 }
                 "vt" => {
-                    let uvw = [
-                        segments[1].parse::<f32>().expect("failed to parse float"),
-                        segments[2].parse::<f32>().expect("failed to parse float"),
-                        segments[3].parse::<f32>().expect("failed to parse float"),
-                    ];
+                    let mut parse = || segments.next().map_or(Ok(0.0), parse_float);
+
+                    let u = parse()?;
+                    let v = parse()?;
+                    let w = parse()?;
+
+                    let v = if options.flip_v { 1.0 - v } else { v };
+
+                    let uvw = [u, v, w];
 
                     uvws.push(uvw);
                 }
                 "vn" => {
-                    let normal = [
-                        segments[1].parse::<f32>().expect("failed to parse float"),
-                        segments[2].parse::<f32>().expect("failed to parse float"),
-                        segments[3].parse::<f32>().expect("failed to parse float"),
-                    ];
+                    let normal = [next_float()?, next_float()?, next_float()?];
 
                     normals.push(normal);
                 }
                 "f" => {
+                    //obj indices start at 1
+                    let parse = |segment: Option<&str>, count: usize| match segment {
+                        None | Some("") => Ok(None),
+                        Some(segment) => match segment.parse::<isize>() {
+                            Ok(index) if index > 0 && index as usize <= count => {
+                                Ok(Some(index as usize - 1))
+                            }
+                            Ok(index) if index < 0 => Err(MeshError::Unsupported(
+                                "relative face indices".to_owned(),
+                            )),
+                            _ => Err(parse_error()),
+                        },
+                    };
+
                     let parse_index = |id: &str| {
-                        let y = id.split("/").collect::<Vec<_>>();
+                        let mut y = id.split('/');
 
-                        let i = y[0].parse::<usize>().unwrap_or_default();
-                        let j = y[1].parse::<usize>().unwrap_or_default();
-                        let k = y[2].parse::<usize>().unwrap_or_default();
+                        let i = parse(y.next(), positions.len())?.ok_or_else(parse_error)?;
+                        let j = parse(y.next(), uvws.len())?;
+                        let k = parse(y.next(), normals.len())?;
 
-                        //obj indices start at 1
-                        (i - 1, j - 1, k - 1)
+                        Ok::<_, MeshError>((i, j, k))
                     };
 
-                    let (i1, j1, k1) = parse_index(segments[1]);
-                    let (i2, j2, k2) = parse_index(segments[2]);
-                    let (i3, j3, k3) = parse_index(segments[3]);
+                    corners.clear();
 
-                    let vertex_1 = Vertex {
-                        position: positions[i1],
-                        uvw: uvws[j1],
-                        normal: normals[k1],
-                    };
+                    for segment in segments.by_ref() {
+                        corners.push(parse_index(segment)?);
+                    }
 
-                    let vertex_2 = Vertex {
-                        position: positions[i2],
-                        uvw: uvws[j2],
-                        normal: normals[k2],
-                    };
+                    if corners.len() < 3 {
+                        return Err(parse_error());
+                    }
 
-                    let vertex_3 = Vertex {
-                        position: positions[i3],
-                        uvw: uvws[j3],
-                        normal: normals[k3],
-                    };
+                    //fan triangulate polygons with more than three corners
+                    for c in 1..corners.len() - 1 {
+                        let triangle = [corners[0], corners[c], corners[c + 1]];
+
+                        let face_normal = {
+                            let p1 = Vector::<f32, 3>::new(positions[triangle[0].0]);
+                            let p2 = Vector::<f32, 3>::new(positions[triangle[1].0]);
+                            let p3 = Vector::<f32, 3>::new(positions[triangle[2].0]);
 
-                    vertices.push(vertex_1);
-                    vertices.push(vertex_2);
-                    vertices.push(vertex_3);
+                            *(p2 - p1).cross(p3 - p1).normalize()
+                        };
 
-                    let index_count = indices.len();
+                        for (i, j, k) in triangle {
+                            let vertex = Vertex {
+                                position: positions[i],
+                                uvw: j.map_or([0.0; 3], |j| uvws[j]),
+                                normal: k.map_or(face_normal, |k| normals[k]),
+                            };
 
-                    indices.push(index_count as Index + 0);
-                    indices.push(index_count as Index + 1);
-                    indices.push(index_count as Index + 2);
+                            indices.push(vertices.len() as Index);
+
+                            vertices.push(vertex);
+                        }
+                    }
                 }
                 _ => {}
             }
         }
 
-        Mesh::create(&vertices, &indices)
+        Ok(Mesh::create(&vertices, &indices))
+    }
+
+    /// Loads a mesh from a gltf or glb file. Buffers must be embedded, either in the glb
+    /// binary chunk or as data uris. All primitives of the mesh are merged into one, and
+    /// normals are recomputed if any primitive lacks them.
+    pub fn from_gltf(mut reader: impl Read, mesh_index: usize) -> Result<Self, MeshError> {
+        let mut bytes = vec![];
+
+        reader.read_to_end(&mut bytes)?;
+
+        let gltf = gltf::Gltf::from_slice(&bytes)?;
+
+        //there's no base path to resolve files against, only the reader
+        for buffer in gltf.document.buffers() {
+            if let gltf::buffer::Source::Uri(uri) = buffer.source() {
+                if !uri.starts_with("data:") {
+                    return Err(MeshError::Unsupported(format!("external buffer {}", uri)));
+                }
+            }
+        }
+
+        let buffers = gltf::import_buffers(&gltf.document, None, gltf.blob.clone())?;
+
+        let mesh = gltf
+            .document
+            .meshes()
+            .nth(mesh_index)
+            .ok_or_else(|| MeshError::Unsupported(format!("missing mesh {}", mesh_index)))?;
+
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        let mut missing_normals = false;
+
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                return Err(MeshError::Unsupported(format!(
+                    "primitive mode {:?}",
+                    primitive.mode()
+                )));
+            }
+
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()].0[..]));
+
+            let positions = reader
+                .read_positions()
+                .ok_or_else(|| MeshError::Unsupported("primitive without positions".to_owned()))?
+                .collect::<Vec<_>>();
+
+            let normals = reader
+                .read_normals()
+                .map(|normals| normals.collect::<Vec<_>>());
+
+            let uvs = reader
+                .read_tex_coords(0)
+                .map(|uvs| uvs.into_f32().collect::<Vec<_>>());
+
+            missing_normals |= normals.is_none();
+
+            let base = vertices.len() as Index;
+
+            for (i, &position) in positions.iter().enumerate() {
+                let normal = normals.as_ref().map_or([0.0; 3], |normals| normals[i]);
+                let uvw = uvs.as_ref().map_or([0.0; 3], |uvs| [uvs[i][0], uvs[i][1], 0.0]);
+
+                vertices.push(Vertex {
+                    position,
+                    normal,
+                    uvw,
+                });
+            }
+
+            match reader.read_indices() {
+                Some(primitive_indices) => {
+                    indices.extend(primitive_indices.into_u32().map(|i| base + i));
+                }
+                None => {
+                    indices.extend((0..positions.len() as Index).map(|i| base + i));
+                }
+            }
+        }
+
+        let mut mesh = Mesh::create(&vertices, &indices);
+
+        if missing_normals {
+            mesh.compute_normals(true);
+        }
+
+        Ok(mesh)
+    }
+
+    /// Merges vertices that are equal once quantized to `epsilon` and rewrites the indices.
+    /// An `epsilon` of zero merges only bitwise equal vertices. Returns the ratio of the
+    /// original vertex count to the unique vertex count.
+    pub fn index(&mut self, epsilon: f32) -> f32 {
+        let (vertices, indices) = self.get();
+
+        let quantize = |x: f32| {
+            if epsilon > 0.0 {
+                (x / epsilon).round() as i64
+            } else {
+                x.to_bits() as i64
+            }
+        };
+
+        let key = |vertex: &Vertex| {
+            let mut key = [0; 9];
+
+            for i in 0..3 {
+                key[i] = quantize(vertex.position[i]);
+                key[i + 3] = quantize(vertex.normal[i]);
+                key[i + 6] = quantize(vertex.uvw[i]);
+            }
+
+            key
+        };
+
+        let mut unique_vertices = vec![];
+        let mut remap = HashMap::<[i64; 9], Index>::new();
+
+        let new_indices = (0..indices.len())
+            .map(|i| {
+                let vertex = &vertices[indices.get(i) as usize];
+
+                *remap.entry(key(vertex)).or_insert_with(|| {
+                    unique_vertices.push(*vertex);
+
+                    unique_vertices.len() as Index - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let ratio = if !unique_vertices.is_empty() {
+            vertices.len() as f32 / unique_vertices.len() as f32
+        } else {
+            1.0
+        };
+
+        *self = Self::create(&unique_vertices, &new_indices);
+
+        ratio
     }
 
     pub fn create(vertices: &'_ [Vertex], indices: &'_ [Index]) -> Self {
+        let index_type = if vertices.len() <= u16::MAX as usize + 1 {
+            IndexType::Uint16
+        } else {
+            IndexType::Uint32
+        };
+
         let vertex_byte_len = vertices.len() * mem::size_of::<Vertex>();
-        let index_byte_len = indices.len() * mem::size_of::<Index>();
+        let index_byte_len = indices.len() * index_type.to_bytes();
         let byte_len = vertex_byte_len + index_byte_len;
 
-        let layout = alloc::Layout::array::<u8>(byte_len).expect("failed to create layout");
+        let layout = alloc::Layout::from_size_align(byte_len, mem::align_of::<Vertex>())
+            .expect("failed to create layout");
 
-        let data = match ptr::NonNull::new(unsafe { alloc::alloc(layout) }) {
-            Some(p) => p,
-            None => alloc::handle_alloc_error(layout),
+        //zero sized allocations are undefined, an empty mesh points nowhere instead
+        let data = if byte_len == 0 {
+            ptr::NonNull::<Vertex>::dangling().cast()
+        } else {
+            match ptr::NonNull::new(unsafe { alloc::alloc(layout) }) {
+                Some(p) => p,
+                None => alloc::handle_alloc_error(layout),
+            }
         };
 
         let (data_vertex, data_index) = unsafe {
             (
-                data.as_ptr().cast::<_>(),
-                data.as_ptr().add(vertex_byte_len).cast::<_>(),
+                data.as_ptr().cast::<Vertex>(),
+                data.as_ptr().add(vertex_byte_len),
             )
         };
 
         unsafe { ptr::copy(vertices.as_ptr(), data_vertex, vertices.len()) };
-        unsafe { ptr::copy(indices.as_ptr(), data_index, indices.len()) };
+
+        match index_type {
+            IndexType::Uint16 => {
+                let indices = indices.iter().map(|&i| i as u16).collect::<Vec<_>>();
+
+                unsafe { ptr::copy(indices.as_ptr(), data_index.cast::<u16>(), indices.len()) };
+            }
+            IndexType::Uint32 => {
+                unsafe { ptr::copy(indices.as_ptr(), data_index.cast::<u32>(), indices.len()) };
+            }
+        }
+
+        let mut aabb = if !vertices.is_empty() {
+            ([f32::MAX; 3], [f32::MIN; 3])
+        } else {
+            ([0.0; 3], [0.0; 3])
+        };
+
+        for vertex in vertices {
+            for i in 0..3 {
+                aabb.0[i] = aabb.0[i].min(vertex.position[i]);
+                aabb.1[i] = aabb.1[i].max(vertex.position[i]);
+            }
+        }
+
+        let center = (Vector::<f32, 3>::new(aabb.0) + Vector::new(aabb.1)) / 2.0;
+
+        let radius = vertices
+            .iter()
+            .map(|vertex| center.distance(&Vector::new(vertex.position)))
+            .fold(0.0, f32::max);
 
         Self {
             vertex_count: vertices.len(),
             index_count: indices.len(),
+            index_type,
+            aabb,
+            bounding_sphere: (*center, radius),
             data,
+            layout,
         }
     }
 
-    pub fn get(&self) -> (&'_ [Vertex], &'_ [Index]) {
+    pub fn get(&self) -> (&'_ [Vertex], Indices<'_>) {
         let vertices = unsafe {
             slice::from_raw_parts(
                 self.data
@@ -158,19 +502,92 @@ impl Mesh {
             )
         };
 
-        let indices = unsafe {
-            slice::from_raw_parts(
-                self.data
-                    .as_ptr()
-                    .add(self.get_index_offset())
-                    .cast::<Index>(),
-                self.index_count,
-            )
+        let data_index = unsafe { self.data.as_ptr().add(self.get_index_offset()) };
+
+        let indices = match self.index_type {
+            IndexType::Uint16 => Indices::Uint16(unsafe {
+                slice::from_raw_parts(data_index.cast::<u16>(), self.index_count)
+            }),
+            IndexType::Uint32 => Indices::Uint32(unsafe {
+                slice::from_raw_parts(data_index.cast::<u32>(), self.index_count)
+            }),
         };
 
         (vertices, indices)
     }
 
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Returns the minimum and maximum corner of the box enclosing every vertex.
+    pub fn aabb(&self) -> ([f32; 3], [f32; 3]) {
+        self.aabb
+    }
+
+    /// Returns the center and radius of a sphere enclosing every vertex, centered on the aabb.
+    pub fn bounding_sphere(&self) -> ([f32; 3], f32) {
+        self.bounding_sphere
+    }
+
+    /// Replaces the normals with ones computed from the triangle faces.
+    /// Smooth normals are area weighted across every vertex sharing a position,
+    /// flat normals duplicate the vertices of each face.
+    pub fn compute_normals(&mut self, smooth: bool) {
+        let (vertices, indices) = self.get();
+
+        let mut vertices = vertices.to_vec();
+        let indices = (0..indices.len()).map(|i| indices.get(i)).collect::<Vec<_>>();
+
+        let position_key = |vertex: &Vertex| vertex.position.map(f32::to_bits);
+
+        //the cross product's length is twice the triangle area, which weights the sum
+        let face_normal = |triangle: &[Index]| {
+            let p1 = Vector::<f32, 3>::new(vertices[triangle[0] as usize].position);
+            let p2 = Vector::<f32, 3>::new(vertices[triangle[1] as usize].position);
+            let p3 = Vector::<f32, 3>::new(vertices[triangle[2] as usize].position);
+
+            (p2 - p1).cross(p3 - p1)
+        };
+
+        if smooth {
+            let mut normals = HashMap::<[u32; 3], Vector<f32, 3>>::new();
+
+            for triangle in indices.chunks_exact(3) {
+                let normal = face_normal(triangle);
+
+                for &i in triangle {
+                    *normals
+                        .entry(position_key(&vertices[i as usize]))
+                        .or_default() += normal;
+                }
+            }
+
+            for vertex in &mut vertices {
+                vertex.normal = *normals[&position_key(vertex)].normalize();
+            }
+
+            *self = Self::create(&vertices, &indices);
+        } else {
+            let mut flat_vertices = Vec::with_capacity(indices.len());
+
+            for triangle in indices.chunks_exact(3) {
+                let normal = *face_normal(triangle).normalize();
+
+                for &i in triangle {
+                    flat_vertices.push(Vertex {
+                        normal,
+                        ..vertices[i as usize]
+                    });
+                }
+            }
+
+            let flat_indices = (0..flat_vertices.len() as Index).collect::<Vec<_>>();
+
+            *self = Self::create(&flat_vertices, &flat_indices);
+        }
+    }
+
     #[inline]
     fn get_vertex_offset(&self) -> usize {
         0
@@ -183,5 +600,380 @@ impl Mesh {
 }
 
 impl Drop for Mesh {
-    fn drop(&mut self) {}
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { alloc::dealloc(self.data.as_ptr(), self.layout) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TETRAHEDRON: &str = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+f 1 3 2
+f 1 2 4
+f 1 4 3
+f 2 3 4
+";
+
+    const CUBE: &str = "\
+v 0 0 0
+v 0 0 1
+v 0 1 0
+v 0 1 1
+v 1 0 0
+v 1 0 1
+v 1 1 0
+v 1 1 1
+vn -1 0 0
+vn 1 0 0
+vn 0 -1 0
+vn 0 1 0
+vn 0 0 -1
+vn 0 0 1
+f 1//1 2//1 4//1 3//1
+f 5//2 7//2 8//2 6//2
+f 1//3 5//3 6//3 2//3
+f 3//4 4//4 8//4 7//4
+f 1//5 3//5 7//5 5//5
+f 2//6 6//6 8//6 4//6
+";
+
+    //the second face has no uvs
+    const QUAD: &str = "\
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+f 1/1 2/2 3/3 4/4
+f 1 2 3
+";
+
+    //one triangle with normals and uvs, indexed once by u16 and once by u32. the second mesh
+    //draws it twice, through the u32 and then the u16 indices
+    const TRIANGLE_GLTF: &str = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{
+            "byteLength": 116,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAABAAIAAAAAAAAAAQAAAAIAAAA="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 72, "byteLength": 24 },
+            { "buffer": 0, "byteOffset": 96, "byteLength": 6 },
+            { "buffer": 0, "byteOffset": 104, "byteLength": 12 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+              "min": [0, 0, 0], "max": [1, 1, 0] },
+            { "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" },
+            { "bufferView": 2, "componentType": 5126, "count": 3, "type": "VEC2" },
+            { "bufferView": 3, "componentType": 5123, "count": 3, "type": "SCALAR" },
+            { "bufferView": 4, "componentType": 5125, "count": 3, "type": "SCALAR" }
+        ],
+        "meshes": [
+            { "primitives": [
+                { "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }, "indices": 3 }
+            ] },
+            { "primitives": [
+                { "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }, "indices": 4 },
+                { "attributes": { "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }, "indices": 3 }
+            ] }
+        ]
+    }"#;
+
+    //every normal of a convex mesh points away from its center
+    fn assert_outward(mesh: &Mesh) {
+        let (vertices, _) = mesh.get();
+
+        let center = Vector::<f32, 3>::new([0.25; 3]);
+
+        for vertex in vertices {
+            let outward = Vector::new(vertex.position) - center;
+
+            assert!(Vector::new(vertex.normal).dot(outward) > 0.0, "{:?}", vertex);
+        }
+    }
+
+    #[test]
+    fn compute_normals_tetrahedron() {
+        let mut mesh = Mesh::from_obj(TETRAHEDRON.as_bytes()).unwrap();
+
+        mesh.compute_normals(true);
+
+        assert_eq!(mesh.get().0.len(), 12);
+        assert_outward(&mesh);
+
+        mesh.compute_normals(false);
+
+        assert_eq!(mesh.get().0.len(), 12);
+        assert_outward(&mesh);
+    }
+
+    #[test]
+    fn index_cube_soup() {
+        let mut mesh = Mesh::from_obj(CUBE.as_bytes()).unwrap();
+
+        let soup = {
+            let (vertices, indices) = mesh.get();
+
+            (0..indices.len())
+                .map(|i| vertices[indices.get(i) as usize])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(soup.len(), 36);
+
+        assert_eq!(mesh.index(0.0), 1.5);
+
+        let (vertices, indices) = mesh.get();
+
+        assert_eq!(vertices.len(), 24);
+        assert_eq!(indices.len(), 36);
+
+        for (i, vertex) in soup.iter().enumerate() {
+            let indexed = vertices[indices.get(i) as usize];
+
+            assert_eq!(indexed.position, vertex.position);
+            assert_eq!(indexed.normal, vertex.normal);
+        }
+    }
+
+    #[test]
+    fn from_obj_empty() {
+        let mesh = Mesh::from_obj("# nothing here\n".as_bytes()).unwrap();
+
+        let (vertices, indices) = mesh.get();
+
+        assert!(vertices.is_empty());
+        assert_eq!(indices.len(), 0);
+    }
+
+    #[test]
+    fn create_wide_indices() {
+        let count = u16::MAX as usize + 2;
+
+        let vertices = (0..count)
+            .map(|i| Vertex {
+                position: [i as f32, 0.0, 0.0],
+                normal: [0.0; 3],
+                uvw: [0.0; 3],
+            })
+            .collect::<Vec<_>>();
+
+        let indices = (0..count as Index).rev().collect::<Vec<_>>();
+
+        let mesh = Mesh::create(&vertices, &indices);
+
+        assert_eq!(mesh.index_type(), IndexType::Uint32);
+        assert!(matches!(mesh.get().1, Indices::Uint32(_)));
+        assert_eq!(mesh.get().1.get(0), count as Index - 1);
+
+        //one vertex less and the last index still fits in a u16
+        let mesh = Mesh::create(&vertices[1..], &indices[1..]);
+
+        assert_eq!(mesh.index_type(), IndexType::Uint16);
+        assert_eq!(mesh.get().1.get(0), u16::MAX as Index);
+        assert_eq!(mesh.get().1.get(indices.len() - 2), 0);
+    }
+
+    #[test]
+    fn from_obj_quad_uvs() {
+        let uvs = |flip_v| {
+            let mesh =
+                Mesh::from_obj_with_options(QUAD.as_bytes(), ObjOptions { flip_v }).unwrap();
+
+            let (vertices, indices) = mesh.get();
+
+            (0..indices.len())
+                .map(|i| {
+                    let uvw = vertices[indices.get(i) as usize].uvw;
+                    [uvw[0], uvw[1]]
+                })
+                .collect::<Vec<_>>()
+        };
+
+        //the quad is fanned into 1 2 3 and 1 3 4
+        assert_eq!(
+            uvs(false),
+            [
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [1.0, 1.0],
+                [0.0, 0.0],
+                [1.0, 1.0],
+                [0.0, 1.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+            ]
+        );
+        assert_eq!(
+            uvs(true),
+            [
+                [0.0, 1.0],
+                [1.0, 1.0],
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+                [0.0, 0.0],
+            ]
+        );
+        assert!(ObjOptions::default().flip_v);
+    }
+
+    #[test]
+    fn from_obj_normals() {
+        let mesh = Mesh::from_obj(CUBE.as_bytes()).unwrap();
+
+        let (vertices, indices) = mesh.get();
+
+        let faces = [
+            [-1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, -1.0],
+            [0.0, 0.0, 1.0],
+        ];
+
+        assert_eq!(indices.len(), 36);
+
+        //each quad is two triangles, every corner with the normal its face names
+        for i in 0..indices.len() {
+            assert_eq!(vertices[indices.get(i) as usize].normal, faces[i / 6]);
+        }
+
+        //without any `vn` the face normal is computed from the winding
+        let mesh = Mesh::from_obj(TETRAHEDRON.as_bytes()).unwrap();
+
+        let (vertices, _) = mesh.get();
+
+        for vertex in &vertices[..3] {
+            assert_eq!(vertex.normal, [0.0, 0.0, -1.0]);
+        }
+    }
+
+    fn parse_error_line(obj: &str) -> Option<(usize, String)> {
+        match Mesh::from_obj(obj.as_bytes()) {
+            Err(MeshError::Parse { line, content }) => Some((line, content)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn from_obj_parse_errors() {
+        let bad_float = "v 0 0 0\n\nv 1 x 0\n";
+        let out_of_range = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        let too_few = "v 0 0 0\nv 1 0 0\n# a line\nf 1 2\n";
+
+        assert_eq!(parse_error_line(bad_float), Some((3, "v 1 x 0".to_owned())));
+        assert_eq!(parse_error_line(out_of_range), Some((4, "f 1 2 4".to_owned())));
+        assert_eq!(parse_error_line(too_few), Some((4, "f 1 2".to_owned())));
+
+        assert!(matches!(
+            Mesh::from_obj("v 0 0 0\nf -1 -1 -1\n".as_bytes()),
+            Err(MeshError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn cube_bounds() {
+        let mesh = Mesh::from_obj(CUBE.as_bytes()).unwrap();
+
+        assert_eq!(mesh.aabb(), ([0.0; 3], [1.0; 3]));
+
+        let (center, radius) = mesh.bounding_sphere();
+
+        //the corners are the furthest out, half the diagonal away
+        assert_eq!(center, [0.5; 3]);
+        assert!((radius - 0.75_f32.sqrt()).abs() < 1e-6);
+
+        let empty = Mesh::create(&[], &[]);
+
+        assert_eq!(empty.aabb(), ([0.0; 3], [0.0; 3]));
+        assert_eq!(empty.bounding_sphere(), ([0.0; 3], 0.0));
+    }
+
+    #[test]
+    fn from_gltf_triangle() {
+        let mesh = Mesh::from_gltf(TRIANGLE_GLTF.as_bytes(), 0).unwrap();
+
+        let (vertices, indices) = mesh.get();
+
+        let positions = vertices.iter().map(|v| v.position).collect::<Vec<_>>();
+        let uvs = vertices.iter().map(|v| v.uvw).collect::<Vec<_>>();
+
+        assert_eq!(
+            positions,
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(uvs, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+
+        for vertex in vertices {
+            assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        }
+
+        assert_eq!(
+            (0..indices.len())
+                .map(|i| indices.get(i))
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn from_gltf_merges_primitives() {
+        let mesh = Mesh::from_gltf(TRIANGLE_GLTF.as_bytes(), 1).unwrap();
+
+        let (vertices, indices) = mesh.get();
+
+        assert_eq!(vertices.len(), 6);
+
+        //the u16 indices of the second primitive are offset past the first one's vertices
+        assert_eq!(
+            (0..indices.len())
+                .map(|i| indices.get(i))
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 5]
+        );
+
+        for (first, second) in vertices[..3].iter().zip(&vertices[3..]) {
+            assert_eq!(first.position, second.position);
+            assert_eq!(first.normal, second.normal);
+            assert_eq!(first.uvw, second.uvw);
+        }
+
+        assert!(matches!(
+            Mesh::from_gltf(TRIANGLE_GLTF.as_bytes(), 2),
+            Err(MeshError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn from_gltf_external_buffer() {
+        let gltf = r#"{
+            "asset": { "version": "2.0" },
+            "buffers": [{ "byteLength": 116, "uri": "triangle.bin" }]
+        }"#;
+
+        assert!(matches!(
+            Mesh::from_gltf(gltf.as_bytes(), 0),
+            Err(MeshError::Unsupported(_))
+        ));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::bucket::Bucket;
-use crate::mesh::{Mesh, Vertex};
+use crate::mesh::{Indices, Mesh, Vertex};
 use crate::octree::{Node, Octree, SparseOctree};
 use crate::voxel::Id::*;
 use crate::voxel::Voxel;
@@ -12,7 +12,7 @@ use std::collections::{HashMap, HashSet};
 use std::error;
 use std::fmt;
 use std::fs;
-use std::io::Read;
+use std::io::{self, Read};
 use std::iter;
 use std::mem;
 use std::path::PathBuf;
@@ -22,6 +22,8 @@ use std::time;
 use log::{error, info, trace, warn};
 use raw_window_handle::HasRawWindowHandle;
 
+pub use gpu::prelude::PresentMode;
+
 pub const CHUNK_SIZE: usize = 8;
 //temporary for here for now.
 #[derive(Default, Clone, Copy, Debug)]
@@ -45,7 +47,22 @@ pub struct RenderSettings {
 pub struct RendererInfo<'a> {
     pub window: &'a dyn HasRawWindowHandle,
     pub render_distance: u32,
-    pub hq4x: String,
+    //most preferred first, the first one the surface supports is used and fifo otherwise
+    pub present_modes: Vec<PresentMode>,
+    //compiled shaders, textures and meshes
+    pub assets: PathBuf,
+    //glsl sources the shaders are compiled from
+    pub resources: PathBuf,
+    //part of the gpu name to use instead of the best scoring one, e.g. on hybrid graphics
+    pub preferred_device: Option<String>,
+}
+
+pub struct HeadlessRendererInfo {
+    pub resolution: (u32, u32),
+    pub render_distance: u32,
+    pub assets: PathBuf,
+    pub resources: PathBuf,
+    pub preferred_device: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -61,16 +78,39 @@ impl fmt::Display for Condition {
 }
 
 #[derive(Clone, Copy, Debug)]
-pub enum Error {}
+pub enum Error {
+    //the renderer is unusable until `Vulkan::recreate_device` is called
+    DeviceLost,
+}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error")
+        match self {
+            Self::DeviceLost => write!(f, "device lost"),
+        }
     }
 }
 
 impl error::Error for Error {}
 
+#[derive(Clone, Copy, Debug)]
+pub enum CaptureError {
+    //the renderer draws to a window, see `Vulkan::init_headless`
+    NotHeadless,
+    NoFrame,
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotHeadless => write!(f, "only headless frames can be captured"),
+            Self::NoFrame => write!(f, "no frame has been drawn"),
+        }
+    }
+}
+
+impl error::Error for CaptureError {}
+
 type Result = std::result::Result<Condition, Error>;
 
 pub trait Renderer {
@@ -90,6 +130,30 @@ pub struct Object<'a> {
     pub model: Matrix<f32, 4, 4>,
 }
 
+//one indexed draw of a mesh in the data buffer over its own range of the instance buffer,
+//so meshes with different instance counts can share both buffers
+#[derive(Clone, Copy, Debug)]
+struct MeshDraw {
+    first_index: u32,
+    index_count: u32,
+    vertex_offset: i32,
+    first_instance: u32,
+    instance_count: u32,
+}
+
+//`(first_instance, instance_count)` of each mesh, with their instances packed back to back
+//in the instance buffer in the same order
+fn instance_ranges(instance_counts: &[u32]) -> Vec<(u32, u32)> {
+    instance_counts
+        .iter()
+        .scan(0, |first_instance, &instance_count| {
+            let range = (*first_instance, instance_count);
+            *first_instance += instance_count;
+            Some(range)
+        })
+        .collect()
+}
+
 const SMALL_BUFFER: usize = 1_000_000;
 const BIG_BUFFER: usize = 1_000_000_000;
 
@@ -100,11 +164,19 @@ const VERTEX_OFFSET: u64 = 2048;
 const INDEX_OFFSET: u64 = 4096;
 const BITFIELD_OFFSET: u64 = 65536;
 
+//staging regions, below INSTANCE_STAGING it mirrors the data buffer. copies out of it run in
+//the background, so each region is written once per frame after waiting for the last frame's
+const INSTANCE_STAGING: u64 = SMALL_BUFFER as u64;
+const OCTREE_STAGING: u64 = 64 * SMALL_BUFFER as u64;
+
 pub struct Vulkan {
     index_count: usize,
-    instance_count: usize,
+    index_type: IndexType,
+    //recorded in order, each with its own instance range
+    draws: Vec<MeshDraw>,
     settings: Bucket<RenderSettings>,
     last_camera: Option<Camera>,
+    swapchain_data: Option<VulkanSwapchainData>,
     render_data: Option<VulkanRenderData>,
     present_fragment_shader: Shader,
     postfx_fragment_shader: Shader,
@@ -117,46 +189,122 @@ pub struct Vulkan {
     data_buffer: Buffer,
     staging_buffer: Buffer,
     device: Device,
-    surface: Surface,
-    context: Context,
+    device_lost: bool,
+    //kept so a recreated device can choose again, it may support different modes
+    present_modes: Vec<PresentMode>,
+    present_mode: PresentMode,
+    assets: PathBuf,
+    resources: PathBuf,
+    preferred_device: Option<String>,
+    surface: Option<Surface>,
+    //shared so a recreated device can reuse the instance after the old renderer is dropped
+    context: Rc<Context>,
 }
 
+//render passes and pipelines, only rebuilt when shaders change
 pub struct VulkanRenderData {
-    graphics_color: Vec<Image>,
-    graphics_occlusion: Vec<Image>,
-    graphics_framebuffers: Vec<Framebuffer>,
+    image_count: usize,
     graphics_prepass_pipeline: Pipeline,
     graphics_raycast_pipeline: Pipeline,
     graphics_render_pass: RenderPass,
-    postfx_color: Vec<Image>,
-    postfx_framebuffers: Vec<Framebuffer>,
     postfx_pipeline: Pipeline,
     postfx_render_pass: RenderPass,
-    present_framebuffers: Vec<Framebuffer>,
     present_pipeline: Pipeline,
     present_render_pass: RenderPass,
+}
+
+//everything sized to the swapchain, rebuilt on resize
+pub struct VulkanSwapchainData {
+    graphics_color: Vec<Image>,
+    graphics_occlusion: Vec<Image>,
+    graphics_framebuffers: Vec<Framebuffer>,
+    postfx_color: Vec<Image>,
+    postfx_framebuffers: Vec<Framebuffer>,
+    present_framebuffers: Vec<Framebuffer>,
     depth: Image,
-    swapchain: Swapchain,
-    swapchain_images: Vec<Image>,
+    //none when headless, the present pass then renders into an offscreen image
+    swapchain: Option<Swapchain>,
+    present_images: Vec<Image>,
 }
 
 impl Vulkan {
     pub fn init(info: RendererInfo<'_>) -> Self {
-        let context = Context::start();
+        let context = Rc::new(Context::start());
 
         let surface = Surface::new(SurfaceInfo {
             context: &context,
             window: &info.window,
         });
 
+        Self::new(
+            context,
+            Some(surface),
+            info.present_modes,
+            info.render_distance,
+            info.assets,
+            info.resources,
+            info.preferred_device,
+        )
+    }
+
+    /// Renders into an offscreen image instead of a swapchain, read it back with
+    /// `capture_frame` after drawing.
+    pub fn init_headless(info: HeadlessRendererInfo) -> Self {
+        let context = Rc::new(Context::start_headless());
+
+        //there is nothing to present to, the mode is never used
+        let mut vulkan = Self::new(
+            context,
+            None,
+            vec![],
+            info.render_distance,
+            info.assets,
+            info.resources,
+            info.preferred_device,
+        );
+
+        let (width, height) = info.resolution;
+
+        vulkan.settings.resolution = Vector::<u32, 4>::new([width, height, 0, 0]);
+
+        vulkan
+    }
+
+    fn new(
+        context: Rc<Context>,
+        surface: Option<Surface>,
+        present_modes: Vec<PresentMode>,
+        render_distance: u32,
+        assets: PathBuf,
+        resources: PathBuf,
+        preferred_device: Option<String>,
+    ) -> Self {
         let mut device = Device::choose_best(DeviceInfo {
             context: &context,
-            surface: &surface,
+            surface: surface.as_ref(),
+            preferred_device: preferred_device.as_deref(),
         });
 
+        let present_mode = match &surface {
+            Some(surface) => {
+                let supported = surface.present_modes(&device);
+
+                let present_mode = present_modes
+                    .iter()
+                    .copied()
+                    .find(|present_mode| supported.contains(present_mode))
+                    .unwrap_or(PresentMode::Fifo);
+
+                info!("using {:?} present mode\n", present_mode);
+
+                present_mode
+            }
+            None => PresentMode::Fifo,
+        };
+
         let mut staging_buffer = Buffer::new(BufferInfo {
             device: &device,
-            usage: BufferUsage::TRANSFER_SRC,
+            usage: BufferUsage::TRANSFER_SRC | BufferUsage::TRANSFER_DST,
             properties: MemoryProperties::HOST_VISIBLE,
             size: BIG_BUFFER,
         });
@@ -195,7 +343,9 @@ impl Vulkan {
 
         use image::io::Reader as ImageReader;
 
-        let hq4x = ImageReader::open(info.hq4x)
+        let device_lost = false;
+
+        let hq4x = ImageReader::open(assets.join("hq4x.png"))
             .expect("failed to open hq4x")
             .decode()
             .expect("failed to decode hq4x");
@@ -215,15 +365,12 @@ impl Vulkan {
             dst_extent: (256, 256, 1),
         });
 
-        let mut base_path = std::env::current_exe().expect("failed to load path of executable");
-        base_path.pop();
-        let base_path_str = base_path.to_str().unwrap();
-        let base_path_str = base_path_str.replace("\\\\?\\", "");
+        device.wait_for_transfers();
 
-        let cube = format!("{}/assets/cube.obj", base_path_str);
-        let cube_obj = fs::File::open(cube).expect("failed to open obj");
+        let cube_obj = fs::File::open(assets.join("cube.obj")).expect("failed to open obj");
 
-        let mut cube = Mesh::from_obj(cube_obj);
+        let mut cube =
+            Mesh::from_obj(io::BufReader::new(cube_obj)).expect("failed to load obj");
 
         let (cube_vertices, cube_indices) = cube.get();
 
@@ -240,25 +387,27 @@ impl Vulkan {
             size: (cube_vertices.len() * mem::size_of::<Vertex>()) as u64,
         });
 
-        staging_buffer.write(BufferWrite {
-            offset: 0,
-            data: &cube_indices[..],
-        });
+        device.wait_for_transfers();
+
+        match cube_indices {
+            Indices::Uint16(data) => staging_buffer.write(BufferWrite { offset: 0, data }),
+            Indices::Uint32(data) => staging_buffer.write(BufferWrite { offset: 0, data }),
+        }
 
         device.copy_buffer_to_buffer(BufferCopy {
             from: &staging_buffer,
             to: &mut data_buffer,
             src: 0,
             dst: INDEX_OFFSET,
-            size: (cube_indices.len() * mem::size_of::<u16>()) as u64,
+            size: (cube_indices.len() * cube.index_type().to_bytes()) as u64,
         });
 
         let graphics_vertex_shader = Shader::new(ShaderInfo {
             device: &device,
             entry: "main",
             input: ShaderInput::Glsl {
-                asset: PathBuf::from(format!("{}/assets/voxel.vert.spirv", base_path_str)),
-                resource: PathBuf::from(format!("{}/resources/voxel.vert.glsl", base_path_str)),
+                asset: assets.join("voxel.vert.spirv"),
+                resource: resources.join("voxel.vert.glsl"),
             },
         });
 
@@ -266,8 +415,8 @@ impl Vulkan {
             device: &device,
             entry: "main",
             input: ShaderInput::Glsl {
-                asset: PathBuf::from(format!("{}/assets/voxel.frag.spirv", base_path_str)),
-                resource: PathBuf::from(format!("{}/resources/voxel.frag.glsl", base_path_str)),
+                asset: assets.join("voxel.frag.spirv"),
+                resource: resources.join("voxel.frag.glsl"),
             },
         });
 
@@ -275,11 +424,8 @@ impl Vulkan {
             device: &device,
             entry: "main",
             input: ShaderInput::Glsl {
-                asset: PathBuf::from(format!("{}/assets/fullscreen.vert.spirv", base_path_str)),
-                resource: PathBuf::from(format!(
-                    "{}/resources/fullscreen.vert.glsl",
-                    base_path_str
-                )),
+                asset: assets.join("fullscreen.vert.spirv"),
+                resource: resources.join("fullscreen.vert.glsl"),
             },
         });
 
@@ -287,8 +433,8 @@ impl Vulkan {
             device: &device,
             entry: "main",
             input: ShaderInput::Glsl {
-                asset: PathBuf::from(format!("{}/assets/postfx.frag.spirv", base_path_str)),
-                resource: PathBuf::from(format!("{}/resources/postfx.frag.glsl", base_path_str)),
+                asset: assets.join("postfx.frag.spirv"),
+                resource: resources.join("postfx.frag.glsl"),
             },
         });
 
@@ -296,8 +442,8 @@ impl Vulkan {
             device: &device,
             entry: "main",
             input: ShaderInput::Glsl {
-                asset: PathBuf::from(format!("{}/assets/present.frag.spirv", base_path_str)),
-                resource: PathBuf::from(format!("{}/resources/present.frag.glsl", base_path_str)),
+                asset: assets.join("present.frag.spirv"),
+                resource: resources.join("present.frag.glsl"),
             },
         });
 
@@ -305,15 +451,23 @@ impl Vulkan {
 
         let settings = Bucket::new(RenderSettings {
             resolution: Vector::new([944, 501, 0, 0]),
-            render_distance: info.render_distance,
+            render_distance,
         });
 
+        let swapchain_data = None;
+
         let render_data = None;
 
         Self {
             context,
             surface,
+            present_modes,
+            present_mode,
+            assets,
+            resources,
+            preferred_device,
             device,
+            device_lost,
             staging_buffer,
             data_buffer,
             instance_buffer,
@@ -324,11 +478,13 @@ impl Vulkan {
             fullscreen_vertex_shader,
             postfx_fragment_shader,
             present_fragment_shader,
+            swapchain_data,
             render_data,
             settings,
             last_camera,
             index_count: cube_indices.len(),
-            instance_count: 0,
+            index_type: cube.index_type(),
+            draws: vec![],
         }
     }
 }
@@ -338,6 +494,9 @@ static mut DUMMY: bool = true;
 impl Renderer for Vulkan {
     fn draw(&mut self, batch: Batch) -> Result {
         if unsafe { DUMMY } {
+            //usually long done, the staging buffer is only rewritten after this
+            self.device.wait_for_transfers();
+
             let cam_pos = {
                 let mut cam_pos = batch.camera.model[3].resize();
 
@@ -393,19 +552,33 @@ impl Renderer for Vulkan {
                 });
 
                 self.staging_buffer.write(BufferWrite {
-                    offset: 0,
+                    offset: INSTANCE_STAGING,
                     data: &instance_data[..],
                 });
 
                 self.device.copy_buffer_to_buffer(BufferCopy {
                     from: &self.staging_buffer,
                     to: &mut self.instance_buffer,
-                    src: 0,
+                    src: INSTANCE_STAGING,
                     dst: 0,
                     size: (instance_data.len() * mem::size_of::<Vector<u32, 3>>()) as u64,
                 });
 
-                self.instance_count = instance_data.len();
+                //a single cube mesh for now, replicated over every chunk
+                let meshes = [(0, self.index_count as u32, 0)];
+                let instance_counts = [instance_data.len() as u32];
+
+                self.draws = meshes
+                    .into_iter()
+                    .zip(instance_ranges(&instance_counts))
+                    .map(|((first_index, index_count, vertex_offset), instances)| MeshDraw {
+                        first_index,
+                        index_count,
+                        vertex_offset,
+                        first_instance: instances.0,
+                        instance_count: instances.1,
+                    })
+                    .collect();
             }
 
             self.last_camera = Some(batch.camera);
@@ -452,7 +625,7 @@ impl Renderer for Vulkan {
             });
 
             self.staging_buffer.write(BufferWrite {
-                offset: 0,
+                offset: OCTREE_STAGING,
                 data: &[
                     batch.objects[0].data.size() as u32,
                     batch.objects[0].data.nodes().len() as u32,
@@ -464,14 +637,14 @@ impl Renderer for Vulkan {
             let octree_bytes = 2 * mem::size_of::<u32>()
                 + batch.objects[0].data.nodes().len() * mem::size_of::<crate::octree::Node>();
             self.staging_buffer.write(BufferWrite {
-                offset: (2 * mem::size_of::<u32>()) as u64,
+                offset: OCTREE_STAGING + (2 * mem::size_of::<u32>()) as u64,
                 data: batch.objects[0].data.nodes(),
             });
 
             self.device.copy_buffer_to_buffer(BufferCopy {
                 from: &self.staging_buffer,
                 to: &mut self.octree_buffer,
-                src: 0,
+                src: OCTREE_STAGING,
                 dst: 0,
                 size: octree_bytes as u64,
 
@@ -514,7 +687,18 @@ impl Renderer for Vulkan {
                     ShaderError::Compilation(_, message) => {
                         error!("Failed to compile shader: \n {}", message);
                     }
-                    _ => panic!("unexpected error refreshing shader"),
+                    ShaderError::InvalidSpirv(path) => {
+                        error!("Failed to load shader: invalid spirv in {}\n", path.display());
+                    }
+                    ShaderError::Io(path, kind) => {
+                        error!("Failed to read shader {}: {:?}\n", path.display(), kind);
+                    }
+                    ShaderError::Vulkan(err) => {
+                        error!("Failed to create shader module: {:?}\n", err);
+                    }
+                    ShaderError::InvalidResource => {
+                        error!("Failed to compile shader: unknown shader kind\n");
+                    }
                 },
             };
 
@@ -525,29 +709,57 @@ impl Renderer for Vulkan {
             load_shader(&mut self.present_fragment_shader);
 
             if load_graphics {
-                info!("Loading pipeline\n");
-                self.render_data = Some(VulkanRenderData::load(self));
+                if let Some(swapchain_data) = &self.swapchain_data {
+                    info!("Loading pipeline\n");
+                    let image_count = swapchain_data.present_images.len();
+                    self.render_data = Some(VulkanRenderData::load(self, image_count));
+                    //new pipelines come with empty descriptor sets
+                    unsafe { DUMMY = true };
+                }
                 return Ok(Condition::Retry);
             }
         }
 
-        self.device.synchronize();
+        if self.swapchain_data.is_none() {
+            self.load_swapchain();
+        }
 
-        let render_data = self.render_data.as_mut().unwrap();
+        if self.device_lost {
+            return Err(Error::DeviceLost);
+        }
 
-        let image_index = match render_data.swapchain.acquire() {
-            Ok(i) => i,
-            Err(e) => {
-                warn!("Failed to acquire next image\n");
-                return Ok(Condition::Retry);
+        //headless frames are waited on as they are submitted
+        if self.surface.is_some() {
+            if let Err(gpu::prelude::Error::DeviceLost) = self.device.synchronize() {
+                self.device_lost = true;
+                return Err(Error::DeviceLost);
             }
+        }
+
+        let render_data = self.render_data.as_mut().unwrap();
+
+        let swapchain_data = self.swapchain_data.as_mut().unwrap();
+
+        let image_index = match &mut swapchain_data.swapchain {
+            Some(swapchain) => match swapchain.acquire() {
+                Ok(i) => i,
+                Err(gpu::prelude::Error::DeviceLost) => {
+                    self.device_lost = true;
+                    return Err(Error::DeviceLost);
+                }
+                Err(e) => {
+                    warn!("Failed to acquire next image\n");
+                    return Ok(Condition::Retry);
+                }
+            },
+            None => 0,
         };
 
-        let graphics_color = &render_data.graphics_color[image_index as usize];
-        let postfx_color = &render_data.postfx_color[image_index as usize];
+        let graphics_color = &swapchain_data.graphics_color[image_index as usize];
+        let postfx_color = &swapchain_data.postfx_color[image_index as usize];
         if unsafe { DUMMY } {
             println!("DUMMY");
-            for image_index in 0..render_data.swapchain_images.len() as u32 {
+            for image_index in 0..swapchain_data.present_images.len() as u32 {
             render_data.graphics_prepass_pipeline.bind(
                 image_index,
                 &[
@@ -636,13 +848,13 @@ impl Renderer for Vulkan {
                             binding: 2,
                             ty: DescriptorType::StorageImage,
                             layout: ImageLayout::General,
-                            image: &render_data.graphics_occlusion[image_index as usize],
+                            image: &swapchain_data.graphics_occlusion[image_index as usize],
                         },
                         Binding::Image {
                             binding: 3,
                             ty: DescriptorType::CombinedImageSampler,
                             layout: ImageLayout::ShaderReadOnly,
-                            image: &render_data.depth,
+                            image: &swapchain_data.depth,
                         },
                         ],
                         );
@@ -673,10 +885,10 @@ impl Renderer for Vulkan {
                             );
             }
        }
-        self.device.draw_call(|mut commands| {
+        let script = |mut commands: Commands<'_>| {
             let render_pass_begin_info = RenderPassBeginInfo {
                 render_pass: &render_data.graphics_render_pass,
-                framebuffer: &render_data.graphics_framebuffers[image_index as usize],
+                framebuffer: &swapchain_data.graphics_framebuffers[image_index as usize],
                 color_clear_values: &[
                     [0.0385, 0.0385, 0.0385, 1.0],
                     [1.0, 1.0, 1.0, 1.0],
@@ -685,35 +897,55 @@ impl Renderer for Vulkan {
                 depth_stencil_clear_value: Some((1.0, 0)),
             };
 
-            commands.begin_render_pass(render_pass_begin_info);
+            commands.begin_label("graphics", [0.2, 0.6, 0.2, 1.0]);
 
-            commands.bind_pipeline(image_index, &render_data.graphics_prepass_pipeline);
+            let mut pass = commands.begin_render_pass(render_pass_begin_info);
 
-            commands.bind_vertex_buffers(
+            pass.bind_pipeline(image_index, &render_data.graphics_prepass_pipeline);
+
+            pass.bind_vertex_buffers(
                 0,
                 &[&self.data_buffer, &self.instance_buffer],
                 &[VERTEX_OFFSET as usize, 0],
             );
 
-            commands.bind_index_buffer(&self.data_buffer, INDEX_OFFSET as usize);
+            pass.bind_index_buffer(&self.data_buffer, INDEX_OFFSET as usize, self.index_type);
 
-            commands.draw_indexed(self.index_count as _, self.instance_count as _, 0, 0, 0);
+            for draw in &self.draws {
+                pass.draw_indexed(
+                    draw.index_count,
+                    draw.instance_count,
+                    draw.first_index,
+                    draw.vertex_offset,
+                    draw.first_instance,
+                );
+            }
 
-            commands.next_subpass();
+            pass.next_subpass();
 
-            commands.bind_pipeline(image_index, &render_data.graphics_raycast_pipeline);
+            pass.bind_pipeline(image_index, &render_data.graphics_raycast_pipeline);
 
-            commands.bind_vertex_buffers(
+            pass.bind_vertex_buffers(
                 0,
                 &[&self.data_buffer, &self.instance_buffer],
                 &[VERTEX_OFFSET as usize, 0],
             );
 
-            commands.bind_index_buffer(&self.data_buffer, INDEX_OFFSET as usize);
+            pass.bind_index_buffer(&self.data_buffer, INDEX_OFFSET as usize, self.index_type);
+
+            for draw in &self.draws {
+                pass.draw_indexed(
+                    draw.index_count,
+                    draw.instance_count,
+                    draw.first_index,
+                    draw.vertex_offset,
+                    draw.first_instance,
+                );
+            }
 
-            commands.draw_indexed(self.index_count as _, self.instance_count as _, 0, 0, 0);
+            pass.end();
 
-            commands.end_render_pass();
+            commands.end_label();
 
             commands.pipeline_barrier(
                 PipelineStage::COLOR_ATTACHMENT_OUTPUT,
@@ -724,39 +956,43 @@ impl Renderer for Vulkan {
                     dst_access: Access::empty(),
                     old_layout: ImageLayout::Undefined,
                     new_layout: ImageLayout::General,
-                    image: &render_data.graphics_color[image_index as usize],
+                    image: &swapchain_data.graphics_color[image_index as usize],
                 },
                 Barrier::Image {
                     src_access: Access::empty(),
                     dst_access: Access::empty(),
                     old_layout: ImageLayout::Undefined,
                     new_layout: ImageLayout::General,
-                    image: &render_data.graphics_occlusion[image_index as usize],
+                    image: &swapchain_data.graphics_occlusion[image_index as usize],
                 },
                 Barrier::Image {
                     src_access: Access::empty(),
                     dst_access: Access::empty(),
                     old_layout: ImageLayout::Undefined,
                     new_layout: ImageLayout::ShaderReadOnly,
-                    image: &render_data.depth,
+                    image: &swapchain_data.depth,
                 },
                 ],
                 );
 
                 let render_pass_begin_info = RenderPassBeginInfo {
                     render_pass: &render_data.postfx_render_pass,
-                    framebuffer: &render_data.postfx_framebuffers[image_index as usize],
+                    framebuffer: &swapchain_data.postfx_framebuffers[image_index as usize],
                     color_clear_values: &[[1.0, 0.0, 1.0, 1.0]],
                     depth_stencil_clear_value: None,
                 };
 
-                commands.begin_render_pass(render_pass_begin_info);
+                commands.begin_label("postfx", [0.2, 0.2, 0.6, 1.0]);
 
-                commands.bind_pipeline(image_index, &render_data.postfx_pipeline);
+                let mut pass = commands.begin_render_pass(render_pass_begin_info);
 
-                commands.draw(3, 1, 0, 0);
+                pass.bind_pipeline(image_index, &render_data.postfx_pipeline);
 
-                commands.end_render_pass();
+                pass.draw(3, 1, 0, 0);
+
+                pass.end();
+
+                commands.end_label();
 
                 commands.pipeline_barrier(
                     PipelineStage::COLOR_ATTACHMENT_OUTPUT,
@@ -766,32 +1002,46 @@ impl Renderer for Vulkan {
                         dst_access: Access::empty(),
                         old_layout: ImageLayout::Undefined,
                         new_layout: ImageLayout::General,
-                        image: &render_data.postfx_color[image_index as usize],
+                        image: &swapchain_data.postfx_color[image_index as usize],
                     }],
                 );
 
                 let render_pass_begin_info = RenderPassBeginInfo {
                     render_pass: &render_data.present_render_pass,
-                    framebuffer: &render_data.present_framebuffers[image_index as usize],
+                    framebuffer: &swapchain_data.present_framebuffers[image_index as usize],
                     color_clear_values: &[[1.0, 0.0, 1.0, 1.0]],
                     depth_stencil_clear_value: Some((1.0, 0)),
                 };
 
-                commands.begin_render_pass(render_pass_begin_info);
+                commands.begin_label("present", [0.6, 0.2, 0.2, 1.0]);
 
-                commands.bind_pipeline(image_index, &render_data.present_pipeline);
+                let mut pass = commands.begin_render_pass(render_pass_begin_info);
 
-                commands.draw(3, 1, 0, 0);
+                pass.bind_pipeline(image_index, &render_data.present_pipeline);
 
-                commands.end_render_pass();
-        });
+                pass.draw(3, 1, 0, 0);
 
-        let present_result = self.device.present(&render_data.swapchain);
+                pass.end();
+
+                commands.end_label();
+        };
+
+        let present_result = match &swapchain_data.swapchain {
+            Some(swapchain) => self
+                .device
+                .draw_call(script)
+                .and_then(|()| self.device.present(swapchain)),
+            None => self.device.call(script),
+        };
 
         unsafe { DUMMY = false };
 
         match present_result {
             Ok(()) => {}
+            Err(gpu::prelude::Error::DeviceLost) => {
+                self.device_lost = true;
+                return Err(Error::DeviceLost);
+            }
             Err(e) => {
                 warn!("Failed to present\n");
                 return Ok(Condition::Retry);
@@ -803,72 +1053,135 @@ impl Renderer for Vulkan {
     fn resize(&mut self, resolution: (u32, u32)) {
         self.settings.resolution = Vector::<u32, 4>::new([resolution.0, resolution.1, 0, 0]);
 
-        self.render_data = Some(VulkanRenderData::load(self));
+        self.load_swapchain();
     }
 }
 
-impl VulkanRenderData {
-    pub fn load(vk: &mut Vulkan) -> Self {
-        let old = vk.render_data.take();
+impl Vulkan {
+    /// Reads back the last frame drawn headless, as tightly packed `Bgra8Srgb` rows.
+    pub fn capture_frame(&mut self) -> std::result::Result<Vec<u8>, CaptureError> {
+        let swapchain_data = self.swapchain_data.as_ref().ok_or(CaptureError::NoFrame)?;
 
-        //SWAPCHAIN
-        let swapchain = Swapchain::new(SwapchainInfo {
-            device: &vk.device,
-            surface: &vk.surface,
-            old: old.map(|old| old.swapchain),
+        if swapchain_data.swapchain.is_some() {
+            return Err(CaptureError::NotHeadless);
+        }
+
+        let (width, height) = (self.settings.resolution[0], self.settings.resolution[1]);
+
+        //the frame lands where uploads are read from
+        self.device.wait_for_transfers();
+
+        self.device.copy_image_to_buffer(ImageBufferCopy {
+            from: &swapchain_data.present_images[0],
+            to: &mut self.staging_buffer,
+            dst: 0,
+            src_extent: (width, height, 1),
+            src_offset: (0, 0, 0),
         });
 
-        let swapchain_images = swapchain.images();
+        let mut frame = vec![0; (width * height) as usize * Format::Bgra8Srgb.to_bytes()];
 
-        let present_extent = (vk.settings.resolution[0], vk.settings.resolution[1], 1);
+        self.staging_buffer.read(BufferRead {
+            offset: 0,
+            data: &mut frame,
+        });
 
-        let graphics_extent = (vk.settings.resolution[0], vk.settings.resolution[1], 1);
+        Ok(frame)
+    }
 
-        //ATTACHMENTS
-        let depth = Image::new(ImageInfo {
-            device: &vk.device,
-            ty: ImageType::TwoDim,
-            usage: ImageUsage::DEPTH_STENCIL | ImageUsage::SAMPLED,
-            format: Format::D32Sfloat,
-            extent: graphics_extent,
+    /// True once a draw failed with `DeviceLost`, nothing is drawn until the device is
+    /// recreated.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost
+    }
+
+    /// Tears down everything made from the lost device and builds it again on the same
+    /// instance and surface. Render settings carry over, the next draw reuploads the scene.
+    pub fn recreate_device(mut self) -> Self {
+        let context = self.context.clone();
+        let surface = self.surface.take();
+        let present_modes = mem::take(&mut self.present_modes);
+        let assets = mem::take(&mut self.assets);
+        let resources = mem::take(&mut self.resources);
+        let preferred_device = self.preferred_device.take();
+        let settings = *self.settings;
+
+        //everything made from the old device goes before the new one is created
+        drop(self);
+
+        let mut vulkan = Self::new(
+            context,
+            surface,
+            present_modes,
+            settings.render_distance,
+            assets,
+            resources,
+            preferred_device,
+        );
+
+        vulkan.settings.resolution = settings.resolution;
+
+        //buffers are empty on the new device
+        unsafe { DUMMY = true };
+
+        vulkan
+    }
+
+    //recreates the swapchain in place, pipelines are only rebuilt if the image count changed
+    fn load_swapchain(&mut self) {
+        let old = self
+            .swapchain_data
+            .take()
+            .and_then(VulkanSwapchainData::into_swapchain);
+
+        let extent = (self.settings.resolution[0], self.settings.resolution[1]);
+
+        let swapchain = self.surface.as_ref().map(|surface| match old {
+            Some(mut swapchain) => {
+                swapchain.recreate(extent);
+                swapchain
+            }
+            None => Swapchain::new(SwapchainInfo {
+                device: &self.device,
+                surface,
+                present_mode: self.present_mode,
+                extent,
+                old: None,
+            }),
         });
 
-        let graphics_color = (0..swapchain_images.len())
-            .map(|_| {
-                Image::new(ImageInfo {
-                    device: &vk.device,
-                    ty: ImageType::TwoDim,
-                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
-                    format: Format::Rgba32Sfloat,
-                    extent: graphics_extent,
-                })
-            })
-        .collect::<Vec<_>>();
+        //the surface can refuse the requested size, render at whatever it settled on
+        if let Some(swapchain) = &swapchain {
+            let (width, height) = swapchain.extent();
 
-        let graphics_occlusion = (0..swapchain_images.len())
-            .map(|_| {
-                Image::new(ImageInfo {
-                    device: &vk.device,
-                    ty: ImageType::TwoDim,
-                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
-                    format: Format::Rgba32Sfloat,
-                    extent: graphics_extent,
-                })
-            })
-        .collect::<Vec<_>>();
+            if (width, height) != extent {
+                self.settings.resolution = Vector::<u32, 4>::new([width, height, 0, 0]);
+            }
+        }
 
-        let postfx_color = (0..swapchain_images.len())
-            .map(|_| {
-                Image::new(ImageInfo {
-                    device: &vk.device,
-                    ty: ImageType::TwoDim,
-                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
-                    format: Format::Rgba32Sfloat,
-                    extent: graphics_extent,
-                })
-            })
-        .collect::<Vec<_>>();
+        self.swapchain_data = Some(VulkanSwapchainData::load(self, swapchain));
+
+        //attachments changed, so descriptors need to be bound again
+        unsafe { DUMMY = true };
+    }
+}
+
+impl Drop for Vulkan {
+    fn drop(&mut self) {
+        //resources can't be destroyed while the gpu uses them. a lost device is only logged,
+        //panicking here would abort if the drop is part of unwinding and hide the first panic
+        if self.device_lost {
+            return;
+        }
+
+        if let Err(e) = self.device.wait_idle() {
+            error!("failed to wait on device, tearing down anyway: {:?}\n", e);
+        }
+    }
+}
 
+impl VulkanRenderData {
+    pub fn load(vk: &Vulkan, image_count: usize) -> Self {
         //RENDERPASSES
         let graphics_render_pass = RenderPass::new(RenderPassInfo {
             device: &vk.device,
@@ -955,7 +1268,11 @@ impl VulkanRenderData {
                 load_op: AttachmentLoadOp::Clear,
                 store_op: AttachmentStoreOp::Store,
                 initial_layout: ImageLayout::Undefined,
-                final_layout: ImageLayout::PresentSrc,
+                final_layout: if vk.surface.is_some() {
+                    ImageLayout::PresentSrc
+                } else {
+                    ImageLayout::TransferSrc
+                },
                 layout: ImageLayout::ColorAttachment,
                 ty: AttachmentType::Color,
             }],
@@ -970,48 +1287,23 @@ impl VulkanRenderData {
             }],
         });
 
-        //FRAMEBUFFERS
-        let graphics_framebuffers = (0..swapchain_images.len())
-            .map(|i| {
-                Framebuffer::new(FramebufferInfo {
-                    device: &vk.device,
-                    render_pass: &graphics_render_pass,
-                    extent: graphics_extent,
-                    attachments: &[&graphics_color[i], &graphics_occlusion[i], &depth],
-                })
-            })
-        .collect::<Vec<_>>();
-
-        let postfx_framebuffers = (0..swapchain_images.len())
-            .map(|i| {
-                Framebuffer::new(FramebufferInfo {
-                    device: &vk.device,
-                    render_pass: &postfx_render_pass,
-                    extent: graphics_extent,
-                    attachments: &[&postfx_color[i]],
-                })
-            })
-        .collect::<Vec<_>>();
+        //PIPELINES
+        let mut vertex_input = Vertex::attribute_descriptions(0, 0);
 
-        let present_framebuffers = (0..swapchain_images.len())
-            .map(|i| {
-                Framebuffer::new(FramebufferInfo {
-                    device: &vk.device,
-                    render_pass: &present_render_pass,
-                    extent: present_extent,
-                    attachments: &[&swapchain_images[i]],
-                })
-            })
-        .collect::<Vec<_>>();
+        vertex_input.push(VertexInput {
+            binding: 1,
+            location: 3,
+            format: Format::Rgb32Uint,
+            rate: InputRate::Instance,
+            offset: 0,
+        });
 
-        //PIPELINES
         let graphics_prepass_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
             render_pass: &graphics_render_pass,
-            descriptor_set_count: swapchain_images.len() as _,
+            descriptor_set_count: image_count as _,
             color_count: 0,
             subpass: 0,
-            extent: (graphics_extent.0, graphics_extent.1),
             cull_mode: CullMode::BACK,
             vertex_shader: &vk.graphics_vertex_shader,
             fragment_shader: None,
@@ -1020,32 +1312,7 @@ impl VulkanRenderData {
                 write: true,
                 compare_op: CompareOp::Less,
             },
-            vertex_input: &[
-                VertexInput {
-                    binding: 0,
-                    location: 0,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 0,
-                    location: 1,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 0,
-                    location: 2,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 1,
-                    location: 3,
-                    format: Format::Rgb32Uint,
-                    rate: InputRate::Instance,
-                },
-                ],
+            vertex_input: &vertex_input,
                 layout: &[
                     Descriptor {
                         binding: 0,
@@ -1077,10 +1344,9 @@ impl VulkanRenderData {
         let graphics_raycast_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
             render_pass: &graphics_render_pass,
-            descriptor_set_count: swapchain_images.len() as _,
+            descriptor_set_count: image_count as _,
             color_count: 2,
             subpass: 1,
-            extent: (graphics_extent.0, graphics_extent.1),
             cull_mode: CullMode::BACK,
             vertex_shader: &vk.graphics_vertex_shader,
             fragment_shader: Some(&vk.graphics_fragment_shader),
@@ -1089,32 +1355,7 @@ impl VulkanRenderData {
                 write: false,
                 compare_op: CompareOp::LessOrEqual,
             },
-            vertex_input: &[
-                VertexInput {
-                    binding: 0,
-                    location: 0,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 0,
-                    location: 1,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 0,
-                    location: 2,
-                    format: Format::Rgb32Sfloat,
-                    rate: InputRate::Vertex,
-                },
-                VertexInput {
-                    binding: 1,
-                    location: 3,
-                    format: Format::Rgb32Uint,
-                    rate: InputRate::Instance,
-                },
-                ],
+            vertex_input: &vertex_input,
                 layout: &[
                     Descriptor {
                         binding: 0,
@@ -1146,10 +1387,9 @@ impl VulkanRenderData {
         let postfx_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
             render_pass: &postfx_render_pass,
-            descriptor_set_count: swapchain_images.len() as _,
+            descriptor_set_count: image_count as _,
             color_count: 1,
             subpass: 0,
-            extent: (graphics_extent.0, graphics_extent.1),
             cull_mode: CullMode::BACK,
             vertex_shader: &vk.fullscreen_vertex_shader,
             fragment_shader: Some(&vk.postfx_fragment_shader),
@@ -1190,10 +1430,9 @@ impl VulkanRenderData {
         let present_pipeline = Pipeline::new_graphics_pipeline(GraphicsPipelineInfo {
             device: &vk.device,
             render_pass: &present_render_pass,
-            descriptor_set_count: swapchain_images.len() as _,
+            descriptor_set_count: image_count as _,
             color_count: 1,
             subpass: 0,
-            extent: (present_extent.0, present_extent.1),
             cull_mode: CullMode::BACK,
             vertex_shader: &vk.fullscreen_vertex_shader,
             fragment_shader: Some(&vk.present_fragment_shader),
@@ -1226,24 +1465,150 @@ impl VulkanRenderData {
         });
 
         Self {
-            graphics_color,
-            graphics_occlusion,
-            graphics_framebuffers,
+            image_count,
             graphics_render_pass,
             graphics_prepass_pipeline,
             graphics_raycast_pipeline,
-            postfx_color,
-            postfx_framebuffers,
             postfx_render_pass,
             postfx_pipeline,
-            present_framebuffers,
             present_render_pass,
             present_pipeline,
+        }
+    }
+}
+
+impl VulkanSwapchainData {
+    pub fn load(vk: &mut Vulkan, swapchain: Option<Swapchain>) -> Self {
+        //the swapchain may have been created for a different extent than the last resize
+        let present_extent = match &swapchain {
+            Some(swapchain) => {
+                let (width, height) = swapchain.extent();
+
+                (width, height, 1)
+            }
+            None => (vk.settings.resolution[0], vk.settings.resolution[1], 1),
+        };
+
+        let present_images = match &swapchain {
+            Some(swapchain) => swapchain.images(),
+            None => vec![Image::new(ImageInfo {
+                device: &vk.device,
+                ty: ImageType::TwoDim,
+                usage: ImageUsage::COLOR | ImageUsage::TRANSFER_SRC,
+                format: Format::Bgra8Srgb,
+                extent: present_extent,
+            })],
+        };
+
+        //pipelines hold a descriptor set per swapchain image
+        if vk
+            .render_data
+            .as_ref()
+            .map_or(true, |render_data| render_data.image_count != present_images.len())
+        {
+            vk.render_data = Some(VulkanRenderData::load(vk, present_images.len()));
+        }
+
+        let render_data = vk.render_data.as_ref().unwrap();
+
+        let graphics_extent = (vk.settings.resolution[0], vk.settings.resolution[1], 1);
+
+        //ATTACHMENTS
+        let depth = Image::new(ImageInfo {
+            device: &vk.device,
+            ty: ImageType::TwoDim,
+            usage: ImageUsage::DEPTH_STENCIL | ImageUsage::SAMPLED,
+            format: Format::D32Sfloat,
+            extent: graphics_extent,
+        });
+
+        let graphics_color = (0..present_images.len())
+            .map(|_| {
+                Image::new(ImageInfo {
+                    device: &vk.device,
+                    ty: ImageType::TwoDim,
+                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
+                    format: Format::Rgba32Sfloat,
+                    extent: graphics_extent,
+                })
+            })
+        .collect::<Vec<_>>();
+
+        let graphics_occlusion = (0..present_images.len())
+            .map(|_| {
+                Image::new(ImageInfo {
+                    device: &vk.device,
+                    ty: ImageType::TwoDim,
+                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
+                    format: Format::Rgba32Sfloat,
+                    extent: graphics_extent,
+                })
+            })
+        .collect::<Vec<_>>();
+
+        let postfx_color = (0..present_images.len())
+            .map(|_| {
+                Image::new(ImageInfo {
+                    device: &vk.device,
+                    ty: ImageType::TwoDim,
+                    usage: ImageUsage::COLOR | ImageUsage::STORAGE,
+                    format: Format::Rgba32Sfloat,
+                    extent: graphics_extent,
+                })
+            })
+        .collect::<Vec<_>>();
+
+        //FRAMEBUFFERS
+        let graphics_framebuffers = (0..present_images.len())
+            .map(|i| {
+                Framebuffer::new(FramebufferInfo {
+                    device: &vk.device,
+                    render_pass: &render_data.graphics_render_pass,
+                    extent: graphics_extent,
+                    attachments: &[&graphics_color[i], &graphics_occlusion[i], &depth],
+                })
+            })
+        .collect::<Vec<_>>();
+
+        let postfx_framebuffers = (0..present_images.len())
+            .map(|i| {
+                Framebuffer::new(FramebufferInfo {
+                    device: &vk.device,
+                    render_pass: &render_data.postfx_render_pass,
+                    extent: graphics_extent,
+                    attachments: &[&postfx_color[i]],
+                })
+            })
+        .collect::<Vec<_>>();
+
+        let present_framebuffers = (0..present_images.len())
+            .map(|i| {
+                Framebuffer::new(FramebufferInfo {
+                    device: &vk.device,
+                    render_pass: &render_data.present_render_pass,
+                    extent: present_extent,
+                    attachments: &[&present_images[i]],
+                })
+            })
+        .collect::<Vec<_>>();
+
+
+        Self {
+            graphics_color,
+            graphics_occlusion,
+            graphics_framebuffers,
+            postfx_color,
+            postfx_framebuffers,
+            present_framebuffers,
             swapchain,
-            swapchain_images,
+            present_images,
             depth,
         }
     }
+
+    fn into_swapchain(self) -> Option<Swapchain> {
+        self.swapchain
+    }
 }
 
 /*
@@ -3153,3 +3518,15 @@ fn resize(&mut self, resolution: (u32, u32)) {
             &self.render_info,
     ));
 }*/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_ranges_packed_in_order() {
+        assert_eq!(instance_ranges(&[3, 5]), [(0, 3), (3, 5)]);
+        assert_eq!(instance_ranges(&[4, 0, 2]), [(0, 4), (4, 0), (4, 2)]);
+        assert!(instance_ranges(&[]).is_empty());
+    }
+}
@@ -10,41 +10,70 @@ bitflags! {
         const UNIFORM       = 1 << 3;
         const VERTEX        = 1 << 4;
         const INDEX         = 1 << 5;
+        const INDIRECT      = 1 << 6;
     }
 }
 
 impl BufferUsage {
-    pub(crate) fn to_vk(self) -> u32 {
-        let mut vk = 0;
+    pub(crate) fn to_vk(self) -> vk::BufferUsageFlags {
+        let mut vk = vk::BufferUsageFlags::empty();
 
         if self.contains(Self::TRANSFER_SRC) {
-            vk |= vk::BUFFER_USAGE_TRANSFER_SRC;
+            vk |= vk::BufferUsageFlags::TRANSFER_SRC;
         }
 
         if self.contains(Self::TRANSFER_DST) {
-            vk |= vk::BUFFER_USAGE_TRANSFER_DST
+            vk |= vk::BufferUsageFlags::TRANSFER_DST;
         }
 
         if self.contains(Self::STORAGE) {
-            vk |= vk::BUFFER_USAGE_STORAGE
+            vk |= vk::BufferUsageFlags::STORAGE;
         }
 
         if self.contains(Self::UNIFORM) {
-            vk |= vk::BUFFER_USAGE_UNIFORM
+            vk |= vk::BufferUsageFlags::UNIFORM;
         }
 
         if self.contains(Self::VERTEX) {
-            vk |= vk::BUFFER_USAGE_VERTEX
+            vk |= vk::BufferUsageFlags::VERTEX;
         }
 
         if self.contains(Self::INDEX) {
-            vk |= vk::BUFFER_USAGE_INDEX
+            vk |= vk::BufferUsageFlags::INDEX;
+        }
+
+        if self.contains(Self::INDIRECT) {
+            vk |= vk::BufferUsageFlags::INDIRECT;
         }
 
         vk
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexType {
+    Uint16,
+    Uint32,
+}
+
+impl IndexType {
+    pub fn to_bytes(self) -> usize {
+        match self {
+            Self::Uint16 => 2,
+            Self::Uint32 => 4,
+        }
+    }
+}
+
+impl From<IndexType> for vk::IndexType {
+    fn from(index_type: IndexType) -> Self {
+        match index_type {
+            IndexType::Uint16 => Self::Uint16,
+            IndexType::Uint32 => Self::Uint32,
+        }
+    }
+}
+
 pub struct BufferCopy<'a> {
     pub from: &'a Buffer,
     pub to: &'a mut Buffer,
@@ -61,11 +90,24 @@ pub struct BufferImageCopy<'a> {
     pub dst_offset: (i32, i32, i32),
 }
 
+pub struct ImageBufferCopy<'a> {
+    pub from: &'a Image,
+    pub to: &'a mut Buffer,
+    pub dst: u64,
+    pub src_extent: (u32, u32, u32),
+    pub src_offset: (i32, i32, i32),
+}
+
 pub struct BufferWrite<'a, U: ?Sized + Copy> {
     pub offset: u64,
     pub data: &'a [U],
 }
 
+pub struct BufferRead<'a, U: vk::Pod> {
+    pub offset: u64,
+    pub data: &'a mut [U],
+}
+
 pub struct BufferInfo<'a> {
     pub device: &'a Device,
     pub usage: BufferUsage,
@@ -77,7 +119,7 @@ pub struct BufferInfo<'a> {
 pub enum Buffer {
     Vulkan {
         buffer: vk::Buffer,
-        memory: vk::Memory,
+        memory: Suballocation,
     },
 }
 
@@ -85,28 +127,21 @@ impl Buffer {
     pub fn new(info: BufferInfo) -> Self {
         match info.device {
             Device::Vulkan {
-                physical_device,
                 device,
+                memory_pool,
                 ..
             } => {
                 let mut buffer =
                     vk::Buffer::new(device.clone(), info.size as u64, info.usage.to_vk())
                         .expect("failed to create buffer");
 
-                let memory_allocate_info = vk::MemoryAllocateInfo {
-                    property_flags: info.properties.to_vk(),
-                };
-
-                let memory = vk::Memory::allocate(
-                    device.clone(),
-                    memory_allocate_info,
-                    buffer.memory_requirements(),
-                    physical_device.memory_properties(),
-                    info.properties.contains(MemoryProperties::HOST_VISIBLE),
-                )
-                .expect("failed to allocate memory");
+                let memory = memory_pool
+                    .allocate(&buffer.memory_requirements(), info.properties, true)
+                    .expect("failed to allocate memory");
 
-                buffer.bind_memory(&memory);
+                buffer
+                    .bind_memory_at(memory.memory(), memory.offset())
+                    .expect("failed to bind memory to buffer");
 
                 Self::Vulkan { buffer, memory }
             }
@@ -124,6 +159,17 @@ impl Buffer {
             }
         }
     }
+
+    /// Fills `read.data` from host visible memory, the gpu must be done writing to it.
+    pub fn read<U: vk::Pod>(&self, read: BufferRead<'_, U>) {
+        match self {
+            Self::Vulkan { memory, .. } => {
+                memory
+                    .read(read.offset, read.data)
+                    .expect("failed to read from buffer memory");
+            }
+        }
+    }
 }
 
 pub enum Inner {
@@ -27,29 +27,38 @@ fn debug_utils_messenger_callback(data: &vk::DebugUtilsMessengerCallbackData) ->
 
 impl Context {
     pub fn start() -> Self {
-        Self::new_vulkan()
+        Self::new_vulkan(true)
     }
 
-    fn new_vulkan() -> Self {
+    /// Starts without any surface extensions, for rendering that never reaches a window.
+    pub fn start_headless() -> Self {
+        Self::new_vulkan(false)
+    }
+
+    fn new_vulkan(presentable: bool) -> Self {
         let application_info = vk::ApplicationInfo {
             application_name: "Octane",
             application_version: (0, 1, 0).into(),
             engine_name: "Octane",
             engine_version: (0, 1, 0).into(),
-            api_version: (1, 0, 0).into(),
+            api_version: (1, 1, 0).into(),
         };
 
-        let mut extensions = vec![vk::KHR_SURFACE];
+        let mut extensions = vec![];
         let mut layers = vec![];
 
-        #[cfg(target_os = "windows")]
-        {
-            extensions.push(vk::KHR_WIN32_SURFACE);
-        }
-        
-        #[cfg(target_os = "linux")]
-        {
-            extensions.push(vk::KHR_XLIB_SURFACE);
+        if presentable {
+            extensions.push(vk::KHR_SURFACE);
+
+            #[cfg(target_os = "windows")]
+            {
+                extensions.push(vk::KHR_WIN32_SURFACE);
+            }
+
+            #[cfg(target_os = "linux")]
+            {
+                extensions.push(vk::KHR_XLIB_SURFACE);
+            }
         }
 
         let mut debug_utils_messenger_create_info = None;
@@ -57,7 +66,23 @@ impl Context {
         #[cfg(debug_assertions)]
         {
             extensions.push(vk::EXT_DEBUG_UTILS);
-            layers.push(vk::LAYER_KHRONOS_VALIDATION);
+
+            let available_layers = vk::Instance::enumerate_layers();
+
+            //older sdks only ship the lunarg meta layer, and plain drivers ship neither
+            let validation_layers = [
+                vk::LAYER_KHRONOS_VALIDATION,
+                vk::LAYER_LUNARG_STANDARD_VALIDATION,
+            ];
+
+            let validation_layer = validation_layers
+                .into_iter()
+                .find(|layer| available_layers.iter().any(|available| available == layer));
+
+            match validation_layer {
+                Some(layer) => layers.push(layer),
+                None => warn!("no validation layer available, running without validation\n"),
+            }
 
             debug_utils_messenger_create_info = Some(vk::DebugUtilsMessengerCreateInfo {
                 message_severity: vk::DEBUG_UTILS_MESSAGE_SEVERITY_VERBOSE
@@ -9,7 +9,10 @@ use log::{error, info, trace, warn};
 
 pub struct DeviceInfo<'a> {
     pub context: &'a Context,
-    pub surface: &'a Surface,
+    /// `None` for headless devices, which are created without swapchain support.
+    pub surface: Option<&'a Surface>,
+    /// Picks the first GPU whose name contains this, ignoring case, over the best scoring one.
+    pub preferred_device: Option<&'a str>,
 }
 
 #[non_exhaustive]
@@ -18,6 +21,7 @@ pub enum Device {
         instance: Rc<vk::Instance>,
         physical_device: Rc<vk::PhysicalDevice>,
         device: Rc<vk::Device>,
+        queue_family_index: u32,
         queues: Vec<vk::Queue>,
         command_pool: vk::CommandPool,
         command_buffer: vk::CommandBuffer,
@@ -25,9 +29,38 @@ pub enum Device {
         image_available_semaphore: Rc<RefCell<vk::Semaphore>>,
         render_finished_semaphore: Rc<RefCell<vk::Semaphore>>,
         in_flight_fence: vk::Fence,
+        transfer: TransferQueue,
+        memory_pool: MemoryPool,
     },
 }
 
+//uploads in flight before the oldest one is waited on
+const TRANSFER_SLOTS: usize = 4;
+
+//uploads go through their own queue so they don't wait on rendering.
+//without a dedicated transfer family this is just the graphics queue again
+pub struct TransferQueue {
+    queue_family_index: Option<u32>,
+    queue: vk::Queue,
+    command_pool: vk::CommandPool,
+    slots: Vec<TransferSlot>,
+    slot: usize,
+    release_semaphore: vk::Semaphore,
+    transfer_semaphore: vk::Semaphore,
+    release_pending: bool,
+}
+
+//the command buffers of one upload, reused once its fences have signaled
+struct TransferSlot {
+    command_buffer: vk::CommandBuffer,
+    release_command_buffer: vk::CommandBuffer,
+    acquire_command_buffer: vk::CommandBuffer,
+    fence: vk::Fence,
+    acquire_fence: vk::Fence,
+    pending: bool,
+    acquire_pending: bool,
+}
+
 impl Device {
     pub fn choose_best(info: DeviceInfo) -> Self {
         match info.context {
@@ -58,9 +91,31 @@ impl Device {
                         );
                     }
 
-                    candidates.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+                    //best first
+                    candidates.sort_by(|(a, _, _), (b, _, _)| b.cmp(a));
+
+                    let preferred = info.preferred_device.and_then(|preferred_device| {
+                        let preferred_device = preferred_device.to_lowercase();
+
+                        let position = candidates.iter().position(|(_, properties, _)| {
+                            properties
+                                .device_name
+                                .to_lowercase()
+                                .contains(&preferred_device)
+                        });
 
-                    let (_, properties, physical_device) = candidates.remove(0);
+                        if position.is_none() {
+                            warn!(
+                                "No GPU matches \"{}\", falling back to the best scoring one\n",
+                                preferred_device
+                            );
+                        }
+
+                        position
+                    });
+
+                    let (_, properties, physical_device) =
+                        candidates.remove(preferred.unwrap_or(0));
 
                     info!("Selected GPU \"{}\"\n", properties.device_name);
 
@@ -78,15 +133,17 @@ impl Device {
                     if queue_family.queue_flags & vk::QUEUE_COMPUTE == 0 {
                         continue;
                     }
-                    if let Surface::Vulkan { surface, .. } = info.surface {
-                        if !physical_device
-                            .surface_supported(&surface, i as _)
-                            .expect("failed to query surface support")
-                        {
-                            continue;
+                    match info.surface {
+                        Some(Surface::Vulkan { surface, .. }) => {
+                            if !physical_device
+                                .surface_supported(&surface, i as _)
+                                .expect("failed to query surface support")
+                            {
+                                continue;
+                            }
                         }
-                    } else {
-                        panic!("not a vulkan surface");
+                        Some(_) => panic!("not a vulkan surface"),
+                        None => {}
                     }
                     queue_family_index = Some(i as u32);
                     break;
@@ -94,20 +151,45 @@ impl Device {
 
                 let queue_family_index = queue_family_index.expect("failed to find suitable queue");
 
-                let queue_create_info = vk::DeviceQueueCreateInfo {
+                let transfer_queue_family_index = queue_families
+                    .iter()
+                    .position(|queue_family| {
+                        queue_family.queue_flags & vk::QUEUE_TRANSFER != 0
+                            && queue_family.queue_flags & vk::QUEUE_GRAPHICS == 0
+                    })
+                    .map(|i| i as u32);
+
+                if let Some(i) = transfer_queue_family_index {
+                    trace!("Found dedicated transfer queue family {}", i);
+                }
+
+                let mut queue_create_infos = vec![vk::DeviceQueueCreateInfo {
                     queue_family_index,
                     queue_priorities: &[1.0],
-                };
+                }];
+
+                if let Some(transfer_queue_family_index) = transfer_queue_family_index {
+                    queue_create_infos.push(vk::DeviceQueueCreateInfo {
+                        queue_family_index: transfer_queue_family_index,
+                        queue_priorities: &[1.0],
+                    });
+                }
 
                 let physical_device_features = vk::PhysicalDeviceFeatures {
                     shader_int_64: true,
                     ..Default::default()
                 };
 
+                let extensions = if info.surface.is_some() {
+                    vec![vk::KHR_SWAPCHAIN]
+                } else {
+                    vec![]
+                };
+
                 let device_create_info = vk::DeviceCreateInfo {
-                    queues: &[queue_create_info],
+                    queues: &queue_create_infos,
                     enabled_features: &physical_device_features,
-                    extensions: &[vk::KHR_SWAPCHAIN],
+                    extensions: &extensions,
                     layers: &layers[..],
                 };
 
@@ -133,6 +215,16 @@ impl Device {
                     vk::CommandBuffer::allocate(device.clone(), command_buffer_allocate_info)
                         .expect("failed to create command buffer")
                         .remove(0);
+
+                let transfer = TransferQueue::new(
+                    device.clone(),
+                    &command_pool,
+                    queue_family_index,
+                    transfer_queue_family_index,
+                );
+
+                let memory_pool = MemoryPool::new(device.clone(), physical_device.clone());
+
         let count = 2048;
 
                 let uniform_buffer_pool_size = vk::DescriptorPoolSize {
@@ -156,6 +248,7 @@ impl Device {
         };
 
         let descriptor_pool_create_info = vk::DescriptorPoolCreateInfo {
+            flags: 0,
             max_sets: count,
             pool_sizes: &[
                 uniform_buffer_pool_size,
@@ -194,6 +287,7 @@ impl Device {
                     instance: instance.clone(),
                     physical_device,
                     device,
+                    queue_family_index,
                     queues,
                     command_pool,
                     command_buffer,
@@ -201,154 +295,300 @@ impl Device {
                     image_available_semaphore,
                     render_finished_semaphore,
                     in_flight_fence,
+                    transfer,
+                    memory_pool,
                 }
             }
         }
     }
 
-    pub fn copy_buffer_to_buffer(&mut self, mut copy: BufferCopy<'_>) {
+    /// The copy runs in the background, `copy.from` must not be written again until
+    /// `wait_for_transfers` returns.
+    pub fn copy_buffer_to_buffer(&mut self, copy: BufferCopy<'_>) {
         match self {
-            Self::Vulkan { command_buffer, queues, .. } => {
-                command_buffer.record(|mut commands| {
+            Self::Vulkan {
+                queues,
+                queue_family_index,
+                transfer,
+                ..
+            } => {
+                let Buffer::Vulkan { buffer: from, .. } = copy.from else { panic!("not a vulkan buffer") };
+                let Buffer::Vulkan { buffer: to, .. } = copy.to else { panic!("not a vulkan buffer") };
+
+                transfer.next_slot();
+
+                transfer.release(&mut queues[0], *queue_family_index, |commands, (graphics, transfer)| {
+                    commands.pipeline_barrier(
+                        vk::PIPELINE_STAGE_ALL_COMMANDS,
+                        vk::PIPELINE_STAGE_BOTTOM_OF_PIPE,
+                        0,
+                        &[],
+                        &[buffer_ownership_barrier(to, graphics, transfer)],
+                        &[],
+                    );
+                });
+
+                transfer.submit(*queue_family_index, |commands, ownership| {
+                    if let Some((graphics, transfer)) = ownership {
+                        commands.pipeline_barrier(
+                            vk::PIPELINE_STAGE_TOP_OF_PIPE,
+                            vk::PIPELINE_STAGE_TRANSFER,
+                            0,
+                            &[],
+                            &[buffer_ownership_barrier(to, graphics, transfer)],
+                            &[],
+                        );
+                    }
+
                     let buffer_copy = vk::BufferCopy {
                         src_offset: copy.src,
                         dst_offset: copy.dst,
                         size: copy.size as _,
                     };
 
-                    if let Buffer::Vulkan { buffer: from, .. } = &copy.from && let Buffer::Vulkan { buffer: to, .. } = &mut copy.to {
-                        commands.copy_buffer(from, to, &[buffer_copy]);
-                    } else {
-                        panic!("not a vulkan buffer");
-                    }
-                }).expect("failed to record copy buffer to buffer commands");
-
-                let submit_info = vk::SubmitInfo {
-                    wait_semaphores: &[],
-                    wait_stages: &[],
-                    command_buffers: &[&command_buffer],
-                    signal_semaphores: &[],
-                };
-
-                queues[0]
-                    .submit(&[submit_info], None)
-                    .expect("failed to submit buffer copy command buffer");
-
-                queues[0].wait_idle().expect("failed to wait on queue");
-            }
-        }
-    }
-
-    pub fn copy_buffer_to_image(&mut self, mut copy: BufferImageCopy<'_>) {
-        match self {
-            Self::Vulkan { command_buffer, queues, .. } => {
-                command_buffer
-                    .record(|mut commands| {
-                        if let Buffer::Vulkan { buffer: from, .. } = &copy.from && let Image::Vulkan { image: to, .. } = &mut copy.to { 
-                            let barrier = vk::ImageMemoryBarrier {
-                                old_layout: vk::ImageLayout::Undefined,
-                                new_layout: vk::ImageLayout::TransferDst,
-                                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                                image: &to,
-                                src_access_mask: 0,
-                                dst_access_mask: 0,
-                                subresource_range: vk::ImageSubresourceRange {
-                                    aspect_mask: vk::IMAGE_ASPECT_COLOR,
-                                    base_mip_level: 0,
-                                    level_count: 1,
-                                    base_array_layer: 0,
-                                    layer_count: 1,
-                                },
-                            };
+                    commands.copy_buffer(from, to, &[buffer_copy]);
 
+                    match ownership {
+                        Some((graphics, transfer)) => {
                             commands.pipeline_barrier(
-                                vk::PIPELINE_STAGE_TOP_OF_PIPE,
                                 vk::PIPELINE_STAGE_TRANSFER,
+                                vk::PIPELINE_STAGE_BOTTOM_OF_PIPE,
                                 0,
                                 &[],
+                                &[buffer_ownership_barrier(to, transfer, graphics)],
                                 &[],
-                                &[barrier],
                             );
-
-                            let buffer_image_copy = vk::BufferImageCopy {
-                                buffer_offset: copy.src as _,
-                                buffer_row_length: 0,
-                                buffer_image_height: 0,
-                                image_subresource: vk::ImageSubresourceLayers {
-                                    aspect_mask: vk::IMAGE_ASPECT_COLOR,
-                                    mip_level: 0,
-                                    base_array_layer: 0,
-                                    layer_count: 1,
-                                },
-                                image_offset: copy.dst_offset,
-                                image_extent: copy.dst_extent,
-                            };
-
-                            commands.copy_buffer_to_image(
-                                from,
-                                to,
-                                vk::ImageLayout::TransferDst,
-                                &[buffer_image_copy],
-                            );
-
-                            let barrier = vk::ImageMemoryBarrier {
-                                old_layout: vk::ImageLayout::TransferDst,
-                                new_layout: vk::ImageLayout::ShaderReadOnly,
+                        }
+                        //same queue, so the draws reading the buffer only need to see the write
+                        None => {
+                            let barrier = vk::BufferMemoryBarrier {
+                                src_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                                dst_access_mask: vk::ACCESS_VERTEX_ATTRIBUTE_READ
+                                    | vk::ACCESS_INDEX_READ
+                                    | vk::ACCESS_SHADER_READ,
                                 src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
                                 dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
-                                image: &to,
-                                src_access_mask: 0,
-                                dst_access_mask: 0,
-                                subresource_range: vk::ImageSubresourceRange {
-                                    aspect_mask: vk::IMAGE_ASPECT_COLOR,
-                                    base_mip_level: 0,
-                                    level_count: 1,
-                                    base_array_layer: 0,
-                                    layer_count: 1,
-                                },
+                                buffer: to,
+                                offset: 0,
+                                size: vk::WHOLE_SIZE,
                             };
 
                             commands.pipeline_barrier(
                                 vk::PIPELINE_STAGE_TRANSFER,
-                                vk::PIPELINE_STAGE_FRAGMENT_SHADER,
+                                vk::PIPELINE_STAGE_VERTEX_INPUT
+                                    | vk::PIPELINE_STAGE_VERTEX_SHADER
+                                    | vk::PIPELINE_STAGE_FRAGMENT_SHADER
+                                    | vk::PIPELINE_STAGE_COMPUTE_SHADER,
                                 0,
                                 &[],
-                                &[],
                                 &[barrier],
+                                &[],
                             );
                         }
-                    })
-                .expect("failed to record copy buffer to image commands");
+                    }
+                });
+
+                transfer.acquire(&mut queues[0], *queue_family_index, |commands, (graphics, transfer)| {
+                    commands.pipeline_barrier(
+                        vk::PIPELINE_STAGE_TOP_OF_PIPE,
+                        vk::PIPELINE_STAGE_ALL_COMMANDS,
+                        0,
+                        &[],
+                        &[buffer_ownership_barrier(to, transfer, graphics)],
+                        &[],
+                    );
+                });
+            }
+        }
+    }
 
-                let submit_info = vk::SubmitInfo {
-                    wait_semaphores: &[],
-                    wait_stages: &[],
-                    command_buffers: &[&command_buffer],
-                    signal_semaphores: &[],
+    /// Like `copy_buffer_to_buffer`, `copy.from` is read in the background.
+    pub fn copy_buffer_to_image(&mut self, copy: BufferImageCopy<'_>) {
+        match self {
+            Self::Vulkan {
+                queues,
+                queue_family_index,
+                transfer,
+                ..
+            } => {
+                let Buffer::Vulkan { buffer: from, .. } = copy.from else { panic!("not a vulkan buffer") };
+                let Image::Vulkan { image: to, .. } = copy.to else { panic!("not a vulkan image") };
+
+                let subresource_range = || vk::ImageSubresourceRange {
+                    aspect_mask: vk::IMAGE_ASPECT_COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
                 };
 
-                queues[0]
-                    .submit(&[submit_info], None)
-                    .expect("failed to submit buffer copy command buffer");
+                transfer.next_slot();
+
+                //the previous contents are discarded, so there is nothing to release first
+                transfer.submit(*queue_family_index, |commands, ownership| {
+                    commands.transition_image(
+                        to,
+                        vk::ImageLayout::Undefined,
+                        vk::ImageLayout::TransferDst,
+                        subresource_range(),
+                    );
+
+                    let buffer_image_copy = vk::BufferImageCopy {
+                        buffer_offset: copy.src as _,
+                        buffer_row_length: 0,
+                        buffer_image_height: 0,
+                        image_subresource: vk::ImageSubresourceLayers {
+                            aspect_mask: vk::IMAGE_ASPECT_COLOR,
+                            mip_level: 0,
+                            base_array_layer: 0,
+                            layer_count: 1,
+                        },
+                        image_offset: copy.dst_offset,
+                        image_extent: copy.dst_extent,
+                    };
+
+                    commands.copy_buffer_to_image(
+                        from,
+                        to,
+                        vk::ImageLayout::TransferDst,
+                        &[buffer_image_copy],
+                    );
+
+                    let (src_queue_family_index, dst_queue_family_index, dst_stage) =
+                        match ownership {
+                            Some((graphics, transfer)) => {
+                                (transfer, graphics, vk::PIPELINE_STAGE_BOTTOM_OF_PIPE)
+                            }
+                            None => (
+                                vk::QUEUE_FAMILY_IGNORED,
+                                vk::QUEUE_FAMILY_IGNORED,
+                                vk::PIPELINE_STAGE_FRAGMENT_SHADER,
+                            ),
+                        };
+
+                    let barrier = vk::ImageMemoryBarrier {
+                        old_layout: vk::ImageLayout::TransferDst,
+                        new_layout: vk::ImageLayout::ShaderReadOnly,
+                        src_queue_family_index,
+                        dst_queue_family_index,
+                        image: to,
+                        src_access_mask: vk::ACCESS_TRANSFER_WRITE,
+                        dst_access_mask: if ownership.is_some() {
+                            0
+                        } else {
+                            vk::ACCESS_SHADER_READ
+                        },
+                        subresource_range: subresource_range(),
+                    };
+
+                    commands.pipeline_barrier(
+                        vk::PIPELINE_STAGE_TRANSFER,
+                        dst_stage,
+                        0,
+                        &[],
+                        &[],
+                        &[barrier],
+                    );
+                });
+
+                transfer.acquire(&mut queues[0], *queue_family_index, |commands, (graphics, transfer)| {
+                    let barrier = vk::ImageMemoryBarrier {
+                        old_layout: vk::ImageLayout::TransferDst,
+                        new_layout: vk::ImageLayout::ShaderReadOnly,
+                        src_queue_family_index: transfer,
+                        dst_queue_family_index: graphics,
+                        image: to,
+                        src_access_mask: 0,
+                        dst_access_mask: vk::ACCESS_SHADER_READ,
+                        subresource_range: subresource_range(),
+                    };
+
+                    commands.pipeline_barrier(
+                        vk::PIPELINE_STAGE_TOP_OF_PIPE,
+                        vk::PIPELINE_STAGE_FRAGMENT_SHADER,
+                        0,
+                        &[],
+                        &[],
+                        &[barrier],
+                    );
+                });
+            }
+        }
+    }
+
+    /// Waits for the copies out of staging buffers, which can be overwritten afterwards.
+    pub fn wait_for_transfers(&mut self) {
+        match self {
+            Self::Vulkan { transfer, .. } => transfer.wait(),
+        }
+    }
+
+    /// Copies a color image in `TransferSrc` layout into a buffer, waiting for the copy
+    /// to finish so the buffer can be read right away.
+    pub fn copy_image_to_buffer(&mut self, copy: ImageBufferCopy<'_>) {
+        match self {
+            Self::Vulkan {
+                device,
+                queues,
+                command_pool,
+                ..
+            } => {
+                let Image::Vulkan { image: from, .. } = copy.from else { panic!("not a vulkan image") };
+                let Buffer::Vulkan { buffer: to, .. } = copy.to else { panic!("not a vulkan buffer") };
+
+                device
+                    .one_time_submit(&mut queues[0], command_pool, |mut commands| {
+                        let image_buffer_copy = vk::BufferImageCopy {
+                            buffer_offset: copy.dst,
+                            buffer_row_length: 0,
+                            buffer_image_height: 0,
+                            image_subresource: vk::ImageSubresourceLayers {
+                                aspect_mask: vk::IMAGE_ASPECT_COLOR,
+                                mip_level: 0,
+                                base_array_layer: 0,
+                                layer_count: 1,
+                            },
+                            image_offset: copy.src_offset,
+                            image_extent: copy.src_extent,
+                        };
+
+                        commands.copy_image_to_buffer(
+                            from,
+                            vk::ImageLayout::TransferSrc,
+                            to,
+                            &[image_buffer_copy],
+                        );
 
-                queues[0].wait_idle().expect("failed to wait on queue");
+                        Ok(())
+                    })
+                    .expect("failed to copy image to buffer");
             }
         }
     }
 
-    pub fn synchronize(&mut self) {
+    /// Blocks until everything submitted to the device has finished.
+    pub fn wait_idle(&self) -> Result<(), Error> {
+        match self {
+            Device::Vulkan { device, .. } => device
+                .wait_idle()
+                .map_err(|e| check_device_lost(e, "failed to wait on device")),
+        }
+    }
+
+    pub fn synchronize(&mut self) -> Result<(), Error> {
         match self {
             Device::Vulkan { in_flight_fence, .. } => {
                 vk::Fence::wait(&[in_flight_fence], true, u64::MAX)
-                    .expect("failed to wait for fence");
+                    .map_err(|e| check_device_lost(e, "failed to wait for fence"))?;
 
                 vk::Fence::reset(&[in_flight_fence]).expect("failed to reset fence");
+
+                Ok(())
             }
         }
     }
     
-    pub fn draw_call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
+    pub fn draw_call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) -> Result<(), Error> {
         match self {
             Device::Vulkan { 
                 queues,
@@ -377,42 +617,30 @@ impl Device {
 
                 queues[0]
                     .submit(&[submit_info], Some(in_flight_fence))
-                    .expect("failed to submit draw command buffer");
+                    .map_err(|e| check_device_lost(e, "failed to submit draw command buffer"))
             }
         }
     }
 
-    pub fn call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) {
+    pub fn call<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) -> Result<(), Error> {
         match self {
             Device::Vulkan { 
+                device,
                 queues,
-                command_buffer,
+                command_pool,
                 .. 
             } => {
-                command_buffer
-                    .record(|commands| {
+                device
+                    .one_time_submit(&mut queues[0], command_pool, |commands| {
                         let commands = Commands::Vulkan {
                             commands
                         };
 
                         script(commands);
-                    })
-                    .expect("failed to record command buffer");
-
-                let submit_info = vk::SubmitInfo {
-                    wait_semaphores: &[],
-                    wait_stages: &[],
-                    command_buffers: &[command_buffer],
-                    signal_semaphores: &[],
-                };
 
-                queues[0]
-                    .submit(&[submit_info], None)
-                    .expect("failed to submit draw command buffer");
-
-                queues[0]
-                    .wait_idle()
-                    .expect("failed to wait on queue");
+                        Ok(())
+                    })
+                    .map_err(|e| check_device_lost(e, "failed to submit command buffer"))
             }
         }
     }
@@ -436,12 +664,247 @@ impl Device {
                 image_indices: &[image_index],
                 };
 
-                queues[0].present(present_info).map_err(|_| Error::Presentation)
+                queues[0].present(present_info).map_err(|e| match e {
+                    vk::Error::DeviceLost => Error::DeviceLost,
+                    _ => Error::Presentation,
+                })
             }
         }
     }
 }
 
+impl TransferQueue {
+    fn new(
+        device: Rc<vk::Device>,
+        command_pool: &vk::CommandPool,
+        queue_family_index: u32,
+        transfer_queue_family_index: Option<u32>,
+    ) -> Self {
+        let family = transfer_queue_family_index.unwrap_or(queue_family_index);
+
+        let queue = device.queue(family);
+
+        let command_pool_create_info = vk::CommandPoolCreateInfo {
+            queue_family_index: family,
+        };
+
+        let transfer_command_pool = vk::CommandPool::new(device.clone(), command_pool_create_info)
+            .expect("failed to create command pool");
+
+        let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+            command_pool: &transfer_command_pool,
+            level: vk::CommandBufferLevel::Primary,
+            count: TRANSFER_SLOTS as _,
+        };
+
+        let command_buffers =
+            vk::CommandBuffer::allocate(device.clone(), command_buffer_allocate_info)
+                .expect("failed to create command buffer");
+
+        //ownership is released and acquired on the graphics queue
+        let command_buffer_allocate_info = vk::CommandBufferAllocateInfo {
+            command_pool,
+            level: vk::CommandBufferLevel::Primary,
+            count: 2 * TRANSFER_SLOTS as u32,
+        };
+
+        let mut ownership_command_buffers =
+            vk::CommandBuffer::allocate(device.clone(), command_buffer_allocate_info)
+                .expect("failed to create command buffer")
+                .into_iter();
+
+        let slots = command_buffers
+            .into_iter()
+            .map(|command_buffer| TransferSlot {
+                command_buffer,
+                release_command_buffer: ownership_command_buffers.next().unwrap(),
+                acquire_command_buffer: ownership_command_buffers.next().unwrap(),
+                fence: vk::Fence::new(device.clone(), vk::FenceCreateInfo {})
+                    .expect("failed to create fence"),
+                acquire_fence: vk::Fence::new(device.clone(), vk::FenceCreateInfo {})
+                    .expect("failed to create fence"),
+                pending: false,
+                acquire_pending: false,
+            })
+            .collect();
+
+        let release_semaphore = vk::Semaphore::new(device.clone(), vk::SemaphoreCreateInfo {})
+            .expect("failed to create semaphore");
+
+        let transfer_semaphore = vk::Semaphore::new(device.clone(), vk::SemaphoreCreateInfo {})
+            .expect("failed to create semaphore");
+
+        Self {
+            queue_family_index: transfer_queue_family_index,
+            queue,
+            command_pool: transfer_command_pool,
+            slots,
+            slot: 0,
+            release_semaphore,
+            transfer_semaphore,
+            release_pending: false,
+        }
+    }
+
+    //(graphics, transfer) queue family indices, or none if both are the same family
+    fn ownership(&self, queue_family_index: u32) -> Option<(u32, u32)> {
+        self.queue_family_index
+            .map(|transfer_queue_family_index| (queue_family_index, transfer_queue_family_index))
+    }
+
+    //moves on to the next slot, only waiting if its last upload is still running
+    fn next_slot(&mut self) {
+        self.slot = (self.slot + 1) % self.slots.len();
+
+        self.slots[self.slot].reclaim();
+    }
+
+    //everything read from staging memory so far has been copied once this returns
+    fn wait(&mut self) {
+        for slot in &mut self.slots {
+            slot.reclaim();
+        }
+    }
+
+    fn release(
+        &mut self,
+        queue: &mut vk::Queue,
+        queue_family_index: u32,
+        mut script: impl FnMut(&mut vk::Commands<'_>, (u32, u32)),
+    ) {
+        let Some(ownership) = self.ownership(queue_family_index) else { return };
+
+        let slot = &mut self.slots[self.slot];
+
+        slot.release_command_buffer
+            .record(|mut commands| script(&mut commands, ownership))
+            .expect("failed to record release commands");
+
+        //done once the transfer waiting on it signals the slot's fence
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages: &[],
+            command_buffers: &[&slot.release_command_buffer],
+            signal_semaphores: &[&mut self.release_semaphore],
+        };
+
+        queue
+            .submit(&[submit_info], None)
+            .expect("failed to submit release command buffer");
+
+        self.release_pending = true;
+    }
+
+    fn submit(
+        &mut self,
+        queue_family_index: u32,
+        mut script: impl FnMut(&mut vk::Commands<'_>, Option<(u32, u32)>),
+    ) {
+        let ownership = self.ownership(queue_family_index);
+
+        let slot = &mut self.slots[self.slot];
+
+        slot.command_buffer
+            .record(|mut commands| script(&mut commands, ownership))
+            .expect("failed to record transfer commands");
+
+        let mut wait_semaphores = vec![];
+        let mut wait_stages = vec![];
+
+        if self.release_pending {
+            wait_semaphores.push(&self.release_semaphore);
+            wait_stages.push(vk::PIPELINE_STAGE_TRANSFER);
+        }
+
+        let mut signal_semaphores = vec![];
+
+        if ownership.is_some() {
+            signal_semaphores.push(&mut self.transfer_semaphore);
+        }
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &wait_semaphores,
+            wait_stages: &wait_stages,
+            command_buffers: &[&slot.command_buffer],
+            signal_semaphores: &signal_semaphores,
+        };
+
+        self.queue
+            .submit(&[submit_info], Some(&mut slot.fence))
+            .expect("failed to submit transfer command buffer");
+
+        slot.pending = true;
+
+        self.release_pending = false;
+    }
+
+    fn acquire(
+        &mut self,
+        queue: &mut vk::Queue,
+        queue_family_index: u32,
+        mut script: impl FnMut(&mut vk::Commands<'_>, (u32, u32)),
+    ) {
+        let Some(ownership) = self.ownership(queue_family_index) else { return };
+
+        let slot = &mut self.slots[self.slot];
+
+        slot.acquire_command_buffer
+            .record(|mut commands| script(&mut commands, ownership))
+            .expect("failed to record acquire commands");
+
+        let submit_info = vk::SubmitInfo {
+            wait_semaphores: &[&self.transfer_semaphore],
+            wait_stages: &[vk::PIPELINE_STAGE_ALL_COMMANDS],
+            command_buffers: &[&slot.acquire_command_buffer],
+            signal_semaphores: &[],
+        };
+
+        queue
+            .submit(&[submit_info], Some(&mut slot.acquire_fence))
+            .expect("failed to submit acquire command buffer");
+
+        slot.acquire_pending = true;
+    }
+}
+
+impl TransferSlot {
+    fn reclaim(&mut self) {
+        if self.pending {
+            vk::Fence::wait(&[&mut self.fence], true, u64::MAX).expect("failed to wait for fence");
+
+            vk::Fence::reset(&[&mut self.fence]).expect("failed to reset fence");
+
+            self.pending = false;
+        }
+
+        if self.acquire_pending {
+            vk::Fence::wait(&[&mut self.acquire_fence], true, u64::MAX)
+                .expect("failed to wait for fence");
+
+            vk::Fence::reset(&[&mut self.acquire_fence]).expect("failed to reset fence");
+
+            self.acquire_pending = false;
+        }
+    }
+}
+
+fn buffer_ownership_barrier(
+    buffer: &vk::Buffer,
+    src_queue_family_index: u32,
+    dst_queue_family_index: u32,
+) -> vk::BufferMemoryBarrier<'_> {
+    //the whole buffer changes hands so regions outside the copy are kept
+    vk::BufferMemoryBarrier {
+        src_access_mask: vk::ACCESS_MEMORY_WRITE,
+        dst_access_mask: vk::ACCESS_MEMORY_READ | vk::ACCESS_MEMORY_WRITE,
+        src_queue_family_index,
+        dst_queue_family_index,
+        buffer,
+        offset: 0,
+        size: vk::WHOLE_SIZE,
+    }
+}
+
 #[non_exhaustive]
 pub enum Commands<'a> {
     Vulkan {
@@ -450,12 +913,23 @@ pub enum Commands<'a> {
 }
 
 impl Commands<'_> {
-    pub fn begin_render_pass(&mut self, info: RenderPassBeginInfo<'_>) {
+    /// The returned commands record into the render pass, which ends once they are dropped.
+    pub fn begin_render_pass(&mut self, info: RenderPassBeginInfo<'_>) -> RenderPassCommands<'_> {
         match self {
             Self::Vulkan { commands } => {
                 let RenderPass::Vulkan { render_pass, .. } = info.render_pass else { panic!("not a vulkan render pass") };
                 let Framebuffer::Vulkan { framebuffer, extent, .. } = info.framebuffer else { panic!("not a vulkan framebuffer") };
 
+                let mut clear_values = info
+                    .color_clear_values
+                    .iter()
+                    .map(|&rgba| vk::ClearValue::Color(rgba))
+                    .collect::<Vec<_>>();
+
+                if let Some((depth, stencil)) = info.depth_stencil_clear_value {
+                    clear_values.push(vk::ClearValue::DepthStencil { depth, stencil });
+                }
+
                 let info = vk::RenderPassBeginInfo {
                     render_pass: &render_pass,
                     framebuffer: &framebuffer,
@@ -463,104 +937,87 @@ impl Commands<'_> {
                         offset: (0, 0),
                         extent: (extent.0, extent.1),
                     },
-                    color_clear_values: &info.color_clear_values,
-                    depth_stencil_clear_value: info.depth_stencil_clear_value,
+                    clear_values: &clear_values,
                 };
 
-                commands.begin_render_pass(info);
+                let mut commands = commands.begin_render_pass(info);
+
+                commands.set_viewport(
+                    0,
+                    &[vk::Viewport {
+                        x: 0.0,
+                        y: 0.0,
+                        width: extent.0 as f32,
+                        height: extent.1 as f32,
+                        min_depth: 0.0,
+                        max_depth: 1.0,
+                    }],
+                );
+
+                commands.set_scissor(
+                    0,
+                    &[vk::Rect2d {
+                        offset: (0, 0),
+                        extent: (extent.0, extent.1),
+                    }],
+                );
+
+                RenderPassCommands::Vulkan { commands }
             }
         }
     }
 
-    pub fn end_render_pass(&mut self) {
+    pub fn begin_label(&mut self, name: &str, color: [f32; 4]) {
         match self {
             Self::Vulkan { commands } => {
-                commands.end_render_pass();
+                commands.begin_label(name, color);
             }
         }
-        
     }
-    
-    pub fn next_subpass(&mut self) {
+
+    pub fn end_label(&mut self) {
         match self {
             Self::Vulkan { commands } => {
-                commands.next_subpass();
+                commands.end_label();
             }
         }
-        
     }
-    
-    pub fn bind_pipeline(&mut self, image_index: u32, pipeline: &Pipeline) {
-        match self {
-            Self::Vulkan { commands } => {
-                let Pipeline::Vulkan { descriptor_sets, pipeline, pipeline_layout, bind_point, .. } = pipeline else { panic!("not a vulkan pipeline") };
 
-                commands.bind_pipeline(*bind_point, pipeline);
-                commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], &[]);
-            }
-        }
-        
-    }
-    
-    pub fn bind_vertex_buffers(&mut self, 
-        first_binding: u32,
-        buffers: &'_ [&'_ Buffer],
-        offsets: &'_ [usize],
-        ) {
+    pub fn insert_label(&mut self, name: &str, color: [f32; 4]) {
         match self {
             Self::Vulkan { commands } => {
-                 let buffers = buffers.iter().map(|buffer| 
-                     {
-                        let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
-
-                        buffer
-                     }).collect::<Vec<_>>();
-
-                 commands.bind_vertex_buffers(first_binding, &buffers, offsets);
+                commands.insert_label(name, color);
             }
         }
-        
     }
     
-    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize) {
+    pub fn bind_pipeline(&mut self, image_index: u32, pipeline: &Pipeline) {
         match self {
             Self::Vulkan { commands } => {
-                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
-                
-                commands.bind_index_buffer(buffer, offset, vk::IndexType::Uint16);
+                let Pipeline::Vulkan { descriptor_sets, pipeline, pipeline_layout, bind_point, .. } = pipeline else { panic!("not a vulkan pipeline") };
+
+                commands.bind_pipeline(*bind_point, pipeline);
+                commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], &[]);
             }
         }
         
     }
-    
-    pub fn draw(&mut self,
-        vertex_count: u32,
-        instance_count: u32,
-        first_vertex: u32,
-        first_instance: u32,
-        ) {
+
+    /// Runs the bound compute pipeline over a grid of workgroups.
+    pub fn dispatch(&mut self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
         match self {
             Self::Vulkan { commands } => {
-                commands.draw(vertex_count, instance_count, first_vertex, first_instance);
+                commands.dispatch(group_count_x, group_count_y, group_count_z);
             }
         }
-        
     }
     
-    pub fn draw_indexed(&mut self,
-        index_count: u32,
-        instance_count: u32,
-        first_index: u32,
-        vertex_offset: i32,
-        first_instance: u32,
-
-        ) {
+    pub fn set_line_width(&mut self, line_width: f32) {
         match self {
             Self::Vulkan { commands } => {
-                commands.draw_indexed(index_count, instance_count, first_index, vertex_offset, first_instance);
+                commands.set_line_width(line_width);
             }
         }
-        
     }
 
     pub fn pipeline_barrier(&mut self, src_stage: PipelineStage, dst_stage: PipelineStage, barriers: &'_ [Barrier]) {
@@ -649,6 +1106,130 @@ old_layout: old_layout.clone().into(),
     }
 }
 
+#[non_exhaustive]
+pub enum RenderPassCommands<'a> {
+    Vulkan {
+        commands: vk::RenderPassCommands<'a>,
+    }
+}
+
+impl RenderPassCommands<'_> {
+    pub fn end(self) {}
+
+    pub fn next_subpass(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.next_subpass();
+            }
+        }
+    }
+
+    pub fn begin_label(&mut self, name: &str, color: [f32; 4]) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.begin_label(name, color);
+            }
+        }
+    }
+
+    pub fn end_label(&mut self) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.end_label();
+            }
+        }
+    }
+
+    pub fn insert_label(&mut self, name: &str, color: [f32; 4]) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.insert_label(name, color);
+            }
+        }
+    }
+    
+    pub fn bind_pipeline(&mut self, image_index: u32, pipeline: &Pipeline) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Pipeline::Vulkan { descriptor_sets, pipeline, pipeline_layout, bind_point, .. } = pipeline else { panic!("not a vulkan pipeline") };
+
+                commands.bind_pipeline(*bind_point, pipeline);
+                commands.bind_descriptor_sets(*bind_point, pipeline_layout, 0, &[&descriptor_sets[image_index as usize]], &[]);
+            }
+        }
+        
+    }
+    
+    pub fn bind_vertex_buffers(&mut self, 
+        first_binding: u32,
+        buffers: &'_ [&'_ Buffer],
+        offsets: &'_ [usize],
+        ) {
+        match self {
+            Self::Vulkan { commands } => {
+                 let buffers = buffers.iter().map(|buffer| 
+                     {
+                        let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+
+                        buffer
+                     }).collect::<Vec<_>>();
+
+                 commands.bind_vertex_buffers(first_binding, &buffers, offsets);
+            }
+        }
+        
+    }
+    
+    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize, index_type: IndexType) {
+        match self {
+            Self::Vulkan { commands } => {
+                let Buffer::Vulkan { buffer, .. } = buffer else { panic!("not a vulkan buffer") };
+                
+                commands.bind_index_buffer(buffer, offset, index_type.into());
+            }
+        }
+        
+    }
+    
+    pub fn set_line_width(&mut self, line_width: f32) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.set_line_width(line_width);
+            }
+        }
+    }
+
+    pub fn draw(&mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+        ) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.draw(vertex_count, instance_count, first_vertex, first_instance);
+            }
+        }
+        
+    }
+    
+    pub fn draw_indexed(&mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+
+        ) {
+        match self {
+            Self::Vulkan { commands } => {
+                commands.draw_indexed(index_count, instance_count, first_index, vertex_offset, first_instance);
+            }
+        }
+        
+    }
+}
+
 pub enum Barrier<'a> {
     Memory {
         src_access: Access,
@@ -2,4 +2,14 @@
 pub enum Error {
     Acquisition,
     Presentation,
+    //the driver reset or crashed, everything made from the device has to be recreated
+    DeviceLost,
+}
+
+//a lost device can be recovered from by recreating it, anything else is a bug
+pub(crate) fn check_device_lost(error: vk::Error, message: &str) -> Error {
+    match error {
+        vk::Error::DeviceLost => Error::DeviceLost,
+        error => panic!("{}: {:?}", message, error),
+    }
 }
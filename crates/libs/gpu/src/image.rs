@@ -126,7 +126,7 @@ pub enum Image {
     Vulkan {
         image: vk::Image,
         format: vk::Format,
-        memory: Option<vk::Memory>,
+        memory: Option<Suballocation>,
         view: vk::ImageView,
         sampler: vk::Sampler,
     },
@@ -136,12 +136,12 @@ impl Image {
     pub fn new(info: ImageInfo<'_>) -> Self {
         match info.device {
             Device::Vulkan {
-                physical_device,
                 device,
+                memory_pool,
                 ..
             } => {
                 let (image, memory) = Self::new_managed_vk_image(
-                    &physical_device,
+                    memory_pool,
                     device.clone(),
                     info.format.into(),
                     info.usage.to_vk(),
@@ -168,14 +168,15 @@ impl Image {
     }
 
     pub(crate) fn new_managed_vk_image(
-        physical_device: &vk::PhysicalDevice,
+        memory_pool: &MemoryPool,
         device: Rc<vk::Device>,
         format: vk::Format,
         image_usage: u32,
         image_type: vk::ImageType,
         extent: (u32, u32, u32),
-    ) -> (vk::Image, vk::Memory) {
+    ) -> (vk::Image, Suballocation) {
         let image_create_info = vk::ImageCreateInfo {
+            flags: 0,
             image_type,
             format,
             extent,
@@ -190,21 +191,16 @@ impl Image {
         let mut image =
             vk::Image::new(device.clone(), image_create_info).expect("failed to allocate image");
 
-        let memory_allocate_info = vk::MemoryAllocateInfo {
-            property_flags: vk::MEMORY_PROPERTY_DEVICE_LOCAL,
-        };
-
-        let memory = vk::Memory::allocate(
-            device.clone(),
-            memory_allocate_info,
-            image.memory_requirements(),
-            physical_device.memory_properties(),
-            false,
-        )
-        .expect("failed to allocate memory");
+        let memory = memory_pool
+            .allocate(
+                &image.memory_requirements(),
+                MemoryProperties::DEVICE_LOCAL,
+                false,
+            )
+            .expect("failed to allocate memory");
 
         image
-            .bind_memory(&memory)
+            .bind_memory_at(memory.memory(), memory.offset())
             .expect("failed to bind memory to image");
 
         (image, memory)
@@ -254,6 +250,7 @@ impl Image {
             max_lod: 0.0,
             border_color: vk::BorderColor::IntTransparentBlack,
             unnormalized_coordinates: false,
+            reduction_mode: None,
         };
 
         let sampler = vk::Sampler::new(device.clone(), sampler_create_info)
@@ -1,3 +1,8 @@
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+use std::rc::Rc;
+
 use bitflags::bitflags;
 
 bitflags! {
@@ -12,18 +17,382 @@ impl MemoryProperties {
     pub(crate) fn to_vk(self) -> u32 {
         let mut vk = 0;
 
-        if self == Self::DEVICE_LOCAL {
+        if self.contains(Self::DEVICE_LOCAL) {
             vk |= vk::MEMORY_PROPERTY_DEVICE_LOCAL;
         }
 
-        if self == Self::HOST_VISIBLE {
+        if self.contains(Self::HOST_VISIBLE) {
             vk |= vk::MEMORY_PROPERTY_HOST_VISIBLE;
         }
 
-        if self == Self::HOST_COHERENT {
+        if self.contains(Self::HOST_COHERENT) {
             vk |= vk::MEMORY_PROPERTY_HOST_COHERENT;
         }
 
         vk
     }
 }
+
+//large enough that a voxel world stays well below max memory allocation count
+const MEMORY_BLOCK_SIZE: u64 = 64 * 1024 * 1024;
+
+struct MemoryBlock {
+    memory: vk::Memory,
+    memory_type: u32,
+    linear: bool,
+    mapped: bool,
+    free: RefCell<FreeList>,
+}
+
+//free ranges of a block as (offset, size), sorted by offset
+struct FreeList {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl FreeList {
+    fn new(size: u64) -> Self {
+        Self {
+            ranges: vec![(0, size)],
+        }
+    }
+
+    fn allocate(&mut self, size: u64, alignment: u64) -> Option<u64> {
+        let free = &mut self.ranges;
+
+        let (i, offset) = free.iter().enumerate().find_map(|(i, &(offset, range))| {
+            let aligned = align(offset, alignment);
+            (aligned + size <= offset + range).then_some((i, aligned))
+        })?;
+
+        let (range_offset, range) = free[i];
+
+        //keep whatever is left on either side of the allocation
+        let before = (range_offset, offset - range_offset);
+        let after = (offset + size, range_offset + range - offset - size);
+
+        free.splice(
+            i..=i,
+            [before, after].into_iter().filter(|&(_, range)| range > 0),
+        );
+
+        Some(offset)
+    }
+
+    fn free(&mut self, offset: u64, size: u64) {
+        let free = &mut self.ranges;
+
+        let i = free.partition_point(|&(o, _)| o < offset);
+
+        free.insert(i, (offset, size));
+
+        //coalesce with the next range, then the previous one
+        if i + 1 < free.len() && free[i].0 + free[i].1 == free[i + 1].0 {
+            free[i].1 += free[i + 1].1;
+            free.remove(i + 1);
+        }
+
+        if i > 0 && free[i - 1].0 + free[i - 1].1 == free[i].0 {
+            free[i - 1].1 += free[i].1;
+            free.remove(i);
+        }
+    }
+}
+
+fn align(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) & !(alignment - 1)
+}
+
+/// Range of a pooled `DeviceMemory` block, handed back to the block when dropped.
+pub struct Suballocation {
+    block: Rc<MemoryBlock>,
+    offset: u64,
+    size: u64,
+}
+
+impl Suballocation {
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) fn memory(&self) -> &vk::Memory {
+        &self.block.memory
+    }
+
+    pub fn write<'a, T: 'a>(
+        &self,
+        offset: usize,
+        mut script: impl FnMut(&'a mut [T]),
+    ) -> Result<(), vk::Error> {
+        if offset > self.size as _ {
+            panic!("attempt to overflow buffer");
+        }
+
+        let len = (self.size as usize - offset) / mem::size_of::<T>();
+
+        self.block
+            .memory
+            .write(self.offset as usize + offset, |slice: &'a mut [T]| {
+                script(&mut slice[..len])
+            })
+    }
+
+    pub fn read<T: vk::Pod>(&self, offset: u64, data: &mut [T]) -> Result<(), vk::Error> {
+        if offset + mem::size_of_val(data) as u64 > self.size {
+            panic!("attempt to overflow buffer");
+        }
+
+        self.block.memory.read(self.offset + offset, data)
+    }
+}
+
+impl Drop for Suballocation {
+    fn drop(&mut self) {
+        self.block.free.borrow_mut().free(self.offset, self.size);
+    }
+}
+
+/// Hands out suballocations of large `DeviceMemory` blocks instead of
+/// allocating memory per resource. Blocks are kept until the pool is dropped.
+pub struct MemoryPool {
+    device: Rc<vk::Device>,
+    physical_device: Rc<vk::PhysicalDevice>,
+    blocks: RefCell<Vec<Rc<MemoryBlock>>>,
+}
+
+impl MemoryPool {
+    pub(crate) fn new(device: Rc<vk::Device>, physical_device: Rc<vk::PhysicalDevice>) -> Self {
+        Self {
+            device,
+            physical_device,
+            blocks: RefCell::new(vec![]),
+        }
+    }
+
+    //buffers and optimal images never share a block, so buffer image granularity never applies
+    pub(crate) fn allocate(
+        &self,
+        requirements: &vk::MemoryRequirements,
+        properties: MemoryProperties,
+        linear: bool,
+    ) -> Result<Suballocation, vk::Error> {
+        let memory_type = self
+            .physical_device
+            .memory_properties()
+            .find_memory_type(requirements.memory_type_bits(), properties.to_vk())
+            .ok_or(vk::Error::OutOfDeviceMemory)?;
+
+        let mapped = properties.contains(MemoryProperties::HOST_VISIBLE);
+
+        let size = requirements.size();
+        let alignment = requirements.alignment().max(1);
+
+        let mut blocks = self.blocks.borrow_mut();
+
+        let compatible = |block: &&Rc<MemoryBlock>| {
+            block.memory_type == memory_type && block.linear == linear && block.mapped == mapped
+        };
+
+        for block in blocks.iter().filter(compatible) {
+            if let Some(offset) = block.free.borrow_mut().allocate(size, alignment) {
+                return Ok(Suballocation {
+                    block: block.clone(),
+                    offset,
+                    size,
+                });
+            }
+        }
+
+        let block_size = cmp::max(MEMORY_BLOCK_SIZE, size);
+
+        let memory =
+            vk::Memory::allocate_from_type(self.device.clone(), memory_type, block_size, mapped)?;
+
+        let block = Rc::new(MemoryBlock {
+            memory,
+            memory_type,
+            linear,
+            mapped,
+            free: RefCell::new(FreeList::new(block_size)),
+        });
+
+        let offset = block
+            .free
+            .borrow_mut()
+            .allocate(size, alignment)
+            .expect("fresh memory block is too small");
+
+        blocks.push(block.clone());
+
+        Ok(Suballocation {
+            block,
+            offset,
+            size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn memory_properties_to_vk() {
+        let properties = MemoryProperties::DEVICE_LOCAL | MemoryProperties::HOST_VISIBLE;
+
+        assert_eq!(
+            properties.to_vk(),
+            vk::MEMORY_PROPERTY_DEVICE_LOCAL | vk::MEMORY_PROPERTY_HOST_VISIBLE
+        );
+        assert_eq!(
+            MemoryProperties::DEVICE_LOCAL.to_vk(),
+            vk::MEMORY_PROPERTY_DEVICE_LOCAL
+        );
+    }
+
+    #[test]
+    fn allocate_pads_to_alignment() {
+        let mut free = FreeList::new(1024);
+
+        assert_eq!(free.allocate(3, 1), Some(0));
+        assert_eq!(free.allocate(16, 256), Some(256));
+
+        //the padding in front of the aligned allocation stays usable
+        assert_eq!(free.ranges, [(3, 253), (272, 752)]);
+        assert_eq!(free.allocate(200, 4), Some(4));
+    }
+
+    //three back to back allocations filling the whole list
+    fn full(size: u64) -> (FreeList, [u64; 3]) {
+        let mut free = FreeList::new(3 * size);
+
+        let offsets = [(); 3].map(|_| free.allocate(size, 1).unwrap());
+
+        assert!(free.ranges.is_empty());
+
+        (free, offsets)
+    }
+
+    #[test]
+    fn free_coalesces_with_next() {
+        let (mut free, [_, b, c]) = full(100);
+
+        free.free(c, 100);
+        free.free(b, 100);
+
+        assert_eq!(free.ranges, [(100, 200)]);
+    }
+
+    #[test]
+    fn free_coalesces_with_previous() {
+        let (mut free, [a, b, _]) = full(100);
+
+        free.free(a, 100);
+        free.free(b, 100);
+
+        assert_eq!(free.ranges, [(0, 200)]);
+    }
+
+    #[test]
+    fn free_coalesces_with_both() {
+        let (mut free, [a, b, c]) = full(100);
+
+        free.free(a, 100);
+        free.free(c, 100);
+
+        assert_eq!(free.ranges, [(0, 100), (200, 100)]);
+
+        free.free(b, 100);
+
+        assert_eq!(free.ranges, [(0, 300)]);
+    }
+
+    #[test]
+    fn refill_after_free() {
+        const SIZE: u64 = 64 * 1024;
+
+        let mut free = FreeList::new(SIZE);
+
+        //thousands of small, oddly sized and aligned allocations like buffers would make
+        let mut allocations = vec![];
+
+        for i in 0..4096 {
+            let size = 1 + i % 13;
+            let alignment = 1 << (i % 4);
+
+            match free.allocate(size, alignment) {
+                Some(offset) => {
+                    assert_eq!(offset % alignment, 0);
+                    allocations.push((offset, size));
+                }
+                None => break,
+            }
+        }
+
+        assert!(allocations.len() > 1000);
+
+        let mut sorted = allocations.clone();
+        sorted.sort();
+
+        for pair in sorted.windows(2) {
+            assert!(pair[0].0 + pair[0].1 <= pair[1].0, "{:?} overlaps", pair);
+        }
+
+        //free every other allocation first so both coalescing directions happen
+        for &(offset, size) in allocations.iter().step_by(2) {
+            free.free(offset, size);
+        }
+
+        for &(offset, size) in allocations.iter().skip(1).step_by(2) {
+            free.free(offset, size);
+        }
+
+        assert_eq!(free.ranges, [(0, SIZE)]);
+        assert_eq!(free.allocate(SIZE, 1), Some(0));
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn small_buffers_reuse_blocks() {
+        let context = Context::start_headless();
+
+        let device = Device::choose_best(DeviceInfo {
+            context: &context,
+            surface: None,
+            preferred_device: None,
+        });
+
+        let Device::Vulkan { memory_pool, .. } = &device;
+
+        let buffers = || {
+            (0..4096)
+                .map(|_| {
+                    Buffer::new(BufferInfo {
+                        device: &device,
+                        usage: BufferUsage::STORAGE,
+                        properties: MemoryProperties::DEVICE_LOCAL,
+                        size: 256,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        drop(buffers());
+
+        let blocks = memory_pool.blocks.borrow().len();
+
+        //every dropped buffer handed its range back
+        for block in memory_pool.blocks.borrow().iter() {
+            assert_eq!(block.free.borrow().ranges, [(0, block.memory.size())]);
+        }
+
+        let _refill = buffers();
+
+        assert_eq!(memory_pool.blocks.borrow().len(), blocks);
+    }
+}
@@ -2,6 +2,7 @@ use crate::prelude::*;
 
 use std::collections::HashMap;
 use std::iter;
+use std::rc::Rc;
 
 use bitflags::bitflags;
 
@@ -34,8 +35,8 @@ bitflags! {
 }
 
 impl CullMode {
-    pub fn to_vk(self) -> u32 {
-        self.bits()
+    pub fn to_vk(self) -> vk::CullMode {
+        self.bits().into()
     }
 }
 
@@ -94,6 +95,8 @@ pub struct VertexInput {
     pub location: u32,
     pub format: Format,
     pub rate: InputRate,
+    /// Byte offset within the binding's stride, which ends after the furthest attribute.
+    pub offset: u32,
 }
 
 #[derive(Clone, Copy)]
@@ -145,7 +148,6 @@ pub struct GraphicsPipelineInfo<'a> {
     pub descriptor_set_count: u32,
     pub color_count: u32,
     pub subpass: u32,
-    pub extent: (u32, u32),
     pub cull_mode: CullMode,
     pub vertex_shader: &'a Shader,
     pub fragment_shader: Option<&'a Shader>,
@@ -154,6 +156,13 @@ pub struct GraphicsPipelineInfo<'a> {
     pub layout: &'a [Descriptor],
 }
 
+pub struct ComputePipelineInfo<'a> {
+    pub device: &'a Device,
+    pub descriptor_set_count: u32,
+    pub shader: &'a Shader,
+    pub layout: &'a [Descriptor],
+}
+
 pub enum Pipeline {
     Vulkan {
         descriptor_sets: Vec<vk::DescriptorSet>,
@@ -172,45 +181,12 @@ impl Pipeline {
                 descriptor_pool,
                 ..
             } => {
-                let bindings = info
-                    .layout
-                    .iter()
-                    .map(|descriptor| vk::DescriptorSetLayoutBinding {
-                        binding: descriptor.binding,
-                        descriptor_type: descriptor.ty.into(),
-                        descriptor_count: descriptor.count,
-                        stage: descriptor.stage.to_vk(),
-                    })
-                    .collect::<Vec<_>>();
-
-                let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
-                    bindings: &bindings,
-                };
-
-                let descriptor_set_layout =
-                    vk::DescriptorSetLayout::new(device.clone(), descriptor_set_layout_create_info)
-                        .expect("failed to create descriptor set layout");
-
-                let set_layouts = iter::repeat(&descriptor_set_layout)
-                    .take(info.descriptor_set_count as _)
-                    .collect::<Vec<_>>();
-
-                let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
-                    descriptor_pool: &descriptor_pool,
-                    set_layouts: &set_layouts,
-                };
-
-                let descriptor_sets =
-                    vk::DescriptorSet::allocate(device.clone(), descriptor_set_allocate_info)
-                        .expect("failed to allocate descriptor sets");
-
-                let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
-                    set_layouts: &[&descriptor_set_layout],
-                };
-
-                let pipeline_layout =
-                    vk::PipelineLayout::new(device.clone(), pipeline_layout_create_info)
-                        .expect("failed to create pipeline layout");
+                let (descriptor_sets, descriptor_set_layout, pipeline_layout) = pipeline_layout(
+                    device,
+                    descriptor_pool,
+                    info.layout,
+                    info.descriptor_set_count,
+                );
 
                 let render_pass = if let RenderPass::Vulkan { render_pass } = info.render_pass {
                     render_pass
@@ -276,8 +252,9 @@ impl Pipeline {
 
                     let stride = input
                         .iter()
-                        .map(|input| input.format.to_bytes())
-                        .sum::<usize>();
+                        .map(|input| input.offset as usize + input.format.to_bytes())
+                        .max()
+                        .unwrap_or_default();
 
                     let input_rate = input[0].rate.into();
 
@@ -289,22 +266,12 @@ impl Pipeline {
 
                     bindings.push(vertex_binding);
 
-                    for (x, i) in input.iter().enumerate() {
-                        let location = i.location;
-
-                        let format = i.format.into();
-
-                        let offset = input
-                            .iter()
-                            .take(x)
-                            .map(|i| i.format.to_bytes() as u32)
-                            .sum::<u32>();
-
+                    for i in input.iter() {
                         let vertex_attribute = vk::VertexInputAttributeDescription {
                             binding,
-                            location,
-                            format,
-                            offset,
+                            location: i.location,
+                            format: i.format.into(),
+                            offset: i.offset,
                         };
 
                         attributes.push(vertex_attribute);
@@ -323,18 +290,20 @@ impl Pipeline {
 
                 let tessellation_state = vk::PipelineTessellationStateCreateInfo {};
 
+                //viewport and scissor are dynamic, begin_render_pass sets them to the
+                //framebuffer extent so pipelines survive a resize
                 let viewport = vk::Viewport {
                     x: 0.0,
                     y: 0.0,
-                    width: info.extent.0 as f32,
-                    height: info.extent.1 as f32,
+                    width: 1.0,
+                    height: 1.0,
                     min_depth: 0.0,
                     max_depth: 1.0,
                 };
 
                 let scissor = vk::Rect2d {
                     offset: (0, 0),
-                    extent: info.extent,
+                    extent: (1, 1),
                 };
 
                 let viewport_state = vk::PipelineViewportStateCreateInfo {
@@ -390,10 +359,11 @@ impl Pipeline {
                 };
 
                 let dynamic_state = vk::PipelineDynamicStateCreateInfo {
-                    dynamic_states: &[],
+                    dynamic_states: &[vk::DynamicState::Viewport, vk::DynamicState::Scissor],
                 };
 
                 let present_pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+                    flags: 0,
                     stages: &stages,
                     vertex_input_state: &vertex_input_info,
                     input_assembly_state: &input_assembly,
@@ -405,7 +375,8 @@ impl Pipeline {
                     color_blend_state: &color_blending,
                     dynamic_state: &dynamic_state,
                     layout: &pipeline_layout,
-                    render_pass: &render_pass,
+                    render_pass: Some(&render_pass),
+                    rendering: None,
                     subpass: info.subpass,
                     base_pipeline: None,
                     base_pipeline_index: -1,
@@ -430,6 +401,59 @@ impl Pipeline {
         }
     }
 
+    /// Dispatched through `Commands::bind_pipeline` and `Commands::dispatch`, outside of
+    /// any render pass.
+    pub fn new_compute_pipeline(info: ComputePipelineInfo<'_>) -> Self {
+        match info.device {
+            Device::Vulkan {
+                device,
+                descriptor_pool,
+                ..
+            } => {
+                let (descriptor_sets, descriptor_set_layout, pipeline_layout) = pipeline_layout(
+                    device,
+                    descriptor_pool,
+                    info.layout,
+                    info.descriptor_set_count,
+                );
+
+                let Shader::Vulkan {
+                    shader_module,
+                    entry,
+                    ..
+                } = info.shader;
+
+                let compute_pipeline_create_info = vk::ComputePipelineCreateInfo {
+                    flags: 0,
+                    stage: vk::PipelineShaderStageCreateInfo {
+                        stage: vk::SHADER_STAGE_COMPUTE,
+                        module: shader_module,
+                        entry_point: entry,
+                    },
+                    layout: &pipeline_layout,
+                    base_pipeline: None,
+                    base_pipeline_index: -1,
+                };
+
+                let pipeline = vk::Pipeline::new_compute_pipelines(
+                    device.clone(),
+                    None,
+                    &[compute_pipeline_create_info],
+                )
+                .expect("failed to create compute pipeline")
+                .remove(0);
+
+                Self::Vulkan {
+                    descriptor_sets,
+                    descriptor_set_layout,
+                    pipeline,
+                    pipeline_layout,
+                    bind_point: vk::PipelineBindPoint::Compute,
+                }
+            }
+        }
+    }
+
     pub fn bind(&mut self, image_index: u32, bindings: &'_ [Binding]) {
         match self {
             Pipeline::Vulkan {
@@ -503,6 +527,7 @@ impl Pipeline {
                         descriptor_type: ty.clone().into(),
                         buffer_infos: &buffer_infos[index..=index],
                         image_infos: &[],
+                        texel_buffer_views: &[],
                     };
 
                     write_descriptors.push(write_descriptor);
@@ -519,13 +544,67 @@ impl Pipeline {
                         descriptor_type: ty.clone().into(),
                         buffer_infos: &[],
                         image_infos: &image_infos[index..=index],
+                        texel_buffer_views: &[],
                     };
 
                     write_descriptors.push(write_descriptor);
                 }
 
-                vk::DescriptorSet::update(&write_descriptors, &[]);
+                vk::DescriptorSet::update(&write_descriptors, &[])
+                    .expect("failed to update descriptor set");
             }
         }
     }
 }
+
+//one descriptor set per frame in flight, all with the same layout
+fn pipeline_layout(
+    device: &Rc<vk::Device>,
+    descriptor_pool: &vk::DescriptorPool,
+    layout: &[Descriptor],
+    descriptor_set_count: u32,
+) -> (
+    Vec<vk::DescriptorSet>,
+    vk::DescriptorSetLayout,
+    vk::PipelineLayout,
+) {
+    let bindings = layout
+        .iter()
+        .map(|descriptor| vk::DescriptorSetLayoutBinding {
+            binding: descriptor.binding,
+            descriptor_type: descriptor.ty.into(),
+            descriptor_count: descriptor.count,
+            stage: descriptor.stage.to_vk(),
+            flags: 0,
+        })
+        .collect::<Vec<_>>();
+
+    let descriptor_set_layout_create_info = vk::DescriptorSetLayoutCreateInfo {
+        bindings: &bindings,
+    };
+
+    let descriptor_set_layout =
+        vk::DescriptorSetLayout::new(device.clone(), descriptor_set_layout_create_info)
+            .expect("failed to create descriptor set layout");
+
+    let set_layouts = iter::repeat(&descriptor_set_layout)
+        .take(descriptor_set_count as _)
+        .collect::<Vec<_>>();
+
+    let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
+        descriptor_pool,
+        set_layouts: &set_layouts,
+    };
+
+    let descriptor_sets = vk::DescriptorSet::allocate(device.clone(), descriptor_set_allocate_info)
+        .expect("failed to allocate descriptor sets");
+
+    let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+        set_layouts: &[&descriptor_set_layout],
+    };
+
+    let pipeline_layout = vk::PipelineLayout::new(device.clone(), pipeline_layout_create_info)
+        .expect("failed to create pipeline layout");
+
+    (descriptor_sets, descriptor_set_layout, pipeline_layout)
+}
@@ -1,7 +1,7 @@
 use crate::prelude::*;
 
 use std::fs;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
@@ -17,6 +17,9 @@ bitflags! {
         const VERTEX = 0x00000001;
         const FRAGMENT = 0x00000010;
         const COMPUTE = 0x00000020;
+        //need a device with mesh shaders
+        const TASK = 0x00000040;
+        const MESH = 0x00000080;
     }
 }
 
@@ -30,6 +33,10 @@ impl ShaderStage {
 pub enum ShaderError {
     Compilation(u32, String),
     InvalidResource,
+    InvalidSpirv(PathBuf),
+    //the asset is missing or could not be read
+    Io(PathBuf, io::ErrorKind),
+    Vulkan(vk::Error),
 }
 
 #[derive(Clone)]
@@ -115,10 +122,9 @@ impl Shader {
                     last_modified = ShaderLastModified::from_input(&info.input);
                 }
 
-                let mut file = fs::File::open(info.input.get_asset().unwrap())
-                    .expect("failed to open shader file");
-
-                let shader_module = Self::load_vk_shader(device.clone(), &mut file);
+                let shader_module =
+                    Self::load_vk_shader(device.clone(), info.input.get_asset().unwrap())
+                        .expect("failed to load shader");
 
                 Self::Vulkan {
                     device: device.clone(),
@@ -172,10 +178,9 @@ impl Shader {
 
                     *last_modified = ShaderLastModified::from_input(&input);
 
-                    let mut file = fs::File::open(input.get_asset().unwrap())
-                        .expect("failed to open shader file");
-
-                    *shader_module = Self::load_vk_shader(device.clone(), &mut file);
+                    //keep the old module around if the new one is unusable
+                    *shader_module =
+                        Self::load_vk_shader(device.clone(), input.get_asset().unwrap())?;
                 }
 
                 Ok(load)
@@ -275,29 +280,39 @@ impl Shader {
         Ok(())
     }
 
-    fn load_vk_shader(device: Rc<vk::Device>, file: &mut fs::File) -> vk::ShaderModule {
-        let mut bytes = vec![];
+    fn load_vk_shader(
+        device: Rc<vk::Device>,
+        path: &Path,
+    ) -> Result<vk::ShaderModule, ShaderError> {
+        let bytes = fs::read(path).map_err(|err| ShaderError::Io(path.to_path_buf(), err.kind()))?;
 
-        file.read_to_end(&mut bytes)
-            .expect("failed to read shader from file");
+        let code = convert_bytes_to_spirv_data(path, &bytes)?;
 
-        let endian = mem::size_of::<u32>() / mem::size_of::<u8>();
+        let shader_module_create_info = vk::ShaderModuleCreateInfo { code: &code[..] };
 
-        if bytes.len() % endian != 0 {
-            panic!("cannot convert bytes to int; too few or too many")
-        }
+        let shader_module = vk::ShaderModule::new(device, shader_module_create_info)
+            .map_err(|err| match err {
+                vk::Error::InvalidShader => ShaderError::InvalidSpirv(path.to_path_buf()),
+                err => ShaderError::Vulkan(err),
+            })?;
 
-        let mut code = Vec::with_capacity(bytes.len() / endian);
+        Ok(shader_module)
+    }
+}
 
-        for slice in bytes.chunks(endian) {
-            code.push(u32::from_le_bytes(slice.try_into().unwrap()));
-        }
+fn convert_bytes_to_spirv_data(path: &Path, bytes: &[u8]) -> Result<Vec<u32>, ShaderError> {
+    let endian = mem::size_of::<u32>() / mem::size_of::<u8>();
 
-        let shader_module_create_info = vk::ShaderModuleCreateInfo { code: &code[..] };
+    //a partially written file from a compile in progress ends up here
+    if bytes.len() % endian != 0 {
+        return Err(ShaderError::InvalidSpirv(path.to_path_buf()));
+    }
 
-        let shader_module = vk::ShaderModule::new(device, shader_module_create_info)
-            .expect("failed to create shader module");
+    let mut code = Vec::with_capacity(bytes.len() / endian);
 
-        shader_module
+    for slice in bytes.chunks(endian) {
+        code.push(u32::from_le_bytes(slice.try_into().unwrap()));
     }
+
+    Ok(code)
 }
@@ -25,6 +25,22 @@ impl Surface {
         }
     }
 
+    /// The present modes `device` supports for this surface, always including `Fifo`.
+    pub fn present_modes(&self, device: &Device) -> Vec<PresentMode> {
+        match (self, device) {
+            (Self::Vulkan { surface }, Device::Vulkan { physical_device, .. }) => physical_device
+                .surface_present_modes(surface)
+                .into_iter()
+                .map(|present_mode| match present_mode {
+                    vk::PresentMode::Immediate => PresentMode::Immediate,
+                    vk::PresentMode::Mailbox => PresentMode::Mailbox,
+                    vk::PresentMode::Fifo => PresentMode::Fifo,
+                    vk::PresentMode::FifoRelaxed => PresentMode::FifoRelaxed,
+                })
+                .collect(),
+        }
+    }
+
     pub(crate) fn get_vk_surface_format(
         &self,
         physical_device: &vk::PhysicalDevice,
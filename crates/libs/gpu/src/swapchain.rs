@@ -6,9 +6,32 @@ use std::rc::Rc;
 pub struct SwapchainInfo<'a> {
     pub device: &'a Device,
     pub surface: &'a Surface,
+    pub present_mode: PresentMode,
+    /// Only used if the surface leaves the extent up to the swapchain, and then clamped to
+    /// what it supports.
+    pub extent: (u32, u32),
     pub old: Option<Swapchain>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PresentMode {
+    Immediate,
+    Mailbox,
+    Fifo,
+    FifoRelaxed,
+}
+
+impl From<PresentMode> for vk::PresentMode {
+    fn from(mode: PresentMode) -> Self {
+        match mode {
+            PresentMode::Immediate => Self::Immediate,
+            PresentMode::Mailbox => Self::Mailbox,
+            PresentMode::Fifo => Self::Fifo,
+            PresentMode::FifoRelaxed => Self::FifoRelaxed,
+        }
+    }
+}
+
 pub struct SwapchainImageFetch<'a> {
     pub device: &'a Device,
     pub surface: &'a Surface,
@@ -23,6 +46,7 @@ pub enum Swapchain {
         swapchain: vk::Swapchain,
         image_available_semaphore: Rc<RefCell<vk::Semaphore>>,
         image_index: u32,
+        present_mode: PresentMode,
     },
 }
 
@@ -41,44 +65,18 @@ impl Swapchain {
                     panic!("not a vulkan surface");
                 };
 
-                let vk::SurfaceCapabilities {
-                    mut min_image_count,
-                    current_transform: pre_transform,
-                    current_extent: image_extent,
-                    ..
-                } = physical_device.surface_capabilities(&surface);
-
-                min_image_count += 1;
-
-                let vk::SurfaceFormat {
-                    format: image_format,
-                    color_space: image_color_space,
-                } = physical_device.surface_format(&surface);
-
-                let present_mode = vk::PresentMode::Immediate;
-
                 let old_swapchain = info.old.map(|old| match old {
                     Self::Vulkan { swapchain, .. } => swapchain,
                     _ => panic!("not a vulkan swapchain"),
                 });
 
-                let swapchain_create_info = vk::SwapchainCreateInfo {
+                let swapchain_create_info = vk_swapchain_create_info(
+                    physical_device,
                     surface,
-                    min_image_count,
-                    image_format,
-                    image_color_space,
-                    image_extent,
-                    image_array_layers: 1,
-                    image_usage: vk::IMAGE_USAGE_COLOR_ATTACHMENT,
-                    //TODO support concurrent image sharing mode
-                    image_sharing_mode: vk::SharingMode::Exclusive,
-                    queue_family_indices: &[],
-                    pre_transform,
-                    composite_alpha: vk::CompositeAlpha::Opaque,
-                    present_mode,
-                    clipped: true,
+                    info.present_mode,
+                    info.extent,
                     old_swapchain,
-                };
+                );
 
                 let mut swapchain = vk::Swapchain::new(device.clone(), swapchain_create_info)
                     .expect("failed to create swapchain");
@@ -90,21 +88,50 @@ impl Swapchain {
                     image_available_semaphore: image_available_semaphore.clone(),
                     swapchain,
                     image_index: 0,
+                    present_mode: info.present_mode,
                 }
             }
         }
     }
 
-    pub fn images(&self) -> Vec<Image> {
+    /// Rebuilds the swapchain in place for the surface's current extent, or `extent` if the
+    /// surface doesn't have one, passing the old handle along. Images fetched before this must
+    /// be fetched again.
+    pub fn recreate(&mut self, extent: (u32, u32)) {
         match self {
             Self::Vulkan {
                 physical_device,
-                device,
                 surface,
                 swapchain,
+                image_index,
+                present_mode,
                 ..
             } => {
-                let vk::SurfaceFormat { format, .. } = physical_device.surface_format(&surface);
+                let swapchain_create_info =
+                    vk_swapchain_create_info(physical_device, surface, *present_mode, extent, None);
+
+                swapchain
+                    .recreate(swapchain_create_info)
+                    .expect("failed to recreate swapchain");
+
+                *image_index = 0;
+            }
+        }
+    }
+
+    /// The extent the current images were created with.
+    pub fn extent(&self) -> (u32, u32) {
+        match self {
+            Self::Vulkan { swapchain, .. } => swapchain.extent(),
+        }
+    }
+
+    pub fn images(&self) -> Vec<Image> {
+        match self {
+            Self::Vulkan {
+                device, swapchain, ..
+            } => {
+                let format = swapchain.format();
 
                 swapchain
                     .images()
@@ -144,10 +171,66 @@ impl Swapchain {
                         Some(&mut image_available_semaphore.borrow_mut()),
                         None,
                     )
-                    .map_err(|_| Error::Acquisition)?;
+                    .map_err(|e| match e {
+                        vk::Error::DeviceLost => Error::DeviceLost,
+                        _ => Error::Acquisition,
+                    })?;
 
                 Ok(*image_index)
             }
         }
     }
 }
+
+fn vk_swapchain_create_info<'a>(
+    physical_device: &vk::PhysicalDevice,
+    surface: &'a vk::Surface,
+    present_mode: PresentMode,
+    extent: (u32, u32),
+    old_swapchain: Option<vk::Swapchain>,
+) -> vk::SwapchainCreateInfo<'a> {
+    //queried every time, the allowed extents change with the window
+    let vk::SurfaceCapabilities {
+        mut min_image_count,
+        current_transform: pre_transform,
+        current_extent,
+        min_image_extent,
+        max_image_extent,
+        ..
+    } = physical_device.surface_capabilities(surface);
+
+    //0xFFFFFFFF means the surface takes its size from the swapchain, e.g. on wayland
+    let image_extent = if current_extent == (u32::MAX, u32::MAX) {
+        (
+            extent.0.clamp(min_image_extent.0, max_image_extent.0),
+            extent.1.clamp(min_image_extent.1, max_image_extent.1),
+        )
+    } else {
+        current_extent
+    };
+
+    min_image_count += 1;
+
+    let vk::SurfaceFormat {
+        format: image_format,
+        color_space: image_color_space,
+    } = physical_device.surface_format(surface);
+
+    vk::SwapchainCreateInfo {
+        surface,
+        min_image_count,
+        image_format,
+        image_color_space,
+        image_extent,
+        image_array_layers: 1,
+        image_usage: vk::IMAGE_USAGE_COLOR_ATTACHMENT,
+        //TODO support concurrent image sharing mode
+        image_sharing_mode: vk::SharingMode::Exclusive,
+        queue_family_indices: &[],
+        pre_transform,
+        composite_alpha: vk::CompositeAlpha::Opaque,
+        present_mode: present_mode.into(),
+        clipped: true,
+        old_swapchain,
+    }
+}
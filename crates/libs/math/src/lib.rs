@@ -3,10 +3,11 @@
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, Sub, SubAssign};
 
 pub mod matrix;
+pub mod quaternion;
 pub mod vector;
 
 pub mod prelude {
-    pub use crate::{matrix::Matrix, vector::Vector};
+    pub use crate::{matrix::Matrix, quaternion::Quaternion, vector::Vector};
 }
 
 pub trait Numeric:
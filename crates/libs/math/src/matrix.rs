@@ -38,6 +38,58 @@ where
     }
 }
 
+impl Matrix<f32, 4, 4> {
+    /// Right handed perspective projection into vulkan clip space, looking down -Z.
+    /// Y is flipped and depth maps `near..far` to `0..1`; swap `near` and `far` for reverse Z.
+    pub fn perspective(fov_y: f32, aspect_ratio: f32, near: f32, far: f32) -> Self {
+        let mut projection = Self::default();
+
+        let focal_length = 1.0 / (fov_y / 2.0).tan();
+
+        projection[0][0] = focal_length / aspect_ratio;
+        projection[1][1] = -focal_length;
+        projection[2][2] = far / (near - far);
+        projection[2][3] = -1.0;
+        projection[3][2] = (near * far) / (near - far);
+        projection
+    }
+
+    /// Right handed orthographic projection into vulkan clip space, looking down -Z.
+    /// Y is flipped and depth maps `near..far` to `0..1`, same as `perspective`.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let mut projection = Self::identity();
+
+        projection[0][0] = 2.0 / (right - left);
+        projection[1][1] = -2.0 / (top - bottom);
+        projection[2][2] = -1.0 / (far - near);
+        projection[3][0] = -(right + left) / (right - left);
+        projection[3][1] = (top + bottom) / (top - bottom);
+        projection[3][2] = -near / (far - near);
+        projection
+    }
+
+    /// Right handed view matrix for a camera at `eye` facing `target`, looking down -Z.
+    /// `up` is +Y in world space, the vulkan Y flip is left to the projection.
+    pub fn look_at(eye: Vector<f32, 3>, target: Vector<f32, 3>, up: Vector<f32, 3>) -> Self {
+        let forward = (target - eye).normalize();
+        let side = forward.cross(up).normalize();
+        let up = side.cross(forward);
+
+        let mut view = Self::identity();
+
+        for i in 0..3 {
+            view[i][0] = side[i];
+            view[i][1] = up[i];
+            view[i][2] = -forward[i];
+        }
+
+        view[3][0] = -side.dot(eye);
+        view[3][1] = -up.dot(eye);
+        view[3][2] = forward.dot(eye);
+        view
+    }
+}
+
 impl<T: Default, const N: usize, const M: usize> Default for Matrix<T, N, M>
 where
     T: Numeric,
@@ -238,3 +290,133 @@ where
         Vector::<T, B>::new(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < EPSILON, "{a} != {b}");
+    }
+
+    //columns are the basis vectors, same as the shaders read them
+    fn transform(matrix: Matrix<f32, 4, 4>, point: [f32; 4]) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        for column in 0..4 {
+            for row in 0..4 {
+                out[row] += matrix[column][row] * point[column];
+            }
+        }
+        out
+    }
+
+    fn depth(matrix: Matrix<f32, 4, 4>, z: f32) -> f32 {
+        let clip = transform(matrix, [0.0, 0.0, z, 1.0]);
+        clip[2] / clip[3]
+    }
+
+    #[test]
+    fn perspective_45_16_9() {
+        let projection = Matrix::perspective(45_f32.to_radians(), 16.0 / 9.0, 0.1, 100.0);
+
+        let mut expected = Matrix::<f32, 4, 4>::default();
+        expected[0][0] = 1.357_995_1;
+        expected[1][1] = -2.414_213_6;
+        expected[2][2] = -1.001_001;
+        expected[2][3] = -1.0;
+        expected[3][2] = -0.100_100_1;
+
+        for column in 0..4 {
+            for row in 0..4 {
+                assert_close(projection[column][row], expected[column][row]);
+            }
+        }
+    }
+
+    #[test]
+    fn perspective_depth_range() {
+        let projection = Matrix::perspective(1.0, 1.0, 0.1, 100.0);
+
+        assert_close(depth(projection, -0.1), 0.0);
+        assert_close(depth(projection, -100.0), 1.0);
+
+        let reverse = Matrix::perspective(1.0, 1.0, 100.0, 0.1);
+
+        assert_close(depth(reverse, -0.1), 1.0);
+        assert_close(depth(reverse, -100.0), 0.0);
+    }
+
+    #[test]
+    fn perspective_handedness() {
+        let projection = Matrix::perspective(1.0, 1.0, 0.1, 100.0);
+
+        //in front of the camera is -Z, and up in view space is down in vulkan clip space
+        let clip = transform(projection, [1.0, 1.0, -10.0, 1.0]);
+
+        assert!(clip[3] > 0.0);
+        assert!(clip[0] > 0.0);
+        assert!(clip[1] < 0.0);
+    }
+
+    #[test]
+    fn orthographic_hand_derived() {
+        let projection = Matrix::orthographic(0.0, 4.0, 0.0, 2.0, 1.0, 11.0);
+
+        let mut expected = Matrix::<f32, 4, 4>::identity();
+        expected[0][0] = 0.5;
+        expected[1][1] = -1.0;
+        expected[2][2] = -0.1;
+        expected[3][0] = -1.0;
+        expected[3][1] = 1.0;
+        expected[3][2] = -0.1;
+
+        for column in 0..4 {
+            for row in 0..4 {
+                assert_close(projection[column][row], expected[column][row]);
+            }
+        }
+
+        //the near bottom left corner is at depth 0 and +Y, which is down in vulkan clip space
+        let near = transform(projection, [0.0, 0.0, -1.0, 1.0]);
+        let far = transform(projection, [4.0, 2.0, -11.0, 1.0]);
+
+        for (a, b) in near.into_iter().zip([-1.0, 1.0, 0.0, 1.0]) {
+            assert_close(a, b);
+        }
+        for (a, b) in far.into_iter().zip([1.0, -1.0, 1.0, 1.0]) {
+            assert_close(a, b);
+        }
+    }
+
+    #[test]
+    fn look_at_down_negative_z() {
+        let view = Matrix::look_at(
+            Vector::new([0.0, 0.0, 0.0]),
+            Vector::new([0.0, 0.0, -1.0]),
+            Vector::new([0.0, 1.0, 0.0]),
+        );
+
+        assert_eq!(view, Matrix::identity());
+    }
+
+    #[test]
+    fn look_at_moves_eye_to_origin() {
+        let view = Matrix::look_at(
+            Vector::new([1.0, 2.0, 3.0]),
+            Vector::new([1.0, 2.0, -5.0]),
+            Vector::new([0.0, 1.0, 0.0]),
+        );
+
+        let eye = transform(view, [1.0, 2.0, 3.0, 1.0]);
+        let target = transform(view, [1.0, 2.0, -5.0, 1.0]);
+
+        for (a, b) in eye.into_iter().zip([0.0, 0.0, 0.0, 1.0]) {
+            assert_close(a, b);
+        }
+        for (a, b) in target.into_iter().zip([0.0, 0.0, -8.0, 1.0]) {
+            assert_close(a, b);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::{Mul, MulAssign};
+
+use crate::{matrix::Matrix, vector::Vector};
+
+/// Rotation stored as `x * i + y * j + z * k + w`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+impl Quaternion<f32> {
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Rotation of `angle` radians counter clockwise around `axis`.
+    pub fn from_axis_angle(axis: Vector<f32, 3>, angle: f32) -> Self {
+        let axis = axis.normalize();
+
+        let (sin, cos) = (angle / 2.0).sin_cos();
+
+        Self::new(axis[0] * sin, axis[1] * sin, axis[2] * sin, cos)
+    }
+
+    pub fn magnitude(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    }
+
+    pub fn normalize(self) -> Self {
+        let magnitude = self.magnitude();
+
+        Self::new(
+            self.x / magnitude,
+            self.y / magnitude,
+            self.z / magnitude,
+            self.w / magnitude,
+        )
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn dot(self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z + self.w * rhs.w
+    }
+
+    /// Interpolates between unit quaternions along the shorter arc, `t` runs from `0..1`.
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+
+        //q and -q are the same rotation, flip to take the shorter way round
+        let other = if cos < 0.0 {
+            cos = -cos;
+            Self::new(-other.x, -other.y, -other.z, -other.w)
+        } else {
+            other
+        };
+
+        let (a, b) = if cos > 0.9995 {
+            //nearly parallel, the sine below would vanish
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+
+        Self::new(
+            self.x * a + other.x * b,
+            self.y * a + other.y * b,
+            self.z * a + other.z * b,
+            self.w * a + other.w * b,
+        )
+        .normalize()
+    }
+
+    pub fn rotate(self, vector: Vector<f32, 3>) -> Vector<f32, 3> {
+        let q = Vector::<f32, 3>::new([self.x, self.y, self.z]);
+
+        let t = q.cross(vector) * 2.0;
+
+        vector + t * self.w + q.cross(t)
+    }
+
+    /// Converts a unit quaternion to a rotation matrix in the column layout used for
+    /// camera and model matrices. `(a * b).to_matrix()` rotates by `b` first, then `a`.
+    pub fn to_matrix(self) -> Matrix<f32, 4, 4> {
+        let Self { x, y, z, w } = self;
+
+        let mut matrix = Matrix::identity();
+
+        matrix[0][0] = 1.0 - 2.0 * (y * y + z * z);
+        matrix[0][1] = 2.0 * (x * y + w * z);
+        matrix[0][2] = 2.0 * (x * z - w * y);
+
+        matrix[1][0] = 2.0 * (x * y - w * z);
+        matrix[1][1] = 1.0 - 2.0 * (x * x + z * z);
+        matrix[1][2] = 2.0 * (y * z + w * x);
+
+        matrix[2][0] = 2.0 * (x * z + w * y);
+        matrix[2][1] = 2.0 * (y * z - w * x);
+        matrix[2][2] = 1.0 - 2.0 * (x * x + y * y);
+
+        matrix
+    }
+}
+
+impl Default for Quaternion<f32> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+//hamilton product, applies rhs first
+impl Mul for Quaternion<f32> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+        )
+    }
+}
+
+impl MulAssign for Quaternion<f32> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_close(a: Vector<f32, 3>, b: Vector<f32, 3>) {
+        assert!(a.distance(&b) < EPSILON, "{a:?} != {b:?}");
+    }
+
+    fn assert_same(a: Quaternion<f32>, b: Quaternion<f32>) {
+        assert!((a.dot(b) - 1.0).abs() < EPSILON, "{a:?} != {b:?}");
+    }
+
+    fn x() -> Vector<f32, 3> {
+        Vector::new([1.0, 0.0, 0.0])
+    }
+
+    fn y() -> Vector<f32, 3> {
+        Vector::new([0.0, 1.0, 0.0])
+    }
+
+    fn z() -> Vector<f32, 3> {
+        Vector::new([0.0, 0.0, 1.0])
+    }
+
+    #[test]
+    fn axis_angle_rotates_counter_clockwise() {
+        let rotation = Quaternion::from_axis_angle(z(), FRAC_PI_2);
+
+        assert_close(rotation.rotate(x()), y());
+        assert_close(rotation.rotate(y()), x() * -1.0);
+        assert_close(rotation.rotate(z()), z());
+    }
+
+    #[test]
+    fn composition_applies_rhs_first() {
+        let yaw = Quaternion::from_axis_angle(y(), FRAC_PI_2);
+        let pitch = Quaternion::from_axis_angle(x(), FRAC_PI_2);
+
+        //pitch takes y to z, then yaw takes z to x
+        assert_close((yaw * pitch).rotate(y()), x());
+        //yaw leaves y alone, then pitch takes it to z
+        assert_close((pitch * yaw).rotate(y()), z());
+    }
+
+    #[test]
+    fn to_matrix_columns_are_rotated_axes() {
+        let rotation = Quaternion::from_axis_angle(Vector::new([1.0, 2.0, 3.0]), 0.7)
+            * Quaternion::from_axis_angle(y(), -1.3);
+
+        let matrix = rotation.to_matrix();
+
+        for (column, axis) in [x(), y(), z()].into_iter().enumerate() {
+            assert_close(matrix[column].resize(), rotation.rotate(axis));
+            assert_eq!(matrix[column][3], 0.0);
+        }
+        assert_eq!(matrix[3], Vector::new([0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(Quaternion::identity().to_matrix(), Matrix::identity());
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let start = Quaternion::identity();
+        let end = Quaternion::from_axis_angle(z(), FRAC_PI_2);
+
+        let half = start.slerp(end, 0.5);
+
+        assert_same(half, Quaternion::from_axis_angle(z(), FRAC_PI_2 / 2.0));
+        assert_same(start.slerp(end, 0.0), start);
+        assert_same(start.slerp(end, 1.0), end);
+    }
+
+    #[test]
+    fn slerp_takes_shorter_arc() {
+        let start = Quaternion::identity();
+        let end = Quaternion::from_axis_angle(z(), FRAC_PI_2);
+        let flipped = Quaternion::new(-end.x, -end.y, -end.z, -end.w);
+
+        let half = start.slerp(flipped, 0.5);
+
+        assert_close(
+            half.rotate(x()),
+            Vector::<f32, 3>::new([1.0, 1.0, 0.0]).normalize(),
+        );
+    }
+}
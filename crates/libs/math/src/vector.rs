@@ -135,6 +135,11 @@ impl<const N: usize> Vector<f32, N> {
         self / magnitude
     }
 
+    /// Reflects off the surface with unit length `normal`.
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+
     pub fn castf<U>(self) -> Vector<U, N>
     where
         f32: FloatToInt<U>,
@@ -176,6 +181,11 @@ impl<const N: usize> Vector<f64, N> {
         self / magnitude
     }
 
+    /// Reflects off the surface with unit length `normal`.
+    pub fn reflect(self, normal: Self) -> Self {
+        self - normal * (2.0 * self.dot(normal))
+    }
+
     pub fn castf<U>(self) -> Vector<U, N>
     where
         f64: FloatToInt<U>,
@@ -309,3 +319,40 @@ where
         *self = *self / rhs;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cross_basis_vectors() {
+        let x = Vector::new([1.0_f32, 0.0, 0.0]);
+        let y = Vector::new([0.0_f32, 1.0, 0.0]);
+        let z = Vector::new([0.0_f32, 0.0, 1.0]);
+
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(z), x);
+        assert_eq!(z.cross(x), y);
+        assert_eq!(y.cross(x), z * -1.0);
+        assert_eq!(x.cross(x), Vector::default());
+    }
+
+    #[test]
+    fn reflect_off_plane() {
+        let normal = Vector::new([0.0_f32, 1.0, 0.0]);
+
+        assert_eq!(
+            Vector::new([1.0_f32, -1.0, 0.0]).reflect(normal),
+            Vector::new([1.0, 1.0, 0.0])
+        );
+        //along the surface nothing changes
+        assert_eq!(
+            Vector::new([3.0_f32, 0.0, -2.0]).reflect(normal),
+            Vector::new([3.0, 0.0, -2.0])
+        );
+        assert_eq!(
+            Vector::new([0.0_f64, 0.0, 2.0]).reflect(Vector::new([0.0, 0.0, -1.0])),
+            Vector::new([0.0, 0.0, -2.0])
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //TODO implement From for ffi types
 
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::mem::{self, MaybeUninit};
+use std::ops::{BitOr, BitOrAssign, Deref, DerefMut};
 use std::ptr;
 use std::rc::Rc;
 use std::slice;
@@ -41,6 +45,17 @@ mod ffi {
         }
     };}
 
+    macro_rules! impl_from_bool_struct {
+    ($ name : ident, $($cases : ident),*) => {
+        impl From<$name> for super::$name {
+            fn from(x: $name) -> Self {
+                Self {
+                    $($cases: x.$cases != 0),*
+                }
+            }
+        }
+    };}
+
     macro_rules! handle {
         ($ name : ident) => {
             #[repr(transparent)]
@@ -60,6 +75,10 @@ mod ffi {
                 pub const fn null() -> Self {
                     Self(::std::ptr::null_mut())
                 }
+
+                pub fn as_raw(self) -> u64 {
+                    self.0 as u64
+                }
             }
 
             impl fmt::Pointer for $name {
@@ -92,6 +111,10 @@ mod ffi {
                 pub const fn null() -> Self {
                     Self(0)
                 }
+
+                pub fn as_raw(self) -> u64 {
+                    self.0
+                }
             }
 
             impl ::std::fmt::Pointer for $name {
@@ -129,7 +152,10 @@ mod ffi {
     handle_nondispatchable!(CommandPool);
     handle_nondispatchable!(Fence);
     handle_nondispatchable!(Semaphore);
+    handle_nondispatchable!(Event);
+    handle_nondispatchable!(QueryPool);
     handle_nondispatchable!(Buffer);
+    handle_nondispatchable!(BufferView);
     handle_nondispatchable!(DeviceMemory);
     handle_nondispatchable!(DescriptorPool);
     handle_nondispatchable!(DescriptorSet);
@@ -185,7 +211,10 @@ mod ffi {
         MemoryAllocateInfo = 5,
         FenceCreateInfo = 8,
         SemaphoreCreateInfo = 9,
+        EventCreateInfo = 10,
+        QueryPoolCreateInfo = 11,
         BufferCreateInfo = 12,
+        BufferViewCreateInfo = 13,
         ImageCreateInfo = 14,
         ImageViewCreateInfo = 15,
         ShaderModuleCreateInfo = 16,
@@ -217,11 +246,27 @@ mod ffi {
         BufferMemoryBarrier = 44,
         ImageMemoryBarrier = 45,
         MemoryBarrier = 46,
+        PhysicalDeviceFeatures2 = 1000059000,
+        PhysicalDeviceMemoryProperties2 = 1000059006,
         SwapchainCreateInfo = 1000001000,
         PresentInfo = 1000001001,
         XlibSurfaceCreateInfo = 1000004000,
         Win32SurfaceCreateInfo = 1000009000,
+        RenderingInfo = 1000044000,
+        RenderingAttachmentInfo = 1000044001,
+        PipelineRenderingCreateInfo = 1000044002,
+        PhysicalDeviceDynamicRenderingFeatures = 1000044003,
+        DebugUtilsObjectNameInfo = 1000128000,
+        DebugUtilsLabel = 1000128002,
         DebugUtilsMessengerCreateInfo = 1000128004,
+        DescriptorSetLayoutBindingFlagsCreateInfo = 1000161000,
+        PhysicalDeviceDescriptorIndexingFeatures = 1000161001,
+        SamplerReductionModeCreateInfo = 1000130001,
+        PhysicalDeviceTimelineSemaphoreFeatures = 1000207000,
+        SemaphoreTypeCreateInfo = 1000207002,
+        TimelineSemaphoreSubmitInfo = 1000207003,
+        PhysicalDeviceMemoryBudgetProperties = 1000237000,
+        PhysicalDeviceMeshShaderFeatures = 1000328000,
     }
 
     #[derive(Clone, Copy)]
@@ -253,11 +298,48 @@ mod ffi {
         DescriptorSet = 23,
         Framebuffer = 24,
         CommandPool = 25,
+        Surface = 1000000000,
+        Swapchain = 1000001000,
+        DebugUtilsMessenger = 1000128000,
     }
 
+    impl_from_enum!(
+        ObjectType,
+        Unknown,
+        Instance,
+        PhysicalDevice,
+        Device,
+        Queue,
+        Semaphore,
+        CommandBuffer,
+        Fence,
+        DeviceMemory,
+        Buffer,
+        Image,
+        Event,
+        QueryPool,
+        BufferView,
+        ImageView,
+        ShaderModule,
+        PipelineCache,
+        PipelineLayout,
+        RenderPass,
+        Pipeline,
+        DescriptorSetLayout,
+        Sampler,
+        DescriptorPool,
+        DescriptorSet,
+        Framebuffer,
+        CommandPool,
+        Surface,
+        Swapchain,
+        DebugUtilsMessenger
+    );
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum Format {
+        Undefined = 0,
         Rgba8Srgb = 43,
         Bgra8Srgb = 50,
         R16Uint = 74,
@@ -306,6 +388,8 @@ mod ffi {
         SrgbNonlinear = 0,
     }
 
+    impl_from_enum!(ColorSpace, SrgbNonlinear);
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum PresentMode {
@@ -315,6 +399,8 @@ mod ffi {
         FifoRelaxed = 3,
     }
 
+    impl_from_enum!(PresentMode, Immediate, Mailbox, Fifo, FifoRelaxed);
+
     pub type Extent2d = [u32; 2];
     pub type Extent3d = [u32; 3];
 
@@ -352,12 +438,19 @@ mod ffi {
         Concurrent = 1,
     }
 
+    impl_from_enum!(SharingMode, Exclusive, Concurrent);
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum CompositeAlpha {
         Opaque = 0x00000001,
+        PreMultiplied = 0x00000002,
+        PostMultiplied = 0x00000004,
+        Inherit = 0x00000008,
     }
 
+    impl_from_enum!(CompositeAlpha, Opaque, PreMultiplied, PostMultiplied, Inherit);
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct ApplicationInfo {
@@ -443,9 +536,89 @@ mod ffi {
         *mut DebugUtilsMessenger,
     ) -> Result;
 
+    pub type GetPhysicalDeviceMemoryProperties2 =
+        unsafe extern "system" fn(PhysicalDevice, *mut PhysicalDeviceMemoryProperties2);
+
+    pub type GetPhysicalDeviceFeatures2 =
+        unsafe extern "system" fn(PhysicalDevice, *mut PhysicalDeviceFeatures2);
+
+    pub type CmdBeginRendering = unsafe extern "system" fn(CommandBuffer, *const RenderingInfo);
+
+    pub type CmdEndRendering = unsafe extern "system" fn(CommandBuffer);
+
+    pub type CmdDrawMeshTasks = unsafe extern "system" fn(CommandBuffer, u32, u32, u32);
+
+    pub type CmdDrawIndexedIndirectCount = unsafe extern "system" fn(
+        CommandBuffer,
+        Buffer,
+        DeviceSize,
+        Buffer,
+        DeviceSize,
+        u32,
+        u32,
+    );
+
     pub type DestroyDebugUtilsMessenger =
         unsafe extern "system" fn(Instance, DebugUtilsMessenger, *const ()) -> Result;
 
+    pub type SetDebugUtilsObjectName =
+        unsafe extern "system" fn(Device, *const DebugUtilsObjectNameInfo) -> Result;
+
+    pub type CmdBeginDebugUtilsLabel =
+        unsafe extern "system" fn(CommandBuffer, *const DebugUtilsLabel);
+
+    pub type CmdEndDebugUtilsLabel = unsafe extern "system" fn(CommandBuffer);
+
+    pub type CmdInsertDebugUtilsLabel =
+        unsafe extern "system" fn(CommandBuffer, *const DebugUtilsLabel);
+
+    #[derive(Clone, Copy)]
+    pub struct DebugUtilsFunctions {
+        pub create_messenger: Option<CreateDebugUtilsMessenger>,
+        pub destroy_messenger: Option<DestroyDebugUtilsMessenger>,
+        pub set_object_name: Option<SetDebugUtilsObjectName>,
+        pub cmd_begin_label: Option<CmdBeginDebugUtilsLabel>,
+        pub cmd_end_label: Option<CmdEndDebugUtilsLabel>,
+        pub cmd_insert_label: Option<CmdInsertDebugUtilsLabel>,
+    }
+
+    impl DebugUtilsFunctions {
+        pub fn load(instance: Instance) -> Self {
+            let proc_addr = |name: &[u8]| {
+                let name = CStr::from_bytes_with_nul(name).unwrap();
+
+                unsafe { vkGetInstanceProcAddr(instance, name.as_ptr()) }
+            };
+
+            //a null pointer becomes None
+            unsafe {
+                Self {
+                    create_messenger: mem::transmute::<*const (), Option<CreateDebugUtilsMessenger>>(
+                        proc_addr(b"vkCreateDebugUtilsMessengerEXT\0"),
+                    ),
+                    destroy_messenger: mem::transmute::<
+                        *const (),
+                        Option<DestroyDebugUtilsMessenger>,
+                    >(proc_addr(
+                        b"vkDestroyDebugUtilsMessengerEXT\0",
+                    )),
+                    set_object_name: mem::transmute::<*const (), Option<SetDebugUtilsObjectName>>(
+                        proc_addr(b"vkSetDebugUtilsObjectNameEXT\0"),
+                    ),
+                    cmd_begin_label: mem::transmute::<*const (), Option<CmdBeginDebugUtilsLabel>>(
+                        proc_addr(b"vkCmdBeginDebugUtilsLabelEXT\0"),
+                    ),
+                    cmd_end_label: mem::transmute::<*const (), Option<CmdEndDebugUtilsLabel>>(
+                        proc_addr(b"vkCmdEndDebugUtilsLabelEXT\0"),
+                    ),
+                    cmd_insert_label: mem::transmute::<*const (), Option<CmdInsertDebugUtilsLabel>>(
+                        proc_addr(b"vkCmdInsertDebugUtilsLabelEXT\0"),
+                    ),
+                }
+            }
+        }
+    }
+
     pub unsafe extern "system" fn debug_utils_messenger_callback(
         message_severity: u32,
         message_type: u32,
@@ -732,6 +905,65 @@ mod ffi {
         inherited_queries
     );
 
+    impl_from_bool_struct!(
+        PhysicalDeviceFeatures,
+        robust_buffer_access,
+        full_draw_index_uint_32,
+        image_cube_array,
+        independent_blend,
+        geometry_shader,
+        tessellation_shader,
+        sample_rate_shading,
+        dual_src_blend,
+        logic_op,
+        multi_draw_indirect,
+        draw_indirect_first_instance,
+        depth_clamp,
+        depth_bias_clamp,
+        fill_mode_non_solid,
+        depth_bounds,
+        wide_lines,
+        large_points,
+        alpha_to_one,
+        multi_viewport,
+        sampler_anisotropy,
+        texture_compression_etc_2,
+        texture_compression_astc_ldr,
+        texture_compression_bc,
+        occlusion_query_precise,
+        pipeline_statistics_query,
+        vertex_pipeline_stores_and_atomics,
+        fragment_stores_and_atomics,
+        shader_tessellation_and_geometry_point_size,
+        shader_image_gather_extended,
+        shader_storage_image_extended_formats,
+        shader_storage_image_multisample,
+        shader_storage_image_read_without_format,
+        shader_storage_image_write_without_format,
+        shader_uniform_buffer_array_dynamic_indexing,
+        shader_sampled_image_array_dynamic_indexing,
+        shader_storage_buffer_array_dynamic_indexing,
+        shader_storage_image_array_dynamic_indexing,
+        shader_clip_distance,
+        shader_cull_distance,
+        shader_float_64,
+        shader_int_64,
+        shader_int_16,
+        shader_resource_residency,
+        shader_resource_min_lod,
+        sparse_binding,
+        sparse_residency_buffer,
+        sparse_residency_image_2_d,
+        sparse_residency_image_3_d,
+        sparse_residency_2_samples,
+        sparse_residency_4_samples,
+        sparse_residency_8_samples,
+        sparse_residency_16_samples,
+        sparse_residency_aliased,
+        variable_multisample_rate,
+        inherited_queries
+    );
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct QueueFamilyProperties {
@@ -821,9 +1053,20 @@ mod ffi {
         Cube = 3,
         OneDimArray = 4,
         TwoDimArray = 5,
-        ThreeDimArray = 6,
+        CubeArray = 6,
     }
 
+    impl_from_enum!(
+        ImageViewType,
+        OneDim,
+        TwoDim,
+        ThreeDim,
+        Cube,
+        OneDimArray,
+        TwoDimArray,
+        CubeArray
+    );
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum ComponentSwizzle {
@@ -836,6 +1079,8 @@ mod ffi {
         A = 6,
     }
 
+    impl_from_enum!(ComponentSwizzle, Identity, Zero, One, R, G, B, A);
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct ComponentMapping {
@@ -1345,34 +1590,66 @@ mod ffi {
     pub enum BlendFactor {
         Zero = 0,
         One = 1,
+        SrcColor = 2,
+        OneMinusSrcColor = 3,
+        DstColor = 4,
+        OneMinusDstColor = 5,
         SrcAlpha = 6,
         OneMinusSrcAlpha = 7,
+        DstAlpha = 8,
+        OneMinusDstAlpha = 9,
+        ConstantColor = 10,
+        OneMinusConstantColor = 11,
+        ConstantAlpha = 12,
+        OneMinusConstantAlpha = 13,
+        SrcAlphaSaturate = 14,
+        Src1Color = 15,
+        OneMinusSrc1Color = 16,
+        Src1Alpha = 17,
+        OneMinusSrc1Alpha = 18,
     }
 
-    impl From<super::BlendFactor> for BlendFactor {
-        fn from(blend_factor: super::BlendFactor) -> Self {
-            match blend_factor {
-                super::BlendFactor::Zero => Self::Zero,
-                super::BlendFactor::One => Self::One,
-                super::BlendFactor::SrcAlpha => Self::SrcAlpha,
-                super::BlendFactor::OneMinusSrcAlpha => Self::OneMinusSrcAlpha,
-            }
-        }
-    }
+    impl_from_enum!(
+        BlendFactor,
+        Zero,
+        One,
+        SrcColor,
+        OneMinusSrcColor,
+        DstColor,
+        OneMinusDstColor,
+        SrcAlpha,
+        OneMinusSrcAlpha,
+        DstAlpha,
+        OneMinusDstAlpha,
+        ConstantColor,
+        OneMinusConstantColor,
+        ConstantAlpha,
+        OneMinusConstantAlpha,
+        SrcAlphaSaturate,
+        Src1Color,
+        OneMinusSrc1Color,
+        Src1Alpha,
+        OneMinusSrc1Alpha
+    );
 
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum BlendOp {
         Add = 0,
+        Subtract = 1,
+        ReverseSubtract = 2,
+        Min = 3,
+        Max = 4,
     }
 
-    impl From<super::BlendOp> for BlendOp {
-        fn from(blend_op: super::BlendOp) -> Self {
-            match blend_op {
-                super::BlendOp::Add => Self::Add,
-            }
-        }
-    }
+    impl_from_enum!(
+        BlendOp,
+        Add,
+        Subtract,
+        ReverseSubtract,
+        Min,
+        Max
+    );
 
     #[derive(Clone, Copy)]
     #[repr(C)]
@@ -1390,16 +1667,43 @@ mod ffi {
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum LogicOp {
+        Clear = 0,
+        And = 1,
+        AndReverse = 2,
         Copy = 3,
+        AndInverted = 4,
+        NoOp = 5,
+        Xor = 6,
+        Or = 7,
+        Nor = 8,
+        Equivalent = 9,
+        Invert = 10,
+        OrReverse = 11,
+        CopyInverted = 12,
+        OrInverted = 13,
+        Nand = 14,
+        Set = 15,
     }
 
-    impl From<super::LogicOp> for LogicOp {
-        fn from(logic_op: super::LogicOp) -> Self {
-            match logic_op {
-                super::LogicOp::Copy => Self::Copy,
-            }
-        }
-    }
+    impl_from_enum!(
+        LogicOp,
+        Clear,
+        And,
+        AndReverse,
+        Copy,
+        AndInverted,
+        NoOp,
+        Xor,
+        Or,
+        Nor,
+        Equivalent,
+        Invert,
+        OrReverse,
+        CopyInverted,
+        OrInverted,
+        Nand,
+        Set
+    );
 
     #[derive(Clone, Copy)]
     #[repr(C)]
@@ -1418,12 +1722,18 @@ mod ffi {
     #[repr(C)]
     pub enum DynamicState {
         Viewport = 0,
+        Scissor = 1,
+        LineWidth = 2,
+        DepthBias = 3,
     }
 
     impl From<super::DynamicState> for DynamicState {
         fn from(dynamic_state: super::DynamicState) -> Self {
             match dynamic_state {
                 super::DynamicState::Viewport => Self::Viewport,
+                super::DynamicState::Scissor => Self::Scissor,
+                super::DynamicState::LineWidth => Self::LineWidth,
+                super::DynamicState::DepthBias => Self::DepthBias,
             }
         }
     }
@@ -1520,6 +1830,104 @@ mod ffi {
         pub clear_values: *const ClearValue,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct RenderingAttachmentInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub image_view: ImageView,
+        pub image_layout: ImageLayout,
+        pub resolve_mode: Flags,
+        pub resolve_image_view: ImageView,
+        pub resolve_image_layout: ImageLayout,
+        pub load_op: AttachmentLoadOp,
+        pub store_op: AttachmentStoreOp,
+        pub clear_value: ClearValue,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct RenderingInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub render_area: Rect2d,
+        pub layer_count: u32,
+        pub view_mask: u32,
+        pub color_attachment_count: u32,
+        pub color_attachments: *const RenderingAttachmentInfo,
+        pub depth_attachment: *const RenderingAttachmentInfo,
+        pub stencil_attachment: *const RenderingAttachmentInfo,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PipelineRenderingCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub view_mask: u32,
+        pub color_attachment_count: u32,
+        pub color_attachment_formats: *const Format,
+        pub depth_attachment_format: Format,
+        pub stencil_attachment_format: Format,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceDescriptorIndexingFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub shader_input_attachment_array_dynamic_indexing: Bool,
+        pub shader_uniform_texel_buffer_array_dynamic_indexing: Bool,
+        pub shader_storage_texel_buffer_array_dynamic_indexing: Bool,
+        pub shader_uniform_buffer_array_non_uniform_indexing: Bool,
+        pub shader_sampled_image_array_non_uniform_indexing: Bool,
+        pub shader_storage_buffer_array_non_uniform_indexing: Bool,
+        pub shader_storage_image_array_non_uniform_indexing: Bool,
+        pub shader_input_attachment_array_non_uniform_indexing: Bool,
+        pub shader_uniform_texel_buffer_array_non_uniform_indexing: Bool,
+        pub shader_storage_texel_buffer_array_non_uniform_indexing: Bool,
+        pub descriptor_binding_uniform_buffer_update_after_bind: Bool,
+        pub descriptor_binding_sampled_image_update_after_bind: Bool,
+        pub descriptor_binding_storage_image_update_after_bind: Bool,
+        pub descriptor_binding_storage_buffer_update_after_bind: Bool,
+        pub descriptor_binding_uniform_texel_buffer_update_after_bind: Bool,
+        pub descriptor_binding_storage_texel_buffer_update_after_bind: Bool,
+        pub descriptor_binding_update_unused_while_pending: Bool,
+        pub descriptor_binding_partially_bound: Bool,
+        pub descriptor_binding_variable_descriptor_count: Bool,
+        pub runtime_descriptor_array: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct DescriptorSetLayoutBindingFlagsCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub binding_count: u32,
+        pub binding_flags: *const Flags,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceMeshShaderFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub task_shader: Bool,
+        pub mesh_shader: Bool,
+        pub multiview_mesh_shader: Bool,
+        pub primitive_fragment_shading_rate_mesh_shader: Bool,
+        pub mesh_shader_queries: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceDynamicRenderingFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub dynamic_rendering: Bool,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub enum SubpassContents {
@@ -1580,6 +1988,61 @@ mod ffi {
         pub flags: u32,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SemaphoreTypeCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub semaphore_type: u32,
+        pub initial_value: u64,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceTimelineSemaphoreFeatures {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub timeline_semaphore: Bool,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct TimelineSemaphoreSubmitInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub wait_semaphore_value_count: u32,
+        pub wait_semaphore_values: *const u64,
+        pub signal_semaphore_value_count: u32,
+        pub signal_semaphore_values: *const u64,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct EventCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum QueryType {
+        Timestamp = 2,
+    }
+
+    impl_from_enum!(QueryType, Timestamp);
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct QueryPoolCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: u32,
+        pub query_type: QueryType,
+        pub query_count: u32,
+        pub pipeline_statistics: Flags,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct SubmitInfo {
@@ -1634,7 +2097,7 @@ mod ffi {
     pub struct MemoryRequirements {
         pub size: DeviceSize,
         pub alignment: DeviceSize,
-        pub memory_type: u32,
+        pub memory_type_bits: u32,
     }
 
     #[derive(Clone, Copy)]
@@ -1657,7 +2120,48 @@ mod ffi {
         pub memory_type_count: u32,
         pub memory_types: [MemoryType; 32],
         pub memory_heap_count: u32,
-        pub memory_heaps: [MemoryHeap; 32],
+        pub memory_heaps: [MemoryHeap; 16],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceMemoryProperties2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub memory_properties: PhysicalDeviceMemoryProperties,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceFeatures2 {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub features: PhysicalDeviceFeatures,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct PhysicalDeviceMemoryBudgetProperties {
+        pub structure_type: StructureType,
+        pub p_next: *mut (),
+        pub heap_budget: [DeviceSize; 16],
+        pub heap_usage: [DeviceSize; 16],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct LayerProperties {
+        pub layer_name: [i8; 256],
+        pub spec_version: u32,
+        pub implementation_version: u32,
+        pub description: [i8; 256],
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ExtensionProperties {
+        pub extension_name: [i8; 256],
+        pub spec_version: u32,
     }
 
     #[derive(Clone, Copy)]
@@ -1665,16 +2169,24 @@ mod ffi {
     pub enum DescriptorType {
         CombinedImageSampler = 1,
         StorageImage = 3,
+        UniformTexelBuffer = 4,
+        StorageTexelBuffer = 5,
         UniformBuffer = 6,
         StorageBuffer = 7,
+        UniformBufferDynamic = 8,
+        StorageBufferDynamic = 9,
     }
 
     impl_from_enum!(
         DescriptorType,
         CombinedImageSampler,
         StorageImage,
+        UniformTexelBuffer,
+        StorageTexelBuffer,
         UniformBuffer,
-        StorageBuffer
+        StorageBuffer,
+        UniformBufferDynamic,
+        StorageBufferDynamic
     );
 
     #[derive(Clone, Copy)]
@@ -1697,6 +2209,18 @@ mod ffi {
         pub bindings: *const DescriptorSetLayoutBinding,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct BufferViewCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub flags: Flags,
+        pub buffer: Buffer,
+        pub format: Format,
+        pub offset: DeviceSize,
+        pub range: DeviceSize,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct DescriptorBufferInfo {
@@ -1725,7 +2249,7 @@ mod ffi {
         pub descriptor_type: DescriptorType,
         pub image_infos: *const DescriptorImageInfo,
         pub buffer_infos: *const DescriptorBufferInfo,
-        pub texel_buffer_view: *const (),
+        pub texel_buffer_view: *const BufferView,
     }
 
     #[derive(Clone, Copy)]
@@ -1891,6 +2415,7 @@ mod ffi {
         MirroredRepeat = 1,
         ClampToEdge = 2,
         ClampToBorder = 3,
+        MirrorClampToEdge = 4,
     }
 
     impl From<super::SamplerAddressMode> for SamplerAddressMode {
@@ -1900,6 +2425,7 @@ mod ffi {
                 super::SamplerAddressMode::MirroredRepeat => Self::MirroredRepeat,
                 super::SamplerAddressMode::ClampToEdge => Self::ClampToEdge,
                 super::SamplerAddressMode::ClampToBorder => Self::ClampToBorder,
+                super::SamplerAddressMode::MirrorClampToEdge => Self::MirrorClampToEdge,
             }
         }
     }
@@ -1928,6 +2454,32 @@ mod ffi {
         }
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub enum SamplerReductionMode {
+        WeightedAverage = 0,
+        Min = 1,
+        Max = 2,
+    }
+
+    impl From<super::SamplerReductionMode> for SamplerReductionMode {
+        fn from(reduction_mode: super::SamplerReductionMode) -> Self {
+            match reduction_mode {
+                super::SamplerReductionMode::WeightedAverage => Self::WeightedAverage,
+                super::SamplerReductionMode::Min => Self::Min,
+                super::SamplerReductionMode::Max => Self::Max,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SamplerReductionModeCreateInfo {
+        pub structure_type: StructureType,
+        pub p_next: *const (),
+        pub reduction_mode: SamplerReductionMode,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct SamplerCreateInfo {
@@ -1999,7 +2551,12 @@ mod ffi {
             instance: *mut Instance,
         ) -> Result;
         pub fn vkDestroyInstance(instance: Instance, allocator: *const ());
+        pub fn vkEnumerateInstanceLayerProperties(
+            property_count: *mut u32,
+            properties: *mut LayerProperties,
+        ) -> Result;
         pub fn vkGetInstanceProcAddr(instance: Instance, name: *const i8) -> *const ();
+        pub fn vkGetDeviceProcAddr(device: Device, name: *const i8) -> *const ();
         pub fn vkEnumeratePhysicalDevices(
             instance: Instance,
             physical_device_count: *mut u32,
@@ -2025,11 +2582,27 @@ mod ffi {
             surface: Surface,
             supported: *mut Bool,
         ) -> Result;
-        pub fn vkGetPhysicalDeviceMemoryProperties(
+        pub fn vkGetPhysicalDeviceSurfacePresentModesKHR(
             physical_device: PhysicalDevice,
-            memory_properties: *mut PhysicalDeviceMemoryProperties,
-        );
-        pub fn vkCreateDevice(
+            surface: Surface,
+            present_mode_count: *mut u32,
+            present_modes: *mut u32,
+        ) -> Result;
+        pub fn vkGetPhysicalDeviceFeatures(
+            physical_device: PhysicalDevice,
+            features: *mut PhysicalDeviceFeatures,
+        );
+        pub fn vkGetPhysicalDeviceMemoryProperties(
+            physical_device: PhysicalDevice,
+            memory_properties: *mut PhysicalDeviceMemoryProperties,
+        );
+        pub fn vkEnumerateDeviceExtensionProperties(
+            physical_device: PhysicalDevice,
+            layer_name: *const i8,
+            property_count: *mut u32,
+            properties: *mut ExtensionProperties,
+        ) -> Result;
+        pub fn vkCreateDevice(
             physical_device: PhysicalDevice,
             create_info: *const DeviceCreateInfo,
             allocator: *const (),
@@ -2088,6 +2661,13 @@ mod ffi {
             image_view: *mut ImageView,
         ) -> Result;
         pub fn vkDestroyImageView(device: Device, image_view: ImageView, allocator: *const ());
+        pub fn vkCreateBufferView(
+            device: Device,
+            create_info: *const BufferViewCreateInfo,
+            allocator: *const (),
+            buffer_view: *mut BufferView,
+        ) -> Result;
+        pub fn vkDestroyBufferView(device: Device, buffer_view: BufferView, allocator: *const ());
         pub fn vkCreateShaderModule(
             device: Device,
             create_info: *const ShaderModuleCreateInfo,
@@ -2185,6 +2765,13 @@ mod ffi {
             allocate_info: *const CommandBufferAllocateInfo,
             command_buffers: *mut CommandBuffer,
         ) -> Result;
+        pub fn vkFreeCommandBuffers(
+            device: Device,
+            command_pool: CommandPool,
+            command_buffer_count: u32,
+            command_buffers: *const CommandBuffer,
+        );
+        pub fn vkTrimCommandPool(device: Device, command_pool: CommandPool, flags: Flags);
         pub fn vkBeginCommandBuffer(
             command_buffer: CommandBuffer,
             begin_info: *const CommandBufferBeginInfo,
@@ -2202,6 +2789,25 @@ mod ffi {
             bind_point: PipelineBindPoint,
             pipeline: Pipeline,
         );
+        pub fn vkCmdSetViewport(
+            command_buffer: CommandBuffer,
+            first_viewport: u32,
+            viewport_count: u32,
+            viewports: *const Viewport,
+        );
+        pub fn vkCmdSetScissor(
+            command_buffer: CommandBuffer,
+            first_scissor: u32,
+            scissor_count: u32,
+            scissors: *const Rect2d,
+        );
+        pub fn vkCmdSetLineWidth(command_buffer: CommandBuffer, line_width: f32);
+        pub fn vkCmdSetDepthBias(
+            command_buffer: CommandBuffer,
+            depth_bias_constant_factor: f32,
+            depth_bias_clamp: f32,
+            depth_bias_slope_factor: f32,
+        );
         pub fn vkCmdDraw(
             command_buffer: CommandBuffer,
             vertex_count: u32,
@@ -2251,6 +2857,14 @@ mod ffi {
             region_count: u32,
             regions: *const BufferImageCopy,
         );
+        pub fn vkCmdCopyImageToBuffer(
+            command_buffer: CommandBuffer,
+            src_image: Image,
+            src_image_layout: ImageLayout,
+            dst_buffer: Buffer,
+            region_count: u32,
+            regions: *const BufferImageCopy,
+        );
         pub fn vkCmdPipelineBarrier(
             command_buffer: CommandBuffer,
             src_stage_mask: Flags,
@@ -2277,6 +2891,60 @@ mod ffi {
             semaphore: *mut Semaphore,
         ) -> Result;
         pub fn vkDestroySemaphore(device: Device, semaphore: Semaphore, allocator: *const ());
+        pub fn vkCreateEvent(
+            device: Device,
+            create_info: *const EventCreateInfo,
+            allocator: *const (),
+            event: *mut Event,
+        ) -> Result;
+        pub fn vkDestroyEvent(device: Device, event: Event, allocator: *const ());
+        pub fn vkGetEventStatus(device: Device, event: Event) -> Result;
+        pub fn vkSetEvent(device: Device, event: Event) -> Result;
+        pub fn vkResetEvent(device: Device, event: Event) -> Result;
+        pub fn vkCmdSetEvent(command_buffer: CommandBuffer, event: Event, stage_mask: Flags);
+        pub fn vkCmdResetEvent(command_buffer: CommandBuffer, event: Event, stage_mask: Flags);
+        pub fn vkCmdWaitEvents(
+            command_buffer: CommandBuffer,
+            event_count: u32,
+            events: *const Event,
+            src_stage_mask: Flags,
+            dst_stage_mask: Flags,
+            memory_barrier_count: u32,
+            memory_barriers: *const MemoryBarrier,
+            buffer_memory_barrier_count: u32,
+            buffer_memory_barriers: *const BufferMemoryBarrier,
+            image_memory_barrier_count: u32,
+            image_memory_barriers: *const ImageMemoryBarrier,
+        );
+        pub fn vkCreateQueryPool(
+            device: Device,
+            create_info: *const QueryPoolCreateInfo,
+            allocator: *const (),
+            query_pool: *mut QueryPool,
+        ) -> Result;
+        pub fn vkDestroyQueryPool(device: Device, query_pool: QueryPool, allocator: *const ());
+        pub fn vkCmdResetQueryPool(
+            command_buffer: CommandBuffer,
+            query_pool: QueryPool,
+            first_query: u32,
+            query_count: u32,
+        );
+        pub fn vkCmdWriteTimestamp(
+            command_buffer: CommandBuffer,
+            pipeline_stage: Flags,
+            query_pool: QueryPool,
+            query: u32,
+        );
+        pub fn vkCmdCopyQueryPoolResults(
+            command_buffer: CommandBuffer,
+            query_pool: QueryPool,
+            first_query: u32,
+            query_count: u32,
+            dst_buffer: Buffer,
+            dst_offset: DeviceSize,
+            stride: DeviceSize,
+            flags: Flags,
+        );
         pub fn vkWaitForFences(
             device: Device,
             fence_count: u32,
@@ -2386,6 +3054,15 @@ pub const KHR_SURFACE: &str = "VK_KHR_surface";
 pub const KHR_XLIB_SURFACE: &str = "VK_KHR_xlib_surface";
 pub const KHR_WIN32_SURFACE: &str = "VK_KHR_win32_surface";
 pub const KHR_SWAPCHAIN: &str = "VK_KHR_swapchain";
+pub const KHR_DYNAMIC_RENDERING: &str = "VK_KHR_dynamic_rendering";
+pub const KHR_DRAW_INDIRECT_COUNT: &str = "VK_KHR_draw_indirect_count";
+pub const EXT_MESH_SHADER: &str = "VK_EXT_mesh_shader";
+pub const EXT_DESCRIPTOR_INDEXING: &str = "VK_EXT_descriptor_indexing";
+pub const EXT_SAMPLER_FILTER_MINMAX: &str = "VK_EXT_sampler_filter_minmax";
+pub const KHR_TIMELINE_SEMAPHORE: &str = "VK_KHR_timeline_semaphore";
+pub const KHR_SAMPLER_MIRROR_CLAMP_TO_EDGE: &str = "VK_KHR_sampler_mirror_clamp_to_edge";
+pub const KHR_GET_PHYSICAL_DEVICE_PROPERTIES_2: &str = "VK_KHR_get_physical_device_properties2";
+pub const EXT_MEMORY_BUDGET: &str = "VK_EXT_memory_budget";
 
 pub const EXT_DEBUG_REPORT: &str = "VK_EXT_debug_report";
 pub const EXT_DEBUG_UTILS: &str = "VK_EXT_debug_utils";
@@ -2404,11 +3081,15 @@ pub const DEBUG_UTILS_MESSAGE_TYPE_PERFORMANCE: u32 = 0x00000004;
 
 pub const QUEUE_GRAPHICS: u32 = 0x00000001;
 pub const QUEUE_COMPUTE: u32 = 0x00000002;
+pub const QUEUE_TRANSFER: u32 = 0x00000004;
 
 pub const QUEUE_FAMILY_IGNORED: u32 = u32::MAX;
 
+pub const WHOLE_SIZE: u64 = u64::MAX;
+
 pub const IMAGE_ASPECT_COLOR: u32 = 0x00000001;
 pub const IMAGE_ASPECT_DEPTH: u32 = 0x00000002;
+pub const IMAGE_ASPECT_STENCIL: u32 = 0x00000004;
 
 pub const CULL_MODE_NONE: u32 = 0;
 pub const CULL_MODE_FRONT: u32 = 0x00000001;
@@ -2425,6 +3106,9 @@ pub const SAMPLE_COUNT_1: u32 = 0x00000001;
 pub const SUBPASS_EXTERNAL: u32 = u32::MAX;
 
 pub const PIPELINE_STAGE_TOP_OF_PIPE: u32 = 0x00000001;
+pub const PIPELINE_STAGE_DRAW_INDIRECT: u32 = 0x00000002;
+pub const PIPELINE_STAGE_VERTEX_INPUT: u32 = 0x00000004;
+pub const PIPELINE_STAGE_VERTEX_SHADER: u32 = 0x00000008;
 pub const PIPELINE_STAGE_FRAGMENT_SHADER: u32 = 0x00000080;
 pub const PIPELINE_STAGE_EARLY_FRAGMENT_TESTS: u32 = 0x00000100;
 pub const PIPELINE_STAGE_LATE_FRAGMENT_TESTS: u32 = 0x00000200;
@@ -2432,19 +3116,126 @@ pub const PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT: u32 = 0x00000400;
 pub const PIPELINE_STAGE_COMPUTE_SHADER: u32 = 0x00000800;
 pub const PIPELINE_STAGE_TRANSFER: u32 = 0x00001000;
 pub const PIPELINE_STAGE_BOTTOM_OF_PIPE: u32 = 0x00002000;
-
+pub const PIPELINE_STAGE_HOST: u32 = 0x00004000;
+pub const PIPELINE_STAGE_ALL_COMMANDS: u32 = 0x00010000;
+
+pub const ACCESS_INDIRECT_COMMAND_READ: u32 = 0x00000001;
+pub const ACCESS_INDEX_READ: u32 = 0x00000002;
+pub const ACCESS_VERTEX_ATTRIBUTE_READ: u32 = 0x00000004;
+pub const ACCESS_UNIFORM_READ: u32 = 0x00000008;
+pub const ACCESS_INPUT_ATTACHMENT_READ: u32 = 0x00000010;
 pub const ACCESS_SHADER_READ: u32 = 0x00000020;
+pub const ACCESS_SHADER_WRITE: u32 = 0x00000040;
 pub const ACCESS_COLOR_ATTACHMENT_READ: u32 = 0x00000080;
 pub const ACCESS_COLOR_ATTACHMENT_WRITE: u32 = 0x00000100;
 pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_READ: u32 = 0x00000200;
 pub const ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE: u32 = 0x00000400;
+pub const ACCESS_TRANSFER_READ: u32 = 0x00000800;
+pub const ACCESS_TRANSFER_WRITE: u32 = 0x00001000;
+pub const ACCESS_HOST_READ: u32 = 0x00002000;
+pub const ACCESS_HOST_WRITE: u32 = 0x00004000;
+pub const ACCESS_MEMORY_READ: u32 = 0x00008000;
+pub const ACCESS_MEMORY_WRITE: u32 = 0x00010000;
 
 pub const BUFFER_USAGE_TRANSFER_SRC: u32 = 0x00000001;
 pub const BUFFER_USAGE_TRANSFER_DST: u32 = 0x00000002;
+pub const BUFFER_USAGE_UNIFORM_TEXEL: u32 = 0x00000004;
+pub const BUFFER_USAGE_STORAGE_TEXEL: u32 = 0x00000008;
 pub const BUFFER_USAGE_UNIFORM: u32 = 0x00000010;
 pub const BUFFER_USAGE_STORAGE: u32 = 0x00000020;
 pub const BUFFER_USAGE_INDEX: u32 = 0x00000040;
 pub const BUFFER_USAGE_VERTEX: u32 = 0x00000080;
+pub const BUFFER_USAGE_INDIRECT: u32 = 0x00000100;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BufferUsageFlags(u32);
+
+impl BufferUsageFlags {
+    pub const TRANSFER_SRC: Self = Self(BUFFER_USAGE_TRANSFER_SRC);
+    pub const TRANSFER_DST: Self = Self(BUFFER_USAGE_TRANSFER_DST);
+    pub const UNIFORM_TEXEL: Self = Self(BUFFER_USAGE_UNIFORM_TEXEL);
+    pub const STORAGE_TEXEL: Self = Self(BUFFER_USAGE_STORAGE_TEXEL);
+    pub const UNIFORM: Self = Self(BUFFER_USAGE_UNIFORM);
+    pub const STORAGE: Self = Self(BUFFER_USAGE_STORAGE);
+    pub const INDEX: Self = Self(BUFFER_USAGE_INDEX);
+    pub const VERTEX: Self = Self(BUFFER_USAGE_VERTEX);
+    pub const INDIRECT: Self = Self(BUFFER_USAGE_INDIRECT);
+
+    const NAMES: [(Self, &'static str); 9] = [
+        (Self::TRANSFER_SRC, "TRANSFER_SRC"),
+        (Self::TRANSFER_DST, "TRANSFER_DST"),
+        (Self::UNIFORM_TEXEL, "UNIFORM_TEXEL"),
+        (Self::STORAGE_TEXEL, "STORAGE_TEXEL"),
+        (Self::UNIFORM, "UNIFORM"),
+        (Self::STORAGE, "STORAGE"),
+        (Self::INDEX, "INDEX"),
+        (Self::VERTEX, "VERTEX"),
+        (Self::INDIRECT, "INDIRECT"),
+    ];
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<u32> for BufferUsageFlags {
+    fn from(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<BufferUsageFlags> for u32 {
+    fn from(flags: BufferUsageFlags) -> Self {
+        flags.0
+    }
+}
+
+impl BitOr for BufferUsageFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for BufferUsageFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Debug for BufferUsageFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut remaining = self.0;
+
+        let mut names = vec![];
+
+        for (flag, name) in Self::NAMES {
+            if self.contains(flag) {
+                names.push(name.to_string());
+                remaining &= !flag.0;
+            }
+        }
+
+        if remaining != 0 {
+            names.push(format!("0x{:x}", remaining));
+        }
+
+        write!(f, "BufferUsageFlags({})", names.join(" | "))
+    }
+}
 
 pub const IMAGE_USAGE_TRANSFER_SRC: u32 = 0x00000001;
 pub const IMAGE_USAGE_TRANSFER_DST: u32 = 0x00000002;
@@ -2455,15 +3246,49 @@ pub const IMAGE_USAGE_DEPTH_STENCIL_ATTACHMENT: u32 = 0x00000020;
 pub const IMAGE_USAGE_TRANSIENT_ATTACHMENT: u32 = 0x00000040;
 pub const IMAGE_USAGE_INPUT_ATTACHMENT: u32 = 0x00000080;
 
+pub const IMAGE_CREATE_CUBE_COMPATIBLE: u32 = 0x00000010;
+
 pub const MEMORY_PROPERTY_DEVICE_LOCAL: u32 = 0x00000001;
 pub const MEMORY_PROPERTY_HOST_VISIBLE: u32 = 0x00000002;
 pub const MEMORY_PROPERTY_HOST_COHERENT: u32 = 0x00000004;
 pub const MEMORY_PROPERTY_HOST_CACHED: u32 = 0x00000008;
 pub const MEMORY_PROPERTY_LAZILY_ALLOCATED: u32 = 0x00000010;
 
+pub const COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT: u32 = 0x00000001;
+
+/// Results are written as u64 instead of u32.
+pub const QUERY_RESULT_64: u32 = 0x00000001;
+/// Waits for every query to be available instead of skipping the ones that aren't.
+pub const QUERY_RESULT_WAIT: u32 = 0x00000002;
+/// Follows each result with a nonzero value if it was available, in the same width.
+pub const QUERY_RESULT_WITH_AVAILABILITY: u32 = 0x00000004;
+/// Writes whatever an unavailable query has so far instead of skipping it. Not allowed for
+/// timestamps.
+pub const QUERY_RESULT_PARTIAL: u32 = 0x00000008;
+
+//binding flags need a device created with `EXT_DESCRIPTOR_INDEXING`
+pub const DESCRIPTOR_BINDING_UPDATE_AFTER_BIND: u32 = 0x00000001;
+pub const DESCRIPTOR_BINDING_UPDATE_UNUSED_WHILE_PENDING: u32 = 0x00000002;
+pub const DESCRIPTOR_BINDING_PARTIALLY_BOUND: u32 = 0x00000004;
+
+/// Needed for pools that sets with `DESCRIPTOR_BINDING_UPDATE_AFTER_BIND` bindings are
+/// allocated from.
+pub const DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND: u32 = 0x00000002;
+pub const DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL: u32 = 0x00000002;
+
+pub const PIPELINE_CREATE_DISABLE_OPTIMIZATION: u32 = 0x00000001;
+pub const PIPELINE_CREATE_ALLOW_DERIVATIVES: u32 = 0x00000002;
+pub const PIPELINE_CREATE_DERIVATIVE: u32 = 0x00000004;
+
 pub const SHADER_STAGE_VERTEX: u32 = 0x00000001;
 pub const SHADER_STAGE_FRAGMENT: u32 = 0x00000010;
 pub const SHADER_STAGE_COMPUTE: u32 = 0x00000020;
+/// Only usable on devices created with `EXT_MESH_SHADER`.
+pub const SHADER_STAGE_TASK: u32 = 0x00000040;
+/// Only usable on devices created with `EXT_MESH_SHADER`.
+pub const SHADER_STAGE_MESH: u32 = 0x00000080;
+
+pub const SPIRV_MAGIC: u32 = 0x07230203;
 
 pub type DebugUtilsMessengerCallback = fn(&DebugUtilsMessengerCallbackData) -> bool;
 
@@ -2493,8 +3318,114 @@ pub enum Error {
     FullScreenExclusiveModeLost,
     InvalidOpaqueCaptureAddress,
     CompressionExhausted,
+    MisalignedOffset,
+    OutOfBounds,
+    AttachmentCountMismatch,
+    InvalidLayerRange,
+    InvalidMipRange,
+    DescriptorCountMismatch,
+    InvalidBasePipeline,
+    SemaphoreValueCountMismatch,
+    WaitStageCountMismatch,
+    /// `Device::new` asked for a device extension the physical device doesn't support, the
+    /// first one missing is named.
+    MissingExtension(&'static str),
+    /// `DescriptorSet::allocate` asked for more sets than the pool has left.
+    OutOfPoolSets {
+        requested: u32,
+        remaining: u32,
+    },
+    /// `DescriptorSet::allocate` asked for more descriptors of a type than the pool has left.
+    OutOfPoolDescriptors {
+        descriptor_type: DescriptorType,
+        requested: u32,
+        remaining: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectType {
+    Unknown,
+    Instance,
+    PhysicalDevice,
+    Device,
+    Queue,
+    Semaphore,
+    CommandBuffer,
+    Fence,
+    DeviceMemory,
+    Buffer,
+    Image,
+    Event,
+    QueryPool,
+    BufferView,
+    ImageView,
+    ShaderModule,
+    PipelineCache,
+    PipelineLayout,
+    RenderPass,
+    Pipeline,
+    DescriptorSetLayout,
+    Sampler,
+    DescriptorPool,
+    DescriptorSet,
+    Framebuffer,
+    CommandPool,
+    Surface,
+    Swapchain,
+    DebugUtilsMessenger,
+}
+
+/// Vulkan objects that can be referred to by handle, e.g. for debug names.
+pub trait HasHandle {
+    const OBJECT_TYPE: ObjectType;
+
+    fn raw_handle(&self) -> u64;
+}
+
+macro_rules! impl_has_handle {
+    ($($name : ident => $object_type : ident),*) => {
+        $(impl HasHandle for $name {
+            const OBJECT_TYPE: ObjectType = ObjectType::$object_type;
+
+            fn raw_handle(&self) -> u64 {
+                self.handle.as_raw()
+            }
+        })*
+    };
 }
 
+impl_has_handle!(
+    Instance => Instance,
+    PhysicalDevice => PhysicalDevice,
+    Device => Device,
+    Queue => Queue,
+    Semaphore => Semaphore,
+    CommandBuffer => CommandBuffer,
+    Fence => Fence,
+    Event => Event,
+    QueryPool => QueryPool,
+    Memory => DeviceMemory,
+    Buffer => Buffer,
+    BufferView => BufferView,
+    Image => Image,
+    ImageView => ImageView,
+    ShaderModule => ShaderModule,
+    PipelineCache => PipelineCache,
+    PipelineLayout => PipelineLayout,
+    RenderPass => RenderPass,
+    Pipeline => Pipeline,
+    DescriptorSetLayout => DescriptorSetLayout,
+    Sampler => Sampler,
+    DescriptorPool => DescriptorPool,
+    DescriptorSet => DescriptorSet,
+    Framebuffer => Framebuffer,
+    CommandPool => CommandPool,
+    Surface => Surface,
+    Swapchain => Swapchain,
+    DebugUtilsMessenger => DebugUtilsMessenger
+);
+
 #[derive(Clone, Copy, Debug)]
 pub enum Format {
     Rgba8Srgb,
@@ -2529,7 +3460,7 @@ pub enum ColorSpace {
     SrgbNonlinear,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PresentMode {
     Immediate,
     Mailbox,
@@ -2566,11 +3497,15 @@ pub struct SurfaceFormat {
 #[derive(Clone, Copy)]
 pub enum SharingMode {
     Exclusive,
+    Concurrent,
 }
 
 #[derive(Clone, Copy)]
 pub enum CompositeAlpha {
     Opaque,
+    PreMultiplied,
+    PostMultiplied,
+    Inherit,
 }
 
 #[derive(Clone, Copy)]
@@ -2629,9 +3564,36 @@ pub struct DebugUtilsMessengerCallbackData<'a> {
 
 pub struct Instance {
     handle: ffi::Instance,
+    api_version: u32,
+    debug_utils: ffi::DebugUtilsFunctions,
+    get_physical_device_memory_properties2: Option<ffi::GetPhysicalDeviceMemoryProperties2>,
+    get_physical_device_features2: Option<ffi::GetPhysicalDeviceFeatures2>,
 }
 
 impl Instance {
+    pub fn enumerate_layers() -> Vec<String> {
+        let mut property_count = 0;
+
+        unsafe { ffi::vkEnumerateInstanceLayerProperties(&mut property_count, ptr::null_mut()) };
+
+        let mut properties = Vec::<ffi::LayerProperties>::with_capacity(property_count as _);
+
+        unsafe {
+            ffi::vkEnumerateInstanceLayerProperties(&mut property_count, properties.as_mut_ptr())
+        };
+
+        unsafe { properties.set_len(property_count as _) };
+
+        properties
+            .iter()
+            .map(|properties| {
+                unsafe { CStr::from_ptr(properties.layer_name.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>()
+    }
+
     pub fn new(create_info: InstanceCreateInfo<'_>) -> Result<Rc<Instance>, Error> {
         let application_name = CString::new(create_info.application_info.application_name).unwrap();
 
@@ -2731,7 +3693,41 @@ impl Instance {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let instance = Self { handle };
+                //resolved once here so messengers and labels don't look them up every call
+                let debug_utils = ffi::DebugUtilsFunctions::load(handle);
+
+                //core since 1.1, before that only with the khr extension enabled
+                let core = api_version >= u32::from(Version::from((1, 1, 0)));
+
+                let proc_addr = |core_name: &[u8], khr_name: &[u8]| {
+                    let name = CStr::from_bytes_with_nul(if core { core_name } else { khr_name });
+
+                    unsafe { ffi::vkGetInstanceProcAddr(handle, name.unwrap().as_ptr()) }
+                };
+
+                let get_physical_device_memory_properties2 = unsafe {
+                    mem::transmute::<*const (), Option<ffi::GetPhysicalDeviceMemoryProperties2>>(
+                        proc_addr(
+                            b"vkGetPhysicalDeviceMemoryProperties2\0",
+                            b"vkGetPhysicalDeviceMemoryProperties2KHR\0",
+                        ),
+                    )
+                };
+
+                let get_physical_device_features2 = unsafe {
+                    mem::transmute::<*const (), Option<ffi::GetPhysicalDeviceFeatures2>>(proc_addr(
+                        b"vkGetPhysicalDeviceFeatures2\0",
+                        b"vkGetPhysicalDeviceFeatures2KHR\0",
+                    ))
+                };
+
+                let instance = Self {
+                    handle,
+                    api_version,
+                    debug_utils,
+                    get_physical_device_memory_properties2,
+                    get_physical_device_features2,
+                };
 
                 let instance = Rc::new(instance);
 
@@ -2764,15 +3760,10 @@ impl DebugUtilsMessenger {
         instance: Rc<Instance>,
         create_info: DebugUtilsMessengerCreateInfo,
     ) -> Result<Self, Error> {
-        let f_name = CStr::from_bytes_with_nul(b"vkCreateDebugUtilsMessengerEXT\0").unwrap();
-
-        let f = unsafe { ffi::vkGetInstanceProcAddr(instance.handle, f_name.as_ptr()) };
-
-        if f == ptr::null() {
-            return Err(Error::ExtensionNotPresent);
-        }
-
-        let f = unsafe { mem::transmute::<_, ffi::CreateDebugUtilsMessenger>(f) };
+        let f = match instance.debug_utils.create_messenger {
+            Some(f) => f,
+            None => return Err(Error::ExtensionNotPresent),
+        };
 
         let g = unsafe { mem::transmute(create_info.user_callback) };
 
@@ -2813,15 +3804,11 @@ impl DebugUtilsMessenger {
 
 impl Drop for DebugUtilsMessenger {
     fn drop(&mut self) {
-        let f_name = CStr::from_bytes_with_nul(b"vkDestroyDebugUtilsMessengerEXT\0").unwrap();
-
-        let f = unsafe { ffi::vkGetInstanceProcAddr(self.instance.handle, f_name.as_ptr()) };
-
-        if f == ptr::null() {
-            panic!("extension not present, but handle already created");
-        }
-
-        let f = unsafe { mem::transmute::<_, ffi::DestroyDebugUtilsMessenger>(f) };
+        let f = self
+            .instance
+            .debug_utils
+            .destroy_messenger
+            .expect("extension not present, but handle already created");
 
         unsafe { f(self.instance.handle, self.handle, ptr::null()) };
     }
@@ -2836,8 +3823,14 @@ pub enum PhysicalDeviceType {
     Cpu,
 }
 
+#[derive(Clone, Copy)]
 pub struct PhysicalDeviceLimits {
     pub max_image_dimension_2d: u32,
+    pub max_sampler_anisotropy: f32,
+    pub min_uniform_buffer_offset_alignment: u64,
+    pub min_storage_buffer_offset_alignment: u64,
+    /// Nanoseconds per timestamp tick.
+    pub timestamp_period: f32,
 }
 
 //TODO add more info
@@ -2907,9 +3900,25 @@ pub struct PhysicalDeviceFeatures {
 }
 
 pub struct PhysicalDevice {
+    instance: Rc<Instance>,
     handle: ffi::PhysicalDevice,
 }
 
+fn missing_extensions<'a>(available: &[String], requested: &[&'a str]) -> Vec<&'a str> {
+    requested
+        .iter()
+        .copied()
+        .filter(|extension| !available.iter().any(|name| name == extension))
+        .collect()
+}
+
+fn check_extensions(available: &[String], requested: &[&'static str]) -> Result<(), Error> {
+    match missing_extensions(available, requested).first() {
+        Some(extension) => Err(Error::MissingExtension(extension)),
+        None => Ok(()),
+    }
+}
+
 impl PhysicalDevice {
     pub fn enumerate(instance: Rc<Instance>) -> Vec<Rc<Self>> {
         let mut handle_count: u32 = 0;
@@ -2932,7 +3941,10 @@ impl PhysicalDevice {
 
         let physical_devices = handles
             .into_iter()
-            .map(|handle| Self { handle })
+            .map(|handle| Self {
+                instance: instance.clone(),
+                handle,
+            })
             .map(|physical_device| Rc::new(physical_device))
             .collect::<Vec<_>>();
 
@@ -2961,6 +3973,14 @@ impl PhysicalDevice {
 
         let limits = PhysicalDeviceLimits {
             max_image_dimension_2d: properties.limits.max_image_dimension_2d,
+            max_sampler_anisotropy: properties.limits.max_sampler_anisotropy,
+            min_uniform_buffer_offset_alignment: properties
+                .limits
+                .min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment: properties
+                .limits
+                .min_storage_buffer_offset_alignment,
+            timestamp_period: properties.limits.timestamp_period,
         };
 
         PhysicalDeviceProperties {
@@ -2970,9 +3990,40 @@ impl PhysicalDevice {
         }
     }
 
-    //TODO
     pub fn features(&self) -> PhysicalDeviceFeatures {
-        unimplemented!();
+        let mut features = MaybeUninit::<ffi::PhysicalDeviceFeatures>::uninit();
+
+        unsafe { ffi::vkGetPhysicalDeviceFeatures(self.handle, features.as_mut_ptr()) };
+
+        unsafe { features.assume_init() }.into()
+    }
+
+    //fills the extension feature structs chained at `p_next`. returns false and writes
+    //nothing if the instance can't query them
+    fn query_features(&self, p_next: *mut ()) -> bool {
+        let get_features2 = match self.instance.get_physical_device_features2 {
+            Some(f) => f,
+            None => return false,
+        };
+
+        let mut features = ffi::PhysicalDeviceFeatures2 {
+            structure_type: ffi::StructureType::PhysicalDeviceFeatures2,
+            p_next,
+            features: unsafe { mem::zeroed() },
+        };
+
+        unsafe { get_features2(self.handle, &mut features) };
+
+        true
+    }
+
+    //the version the driver supports, which can be older than the instance's
+    fn api_version(&self) -> u32 {
+        let mut properties = MaybeUninit::<ffi::PhysicalDeviceProperties>::uninit();
+
+        unsafe { ffi::vkGetPhysicalDeviceProperties(self.handle, properties.as_mut_ptr()) };
+
+        unsafe { properties.assume_init() }.api_version
     }
 
     pub fn queue_families(&self) -> Vec<QueueFamilyProperties> {
@@ -3124,14 +4175,136 @@ impl PhysicalDevice {
         }
     }
 
+    pub fn enumerate_extensions(&self) -> Vec<String> {
+        let mut property_count = 0;
+
+        unsafe {
+            ffi::vkEnumerateDeviceExtensionProperties(
+                self.handle,
+                ptr::null(),
+                &mut property_count,
+                ptr::null_mut(),
+            )
+        };
+
+        let mut properties = Vec::<ffi::ExtensionProperties>::with_capacity(property_count as _);
+
+        unsafe {
+            ffi::vkEnumerateDeviceExtensionProperties(
+                self.handle,
+                ptr::null(),
+                &mut property_count,
+                properties.as_mut_ptr(),
+            )
+        };
+
+        unsafe { properties.set_len(property_count as _) };
+
+        properties
+            .iter()
+            .map(|properties| {
+                unsafe { CStr::from_ptr(properties.extension_name.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>()
+    }
+
+    /// The names in `extensions` this device doesn't support. `Device::new` fails with
+    /// `MissingExtension` naming the first of these.
+    pub fn missing_extensions<'a>(&self, extensions: &[&'a str]) -> Vec<&'a str> {
+        missing_extensions(&self.enumerate_extensions(), extensions)
+    }
+
+    /// `(budget, usage)` in bytes for each memory heap. The budget is the driver's estimate of
+    /// how much this process can allocate from the heap before things start failing or paging,
+    /// and changes with what other processes are doing. Without `VK_EXT_memory_budget` the heap
+    /// size is reported as the budget with no usage.
+    pub fn memory_budget(&self) -> Vec<(u64, u64)> {
+        let heap_sizes = || {
+            self.memory_properties()
+                .memory_heaps
+                .iter()
+                .map(|heap| (heap.size, 0))
+                .collect::<Vec<_>>()
+        };
+
+        let get_memory_properties2 = match self.instance.get_physical_device_memory_properties2 {
+            Some(f) => f,
+            None => return heap_sizes(),
+        };
+
+        if !self
+            .enumerate_extensions()
+            .iter()
+            .any(|extension| extension == EXT_MEMORY_BUDGET)
+        {
+            return heap_sizes();
+        }
+
+        let mut budget = ffi::PhysicalDeviceMemoryBudgetProperties {
+            structure_type: ffi::StructureType::PhysicalDeviceMemoryBudgetProperties,
+            p_next: ptr::null_mut(),
+            heap_budget: [0; 16],
+            heap_usage: [0; 16],
+        };
+
+        let mut memory_properties = ffi::PhysicalDeviceMemoryProperties2 {
+            structure_type: ffi::StructureType::PhysicalDeviceMemoryProperties2,
+            p_next: &mut budget as *mut _ as _,
+            memory_properties: unsafe { mem::zeroed() },
+        };
+
+        unsafe { get_memory_properties2(self.handle, &mut memory_properties) };
+
+        (0..memory_properties.memory_properties.memory_heap_count as usize)
+            .map(|i| (budget.heap_budget[i], budget.heap_usage[i]))
+            .collect::<Vec<_>>()
+    }
+
     //TODO
     pub fn surface_formats(&self, surface: &Surface) -> Vec<SurfaceFormat> {
         unimplemented!();
     }
 
-    //TODO
+    /// Modes from extensions this crate doesn't know about are left out. `Fifo` is always
+    /// supported.
     pub fn surface_present_modes(&self, surface: &Surface) -> Vec<PresentMode> {
-        unimplemented!();
+        let mut present_mode_count = 0;
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceSurfacePresentModesKHR(
+                self.handle,
+                surface.handle,
+                &mut present_mode_count,
+                ptr::null_mut(),
+            )
+        };
+
+        //read as plain integers, the driver can report modes the ffi enum has no variant for
+        let mut present_modes = Vec::<u32>::with_capacity(present_mode_count as _);
+
+        unsafe {
+            ffi::vkGetPhysicalDeviceSurfacePresentModesKHR(
+                self.handle,
+                surface.handle,
+                &mut present_mode_count,
+                present_modes.as_mut_ptr(),
+            )
+        };
+
+        unsafe { present_modes.set_len(present_mode_count as _) };
+
+        present_modes
+            .into_iter()
+            .filter_map(|present_mode| match present_mode {
+                0 => Some(PresentMode::Immediate),
+                1 => Some(PresentMode::Mailbox),
+                2 => Some(PresentMode::Fifo),
+                3 => Some(PresentMode::FifoRelaxed),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
     }
 }
 
@@ -3148,12 +4321,26 @@ pub struct DeviceQueueCreateInfo<'a> {
 pub struct DeviceCreateInfo<'a> {
     pub queues: &'a [DeviceQueueCreateInfo<'a>],
     pub enabled_features: &'a PhysicalDeviceFeatures,
-    pub extensions: &'a [&'a str],
+    pub extensions: &'a [&'static str],
     pub layers: &'a [&'a str],
 }
 
 pub struct Device {
+    instance: Rc<Instance>,
     handle: ffi::Device,
+    enabled_features: PhysicalDeviceFeatures,
+    limits: PhysicalDeviceLimits,
+    dynamic_rendering: Option<(ffi::CmdBeginRendering, ffi::CmdEndRendering)>,
+    draw_indirect_count: Option<ffi::CmdDrawIndexedIndirectCount>,
+    draw_mesh_tasks: Option<ffi::CmdDrawMeshTasks>,
+    descriptor_indexing: bool,
+    sampler_filter_minmax: bool,
+    timeline_semaphore: bool,
+}
+
+//names only show up in debugging tools, so nul bytes are dropped rather than failing on them
+fn debug_name(name: &str) -> CString {
+    CString::new(name.replace('\0', "")).unwrap()
 }
 
 impl Device {
@@ -3161,6 +4348,9 @@ impl Device {
         physical_device: &PhysicalDevice,
         create_info: DeviceCreateInfo<'_>,
     ) -> Result<Rc<Device>, Error> {
+        //checked before creating anything so the error can say which one is missing
+        check_extensions(&physical_device.enumerate_extensions(), create_info.extensions)?;
+
         let queue_create_infos = create_info
             .queues
             .iter()
@@ -3196,11 +4386,130 @@ impl Device {
             .map(|string| string.as_ptr())
             .collect::<Vec<_>>();
 
-        let enabled_features = create_info.enabled_features.clone().into();
+        let features = *create_info.enabled_features;
+
+        let enabled_features = features.into();
+
+        //core since 1.3 when both the instance and the driver are, but the feature has to be
+        //turned on either way. a 1.3 loader can still drive an older driver
+        let version = u32::from(Version::from((1, 3, 0)));
+
+        let core_dynamic_rendering = physical_device.instance.api_version >= version
+            && physical_device.api_version() >= version;
+
+        //the extension requires the feature, core 1.3 only has to report it
+        let dynamic_rendering = if create_info.extensions.contains(&KHR_DYNAMIC_RENDERING) {
+            true
+        } else if core_dynamic_rendering {
+            let mut supported = ffi::PhysicalDeviceDynamicRenderingFeatures {
+                structure_type: ffi::StructureType::PhysicalDeviceDynamicRenderingFeatures,
+                p_next: ptr::null_mut(),
+                dynamic_rendering: false as _,
+            };
+
+            physical_device.query_features(&mut supported as *mut _ as _)
+                && supported.dynamic_rendering != 0
+        } else {
+            false
+        };
+
+        //core since 1.2 as well, but only behind a feature in the 1.2 feature struct, so the
+        //extension is what turns it on here
+        let draw_indirect_count = create_info.extensions.contains(&KHR_DRAW_INDIRECT_COUNT);
+
+        let mesh_shader = create_info.extensions.contains(&EXT_MESH_SHADER);
+
+        let descriptor_indexing = create_info.extensions.contains(&EXT_DESCRIPTOR_INDEXING);
+
+        let sampler_filter_minmax = create_info.extensions.contains(&EXT_SAMPLER_FILTER_MINMAX);
+
+        let timeline_semaphore = create_info.extensions.contains(&KHR_TIMELINE_SEMAPHORE);
+
+        //optional in the extension, so it has to be reported before it can be turned on
+        let mut timeline_semaphore_features = ffi::PhysicalDeviceTimelineSemaphoreFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceTimelineSemaphoreFeatures,
+            p_next: ptr::null_mut(),
+            timeline_semaphore: false as _,
+        };
+
+        if timeline_semaphore {
+            let supported = physical_device
+                .query_features(&mut timeline_semaphore_features as *mut _ as _)
+                && timeline_semaphore_features.timeline_semaphore != 0;
+
+            if !supported {
+                return Err(Error::FeatureNotPresent);
+            }
+        }
+
+        //only what every device with the extension has to support, so this can't fail
+        let mut descriptor_indexing_features = ffi::PhysicalDeviceDescriptorIndexingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDescriptorIndexingFeatures,
+            p_next: ptr::null_mut(),
+            shader_input_attachment_array_dynamic_indexing: false as _,
+            shader_uniform_texel_buffer_array_dynamic_indexing: true as _,
+            shader_storage_texel_buffer_array_dynamic_indexing: true as _,
+            shader_uniform_buffer_array_non_uniform_indexing: false as _,
+            shader_sampled_image_array_non_uniform_indexing: true as _,
+            shader_storage_buffer_array_non_uniform_indexing: true as _,
+            shader_storage_image_array_non_uniform_indexing: false as _,
+            shader_input_attachment_array_non_uniform_indexing: false as _,
+            shader_uniform_texel_buffer_array_non_uniform_indexing: true as _,
+            shader_storage_texel_buffer_array_non_uniform_indexing: false as _,
+            descriptor_binding_uniform_buffer_update_after_bind: false as _,
+            descriptor_binding_sampled_image_update_after_bind: true as _,
+            descriptor_binding_storage_image_update_after_bind: true as _,
+            descriptor_binding_storage_buffer_update_after_bind: true as _,
+            descriptor_binding_uniform_texel_buffer_update_after_bind: true as _,
+            descriptor_binding_storage_texel_buffer_update_after_bind: true as _,
+            descriptor_binding_update_unused_while_pending: true as _,
+            descriptor_binding_partially_bound: true as _,
+            descriptor_binding_variable_descriptor_count: false as _,
+            runtime_descriptor_array: true as _,
+        };
+
+        let mut mesh_shader_features = ffi::PhysicalDeviceMeshShaderFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceMeshShaderFeatures,
+            p_next: ptr::null_mut(),
+            task_shader: true as _,
+            mesh_shader: true as _,
+            multiview_mesh_shader: false as _,
+            primitive_fragment_shading_rate_mesh_shader: false as _,
+            mesh_shader_queries: false as _,
+        };
+
+        let mut dynamic_rendering_features = ffi::PhysicalDeviceDynamicRenderingFeatures {
+            structure_type: ffi::StructureType::PhysicalDeviceDynamicRenderingFeatures,
+            p_next: ptr::null_mut(),
+            dynamic_rendering: true as _,
+        };
+
+        //each enabled feature struct is pushed onto the front of the chain
+        let mut p_next = ptr::null_mut::<()>();
+
+        if descriptor_indexing {
+            descriptor_indexing_features.p_next = p_next;
+            p_next = &mut descriptor_indexing_features as *mut _ as _;
+        }
+
+        if mesh_shader {
+            mesh_shader_features.p_next = p_next;
+            p_next = &mut mesh_shader_features as *mut _ as _;
+        }
+
+        if dynamic_rendering {
+            dynamic_rendering_features.p_next = p_next;
+            p_next = &mut dynamic_rendering_features as *mut _ as _;
+        }
+
+        if timeline_semaphore {
+            timeline_semaphore_features.p_next = p_next;
+            p_next = &mut timeline_semaphore_features as *mut _ as _;
+        }
 
         let create_info = ffi::DeviceCreateInfo {
             structure_type: ffi::StructureType::DeviceCreateInfo,
-            p_next: ptr::null(),
+            p_next: p_next as *const _,
             flags: 0,
             queue_create_info_count: queue_create_infos.len() as _,
             queue_create_infos: queue_create_infos.as_ptr(),
@@ -3226,11 +4535,72 @@ impl Device {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let device = Self { handle };
+                let proc_addr = |name: &[u8]| {
+                    let name = CStr::from_bytes_with_nul(name).unwrap();
 
-                let device = Rc::new(device);
+                    unsafe { ffi::vkGetDeviceProcAddr(handle, name.as_ptr()) }
+                };
 
-                Ok(device)
+                let dynamic_rendering = match (dynamic_rendering, core_dynamic_rendering) {
+                    (false, _) => None,
+                    (true, true) => unsafe {
+                        Option::zip(
+                            mem::transmute::<*const (), Option<ffi::CmdBeginRendering>>(proc_addr(
+                                b"vkCmdBeginRendering\0",
+                            )),
+                            mem::transmute::<*const (), Option<ffi::CmdEndRendering>>(proc_addr(
+                                b"vkCmdEndRendering\0",
+                            )),
+                        )
+                    },
+                    (true, false) => unsafe {
+                        Option::zip(
+                            mem::transmute::<*const (), Option<ffi::CmdBeginRendering>>(proc_addr(
+                                b"vkCmdBeginRenderingKHR\0",
+                            )),
+                            mem::transmute::<*const (), Option<ffi::CmdEndRendering>>(proc_addr(
+                                b"vkCmdEndRenderingKHR\0",
+                            )),
+                        )
+                    },
+                };
+
+                let draw_indirect_count = if draw_indirect_count {
+                    unsafe {
+                        mem::transmute::<*const (), Option<ffi::CmdDrawIndexedIndirectCount>>(
+                            proc_addr(b"vkCmdDrawIndexedIndirectCountKHR\0"),
+                        )
+                    }
+                } else {
+                    None
+                };
+
+                let draw_mesh_tasks = if mesh_shader {
+                    unsafe {
+                        mem::transmute::<*const (), Option<ffi::CmdDrawMeshTasks>>(proc_addr(
+                            b"vkCmdDrawMeshTasksEXT\0",
+                        ))
+                    }
+                } else {
+                    None
+                };
+
+                let device = Self {
+                    instance: physical_device.instance.clone(),
+                    handle,
+                    enabled_features: features,
+                    limits: physical_device.properties().limits,
+                    dynamic_rendering,
+                    draw_indirect_count,
+                    draw_mesh_tasks,
+                    descriptor_indexing,
+                    sampler_filter_minmax,
+                    timeline_semaphore,
+                };
+
+                let device = Rc::new(device);
+
+                Ok(device)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
@@ -3243,6 +4613,92 @@ impl Device {
         }
     }
 
+    pub fn enabled_features(&self) -> PhysicalDeviceFeatures {
+        self.enabled_features
+    }
+
+    pub fn dynamic_rendering_enabled(&self) -> bool {
+        self.dynamic_rendering.is_some()
+    }
+
+    pub fn draw_indirect_count_enabled(&self) -> bool {
+        self.draw_indirect_count.is_some()
+    }
+
+    pub fn mesh_shader_enabled(&self) -> bool {
+        self.draw_mesh_tasks.is_some()
+    }
+
+    pub fn descriptor_indexing_enabled(&self) -> bool {
+        self.descriptor_indexing
+    }
+
+    pub fn sampler_filter_minmax_enabled(&self) -> bool {
+        self.sampler_filter_minmax
+    }
+
+    pub fn timeline_semaphore_enabled(&self) -> bool {
+        self.timeline_semaphore
+    }
+
+    /// Records `script` into a fresh command buffer from `command_pool`, submits it to `queue`
+    /// and blocks on a fence until it has executed. For one off work like uploads, where
+    /// waiting on the whole queue would also wait on unrelated submissions.
+    pub fn one_time_submit(
+        &self,
+        queue: &mut Queue,
+        command_pool: &CommandPool,
+        script: impl FnOnce(Commands<'_>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        if command_pool.device.handle != self.handle {
+            panic!("command pool must be for same device");
+        }
+
+        let device = command_pool.device.clone();
+
+        let command_buffer_allocate_info = CommandBufferAllocateInfo {
+            command_pool,
+            level: CommandBufferLevel::Primary,
+            count: 1,
+        };
+
+        let mut command_buffer =
+            CommandBuffer::allocate(device.clone(), command_buffer_allocate_info)?.remove(0);
+
+        let result = command_buffer
+            .record_with_flags(COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT, script)
+            .and_then(|()| Self::submit_and_wait(device, queue, &command_buffer));
+
+        //only freed once the fence says it's done, or if it never made it to the queue
+        if !matches!(result, Err(Error::DeviceLost)) {
+            CommandBuffer::free(command_pool, vec![command_buffer]);
+        }
+
+        result
+    }
+
+    fn submit_and_wait(
+        device: Rc<Device>,
+        queue: &mut Queue,
+        command_buffer: &CommandBuffer,
+    ) -> Result<(), Error> {
+        //fences are created signaled
+        let mut fence = Fence::new(device, FenceCreateInfo {})?;
+
+        Fence::reset(&[&mut fence])?;
+
+        let submit_info = SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages: &[],
+            signal_semaphores: &[],
+            command_buffers: &[command_buffer],
+        };
+
+        queue.submit(&[submit_info], Some(&mut fence))?;
+
+        Fence::wait(&[&mut fence], true, u64::MAX)
+    }
+
     pub fn queue(&self, queue_family_index: u32) -> Queue {
         let mut handle = MaybeUninit::<ffi::Queue>::uninit();
 
@@ -3266,6 +4722,32 @@ impl Device {
             _ => panic!("unexpected result: {:?}", result),
         }
     }
+
+    pub fn set_object_name<T: HasHandle>(&self, object: &T, name: &str) -> Result<(), Error> {
+        let f = match self.instance.debug_utils.set_object_name {
+            Some(f) => f,
+            None => return Err(Error::ExtensionNotPresent),
+        };
+
+        let object_name = debug_name(name);
+
+        let name_info = ffi::DebugUtilsObjectNameInfo {
+            structure_type: ffi::StructureType::DebugUtilsObjectNameInfo,
+            p_next: ptr::null(),
+            object_type: T::OBJECT_TYPE.into(),
+            object_handle: object.raw_handle(),
+            object_name: object_name.as_ptr(),
+        };
+
+        let result = unsafe { f(self.handle, &name_info) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
 }
 
 impl Drop for Device {
@@ -3284,6 +4766,43 @@ impl Queue {
         submit_infos: &'_ [SubmitInfo],
         fence: Option<&'_ mut Fence>,
     ) -> Result<(), Error> {
+        self.submit_with_values(submit_infos, None, fence)
+    }
+
+    /// Same as `submit`, with one `TimelineSemaphoreSubmitInfo` per submit info giving the
+    /// counter value of each wait and signal semaphore. Values are ignored for binary
+    /// semaphores, but there still has to be one per semaphore.
+    pub fn submit_timeline(
+        &mut self,
+        submit_infos: &'_ [SubmitInfo],
+        timeline_infos: &'_ [TimelineSemaphoreSubmitInfo],
+        fence: Option<&'_ mut Fence>,
+    ) -> Result<(), Error> {
+        self.submit_with_values(submit_infos, Some(timeline_infos), fence)
+    }
+
+    fn submit_with_values(
+        &mut self,
+        submit_infos: &'_ [SubmitInfo],
+        timeline_infos: Option<&'_ [TimelineSemaphoreSubmitInfo]>,
+        fence: Option<&'_ mut Fence>,
+    ) -> Result<(), Error> {
+        check_submit_counts(submit_infos, timeline_infos)?;
+
+        let timeline_infos = timeline_infos.map(|timeline_infos| {
+            timeline_infos
+                .iter()
+                .map(|timeline_info| ffi::TimelineSemaphoreSubmitInfo {
+                    structure_type: ffi::StructureType::TimelineSemaphoreSubmitInfo,
+                    p_next: ptr::null(),
+                    wait_semaphore_value_count: timeline_info.wait_values.len() as _,
+                    wait_semaphore_values: timeline_info.wait_values.as_ptr(),
+                    signal_semaphore_value_count: timeline_info.signal_values.len() as _,
+                    signal_semaphore_values: timeline_info.signal_values.as_ptr(),
+                })
+                .collect::<Vec<_>>()
+        });
+
         let wait_semaphores = submit_infos
             .iter()
             .map(|submit_info| {
@@ -3322,7 +4841,9 @@ impl Queue {
             .enumerate()
             .map(|(i, submit_info)| ffi::SubmitInfo {
                 structure_type: ffi::StructureType::SubmitInfo,
-                p_next: ptr::null(),
+                p_next: timeline_infos
+                    .as_ref()
+                    .map_or(ptr::null(), |timeline_infos| &timeline_infos[i] as *const _ as _),
                 wait_semaphore_count: wait_semaphores[i].len() as _,
                 wait_semaphores: wait_semaphores[i].as_ptr(),
                 wait_dst_stage_mask: submit_info.wait_stages.as_ptr() as _,
@@ -3455,7 +4976,7 @@ impl Surface {
                     structure_type: ffi::StructureType::XlibSurfaceCreateInfo,
                     p_next: ptr::null(),
                     flags: 0,
-                    display: xlib_handle.display,
+                    display: xlib_handle.display as _,
                     window: xlib_handle.window,
                 };
 
@@ -3507,19 +5028,68 @@ pub struct SwapchainCreateInfo<'a> {
 pub struct Swapchain {
     device: Rc<Device>,
     handle: ffi::Swapchain,
+    format: Format,
+    color_space: ColorSpace,
+    extent: Extent2d,
 }
 
 impl Swapchain {
     pub fn new(device: Rc<Device>, create_info: SwapchainCreateInfo<'_>) -> Result<Self, Error> {
-        let image_format = match create_info.image_format {
-            Format::Bgra8Srgb => ffi::Format::Bgra8Srgb,
-            _ => unimplemented!(),
-        };
+        //the old swapchain lives in create_info, so it is destroyed after the new one exists
+        let old_swapchain_handle = create_info
+            .old_swapchain
+            .as_ref()
+            .map_or(ffi::Swapchain::null(), |swapchain| swapchain.handle);
 
-        let image_color_space = match create_info.image_color_space {
-            ColorSpace::SrgbNonlinear => ffi::ColorSpace::SrgbNonlinear,
-            _ => unimplemented!(),
-        };
+        let handle = Self::create(&device, &create_info, old_swapchain_handle)?;
+
+        Ok(Self {
+            device,
+            handle,
+            format: create_info.image_format,
+            color_space: create_info.image_color_space,
+            extent: create_info.image_extent,
+        })
+    }
+
+    /// Replaces the swapchain in place, handing the current one to the driver as the old
+    /// swapchain so presentation can carry over. `old_swapchain` in `create_info` is ignored.
+    /// Images fetched before this call are invalidated.
+    pub fn recreate(&mut self, create_info: SwapchainCreateInfo<'_>) -> Result<(), Error> {
+        let handle = Self::create(&self.device, &create_info, self.handle)?;
+
+        unsafe { ffi::vkDestroySwapchainKHR(self.device.handle, self.handle, ptr::null()) };
+
+        self.handle = handle;
+        self.format = create_info.image_format;
+        self.color_space = create_info.image_color_space;
+        self.extent = create_info.image_extent;
+
+        Ok(())
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// The extent the images were created with, which can lag behind the surface until the
+    /// swapchain is recreated.
+    pub fn extent(&self) -> Extent2d {
+        self.extent
+    }
+
+    fn create(
+        device: &Device,
+        create_info: &SwapchainCreateInfo<'_>,
+        old_swapchain: ffi::Swapchain,
+    ) -> Result<ffi::Swapchain, Error> {
+        let image_format = create_info.image_format.into();
+
+        let image_color_space = create_info.image_color_space.into();
 
         let image_extent = [
             create_info.image_extent.0 as _,
@@ -3528,32 +5098,13 @@ impl Swapchain {
 
         let image_usage = create_info.image_usage;
 
-        let image_sharing_mode = match create_info.image_sharing_mode {
-            SharingMode::Exclusive => ffi::SharingMode::Exclusive,
-            _ => unimplemented!(),
-        };
-
-        let queue_family_indices = unsafe { mem::transmute(&create_info.queue_family_indices) };
-
-        let composite_alpha = match create_info.composite_alpha {
-            CompositeAlpha::Opaque => ffi::CompositeAlpha::Opaque,
-            _ => unimplemented!(),
-        };
+        let image_sharing_mode = create_info.image_sharing_mode.into();
 
-        let present_mode = match create_info.present_mode {
-            PresentMode::Immediate => ffi::PresentMode::Immediate,
-            PresentMode::Mailbox => ffi::PresentMode::Mailbox,
-            PresentMode::Fifo => ffi::PresentMode::Fifo,
-            PresentMode::FifoRelaxed => ffi::PresentMode::FifoRelaxed,
-        };
+        let queue_family_indices = create_info.queue_family_indices.as_ptr();
 
-        //if this is not extracted, and handle is not declared separately,
-        //then swapchain will be dropped too early.
-        let old_swapchain = create_info.old_swapchain;
+        let composite_alpha = create_info.composite_alpha.into();
 
-        let old_swapchain_handle = old_swapchain
-            .as_ref()
-            .map_or(ffi::Swapchain::null(), |swapchain| swapchain.handle);
+        let present_mode = create_info.present_mode.into();
 
         let create_info = ffi::SwapchainCreateInfo {
             structure_type: ffi::StructureType::SwapchainCreateInfo,
@@ -3573,7 +5124,7 @@ impl Swapchain {
             composite_alpha,
             present_mode,
             clipped: create_info.clipped as _,
-            old_swapchain: old_swapchain_handle,
+            old_swapchain,
         };
 
         let mut handle = MaybeUninit::<ffi::Swapchain>::uninit();
@@ -3588,13 +5139,7 @@ impl Swapchain {
         };
 
         match result {
-            ffi::Result::Success => {
-                let handle = unsafe { handle.assume_init() };
-
-                let swapchain = Self { device, handle };
-
-                Ok(swapchain)
-            }
+            ffi::Result::Success => Ok(unsafe { handle.assume_init() }),
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             ffi::Result::DeviceLost => Err(Error::DeviceLost),
@@ -3637,6 +5182,9 @@ impl Swapchain {
                 device: self.device.clone(),
                 handle,
                 user: false,
+                mip_levels: 1,
+                array_layers: 1,
+                flags: 0,
             })
             .collect::<Vec<_>>();
 
@@ -3686,18 +5234,23 @@ impl Drop for Swapchain {
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum ImageType {
     OneDim,
     TwoDim,
     ThreeDim,
 }
 
+#[derive(Clone, Copy)]
 pub enum ImageTiling {
     Optimal,
+    /// Row major texels the host can map and read directly. Most drivers only support it
+    /// for host visible 2d staging images with one mip level and layer, and no attachment use.
     Linear,
 }
 
 pub struct ImageCreateInfo {
+    pub flags: u32,
     pub image_type: ImageType,
     pub format: Format,
     pub extent: Extent3d,
@@ -3713,14 +5266,24 @@ pub struct Image {
     device: Rc<Device>,
     handle: ffi::Image,
     user: bool,
+    mip_levels: u32,
+    array_layers: u32,
+    flags: u32,
 }
 
 impl Image {
     pub fn new(device: Rc<Device>, create_info: ImageCreateInfo) -> Result<Self, Error> {
+        let ImageCreateInfo {
+            flags,
+            mip_levels,
+            array_layers,
+            ..
+        } = create_info;
+
         let create_info = ffi::ImageCreateInfo {
             structure_type: ffi::StructureType::ImageCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags,
             image_type: create_info.image_type.into(),
             extent: [
                 create_info.extent.0,
@@ -3758,6 +5321,9 @@ impl Image {
                     device,
                     handle,
                     user: true,
+                    mip_levels,
+                    array_layers,
+                    flags,
                 };
 
                 Ok(image)
@@ -3785,17 +5351,29 @@ impl Image {
         MemoryRequirements {
             size: memory_requirements.size,
             alignment: memory_requirements.alignment,
-            memory_type: memory_requirements.memory_type,
+            memory_type_bits: memory_requirements.memory_type_bits,
         }
     }
 
+    #[must_use = "an unbound image cannot be used"]
     pub fn bind_memory(&mut self, memory: &Memory) -> Result<(), Error> {
+        self.bind_memory_at(memory, 0)
+    }
+
+    /// Fails with `MisalignedOffset` if `offset` breaks the image's alignment requirement.
+    #[must_use = "an unbound image cannot be used"]
+    pub fn bind_memory_at(&mut self, memory: &Memory, offset: u64) -> Result<(), Error> {
         if !self.user {
             panic!("cannot bind memory to non-user image");
         }
 
-        let result =
-            unsafe { ffi::vkBindImageMemory(self.device.handle, self.handle, memory.handle, 0) };
+        if !offset.is_multiple_of(self.memory_requirements().alignment) {
+            return Err(Error::MisalignedOffset);
+        }
+
+        let result = unsafe {
+            ffi::vkBindImageMemory(self.device.handle, self.handle, memory.handle, offset)
+        };
 
         match result {
             ffi::Result::Success => Ok(()),
@@ -3819,10 +5397,46 @@ pub enum ImageViewType {
     OneDim,
     TwoDim,
     ThreeDim,
+    /// Needs exactly 6 layers of an image created with `IMAGE_CREATE_CUBE_COMPATIBLE`.
     Cube,
     OneDimArray,
     TwoDimArray,
-    ThreeDimArray,
+    /// Like `Cube`, with a multiple of 6 layers.
+    CubeArray,
+}
+
+impl ImageViewType {
+    //the range has to fit the image, and the layer count has to suit the view type
+    fn check_range(
+        &self,
+        range: &ImageSubresourceRange,
+        mip_levels: u32,
+        array_layers: u32,
+        cube_compatible: bool,
+    ) -> Result<(), Error> {
+        let mip_end = range.base_mip_level.checked_add(range.level_count);
+
+        if range.level_count == 0 || !matches!(mip_end, Some(end) if end <= mip_levels) {
+            return Err(Error::InvalidMipRange);
+        }
+
+        let layer_count = range.layer_count;
+
+        let layer_count_valid = match self {
+            Self::OneDim | Self::TwoDim | Self::ThreeDim => layer_count == 1,
+            Self::OneDimArray | Self::TwoDimArray => layer_count > 0,
+            Self::Cube => cube_compatible && layer_count == 6,
+            Self::CubeArray => cube_compatible && layer_count > 0 && layer_count.is_multiple_of(6),
+        };
+
+        let layer_end = range.base_array_layer.checked_add(layer_count);
+
+        if !layer_count_valid || !matches!(layer_end, Some(end) if end <= array_layers) {
+            return Err(Error::InvalidLayerRange);
+        }
+
+        Ok(())
+    }
 }
 
 pub enum ComponentSwizzle {
@@ -3860,39 +5474,38 @@ pub struct ImageViewCreateInfo<'a> {
 pub struct ImageView {
     device: Rc<Device>,
     handle: ffi::ImageView,
+    level_count: u32,
+    layer_count: u32,
 }
 
 impl ImageView {
+    /// Fails with `InvalidMipRange` or `InvalidLayerRange` if the range doesn't fit the image,
+    /// or the layers don't fit the view type.
     pub fn new(device: Rc<Device>, create_info: ImageViewCreateInfo) -> Result<Self, Error> {
-        //TODO change these to From impl
-        let view_type = match create_info.view_type {
-            ImageViewType::OneDim => ffi::ImageViewType::OneDim,
-            ImageViewType::TwoDim => ffi::ImageViewType::TwoDim,
-            ImageViewType::ThreeDim => ffi::ImageViewType::ThreeDim,
-            ImageViewType::Cube => ffi::ImageViewType::Cube,
-            ImageViewType::OneDimArray => ffi::ImageViewType::OneDimArray,
-            ImageViewType::TwoDimArray => ffi::ImageViewType::TwoDimArray,
-            ImageViewType::ThreeDimArray => ffi::ImageViewType::ThreeDimArray,
-        };
+        let image = create_info.image;
 
-        let format = create_info.format.into();
+        create_info.view_type.check_range(
+            &create_info.subresource_range,
+            image.mip_levels,
+            image.array_layers,
+            image.flags & IMAGE_CREATE_CUBE_COMPATIBLE != 0,
+        )?;
 
-        //TODO convert to From<non-ffi> for ffi
-        let swizzle_f = |component| match component {
-            ComponentSwizzle::Identity => ffi::ComponentSwizzle::Identity,
-            ComponentSwizzle::Zero => ffi::ComponentSwizzle::Zero,
-            ComponentSwizzle::One => ffi::ComponentSwizzle::One,
-            ComponentSwizzle::R => ffi::ComponentSwizzle::R,
-            ComponentSwizzle::G => ffi::ComponentSwizzle::G,
-            ComponentSwizzle::B => ffi::ComponentSwizzle::B,
-            ComponentSwizzle::A => ffi::ComponentSwizzle::A,
-        };
+        let ImageSubresourceRange {
+            level_count,
+            layer_count,
+            ..
+        } = create_info.subresource_range;
+
+        let view_type = create_info.view_type.into();
+
+        let format = create_info.format.into();
 
         let components = ffi::ComponentMapping {
-            r: swizzle_f(create_info.components.r),
-            g: swizzle_f(create_info.components.g),
-            b: swizzle_f(create_info.components.b),
-            a: swizzle_f(create_info.components.a),
+            r: create_info.components.r.into(),
+            g: create_info.components.g.into(),
+            b: create_info.components.b.into(),
+            a: create_info.components.a.into(),
         };
 
         let subresource_range = ffi::ImageSubresourceRange {
@@ -3929,7 +5542,12 @@ impl ImageView {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let image_view = Self { device, handle };
+                let image_view = Self {
+                    device,
+                    handle,
+                    level_count,
+                    layer_count,
+                };
 
                 Ok(image_view)
             }
@@ -3938,6 +5556,65 @@ impl ImageView {
             _ => panic!("unexpected result: {:?}", result),
         }
     }
+
+    /// A 2d view of just `mip_level` of the first layer, e.g. to render into one level while
+    /// generating mipmaps.
+    pub fn of_mip(
+        device: Rc<Device>,
+        image: &Image,
+        format: Format,
+        mip_level: u32,
+    ) -> Result<Self, Error> {
+        let create_info = ImageViewCreateInfo {
+            image,
+            view_type: ImageViewType::TwoDim,
+            format,
+            components: ComponentMapping {
+                r: ComponentSwizzle::Identity,
+                g: ComponentSwizzle::Identity,
+                b: ComponentSwizzle::Identity,
+                a: ComponentSwizzle::Identity,
+            },
+            subresource_range: ImageSubresourceRange {
+                aspect_mask: format.aspect_mask(),
+                base_mip_level: mip_level,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+        };
+
+        Self::new(device, create_info)
+    }
+
+    /// A cube view of the 6 layers starting at `base_layer`, covering every mip level.
+    pub fn cube(
+        device: Rc<Device>,
+        image: &Image,
+        format: Format,
+        base_layer: u32,
+    ) -> Result<Self, Error> {
+        let create_info = ImageViewCreateInfo {
+            image,
+            view_type: ImageViewType::Cube,
+            format,
+            components: ComponentMapping {
+                r: ComponentSwizzle::Identity,
+                g: ComponentSwizzle::Identity,
+                b: ComponentSwizzle::Identity,
+                a: ComponentSwizzle::Identity,
+            },
+            subresource_range: ImageSubresourceRange {
+                aspect_mask: format.aspect_mask(),
+                base_mip_level: 0,
+                level_count: image.mip_levels,
+                base_array_layer: base_layer,
+                layer_count: 6,
+            },
+        };
+
+        Self::new(device, create_info)
+    }
 }
 
 impl Drop for ImageView {
@@ -3955,14 +5632,28 @@ pub struct ShaderModule {
     handle: ffi::ShaderModule,
 }
 
+//drivers tend to crash on garbage instead of reporting it, so the header is checked first.
+//code with the magic number byte swapped was written in the other endianness and is swapped back
+fn spirv_code(code: &[u32]) -> Result<Cow<'_, [u32]>, Error> {
+    match code.first() {
+        Some(&SPIRV_MAGIC) => Ok(Cow::Borrowed(code)),
+        Some(&magic) if magic == SPIRV_MAGIC.swap_bytes() => {
+            Ok(code.iter().map(|word| word.swap_bytes()).collect())
+        }
+        _ => Err(Error::InvalidShader),
+    }
+}
+
 impl ShaderModule {
     pub fn new(device: Rc<Device>, create_info: ShaderModuleCreateInfo<'_>) -> Result<Self, Error> {
+        let code = spirv_code(create_info.code)?;
+
         let create_info = ffi::ShaderModuleCreateInfo {
             structure_type: ffi::StructureType::ShaderModuleCreateInfo,
             p_next: ptr::null(),
             flags: 0,
-            code_size: create_info.code.len() * mem::size_of::<u32>(),
-            code: create_info.code.as_ptr(),
+            code_size: code.len() * mem::size_of::<u32>(),
+            code: code.as_ptr(),
         };
 
         let mut handle = MaybeUninit::<ffi::ShaderModule>::uninit();
@@ -4061,6 +5752,28 @@ pub struct Rect2d {
     pub extent: Extent2d,
 }
 
+impl From<&Viewport> for ffi::Viewport {
+    fn from(viewport: &Viewport) -> Self {
+        Self {
+            x: viewport.x,
+            y: viewport.y,
+            width: viewport.width,
+            height: viewport.height,
+            min_depth: viewport.min_depth,
+            max_depth: viewport.max_depth,
+        }
+    }
+}
+
+impl From<&Rect2d> for ffi::Rect2d {
+    fn from(rect: &Rect2d) -> Self {
+        Self {
+            offset: [rect.offset.0, rect.offset.1],
+            extent: [rect.extent.0, rect.extent.1],
+        }
+    }
+}
+
 pub struct PipelineViewportStateCreateInfo<'a> {
     pub viewports: &'a [Viewport],
     pub scissors: &'a [Rect2d],
@@ -4073,6 +5786,37 @@ pub enum PolygonMode {
     Point,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back,
+    FrontAndBack,
+}
+
+impl From<CullMode> for u32 {
+    fn from(cull_mode: CullMode) -> Self {
+        match cull_mode {
+            CullMode::None => CULL_MODE_NONE,
+            CullMode::Front => CULL_MODE_FRONT,
+            CullMode::Back => CULL_MODE_BACK,
+            CullMode::FrontAndBack => CULL_MODE_FRONT_AND_BACK,
+        }
+    }
+}
+
+//for the `CULL_MODE_*` flags, bits other than front and back are ignored
+impl From<u32> for CullMode {
+    fn from(flags: u32) -> Self {
+        match flags & CULL_MODE_FRONT_AND_BACK {
+            CULL_MODE_FRONT => Self::Front,
+            CULL_MODE_BACK => Self::Back,
+            CULL_MODE_FRONT_AND_BACK => Self::FrontAndBack,
+            _ => Self::None,
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum FrontFace {
     Clockwise,
@@ -4083,7 +5827,7 @@ pub struct PipelineRasterizationStateCreateInfo {
     pub depth_clamp_enable: bool,
     pub rasterizer_discard_enable: bool,
     pub polygon_mode: PolygonMode,
-    pub cull_mode: u32,
+    pub cull_mode: CullMode,
     pub front_face: FrontFace,
     pub depth_bias_enable: bool,
     pub depth_bias_constant_factor: f32,
@@ -4103,17 +5847,37 @@ pub struct PipelineDepthStencilStateCreateInfo {
     pub max_depth_bounds: f32,
 }
 
+/// The `Src1` factors read the second fragment output and need `dual_src_blend`.
 #[derive(Clone, Copy)]
 pub enum BlendFactor {
-    One,
     Zero,
+    One,
+    SrcColor,
+    OneMinusSrcColor,
+    DstColor,
+    OneMinusDstColor,
     SrcAlpha,
     OneMinusSrcAlpha,
+    DstAlpha,
+    OneMinusDstAlpha,
+    ConstantColor,
+    OneMinusConstantColor,
+    ConstantAlpha,
+    OneMinusConstantAlpha,
+    SrcAlphaSaturate,
+    Src1Color,
+    OneMinusSrc1Color,
+    Src1Alpha,
+    OneMinusSrc1Alpha,
 }
 
 #[derive(Clone, Copy)]
 pub enum BlendOp {
     Add,
+    Subtract,
+    ReverseSubtract,
+    Min,
+    Max,
 }
 
 pub struct PipelineColorBlendAttachmentState {
@@ -4127,9 +5891,25 @@ pub struct PipelineColorBlendAttachmentState {
     pub alpha_blend_op: BlendOp,
 }
 
+/// Only used with `logic_op_enable`, which needs the `logic_op` feature.
 #[derive(Clone, Copy)]
 pub enum LogicOp {
+    Clear,
+    And,
+    AndReverse,
     Copy,
+    AndInverted,
+    NoOp,
+    Xor,
+    Or,
+    Nor,
+    Equivalent,
+    Invert,
+    OrReverse,
+    CopyInverted,
+    OrInverted,
+    Nand,
+    Set,
 }
 
 pub struct PipelineColorBlendStateCreateInfo<'a> {
@@ -4142,6 +5922,9 @@ pub struct PipelineColorBlendStateCreateInfo<'a> {
 #[derive(Clone, Copy)]
 pub enum DynamicState {
     Viewport,
+    Scissor,
+    LineWidth,
+    DepthBias,
 }
 
 pub struct PipelineDynamicStateCreateInfo<'a> {
@@ -4234,6 +6017,41 @@ pub enum ImageLayout {
     PresentSrc,
 }
 
+impl ImageLayout {
+    //where an image in this layout is usually accessed, as (stage mask, access mask)
+    fn usage(self) -> (u32, u32) {
+        match self {
+            Self::Undefined | Self::Preinitialized => (PIPELINE_STAGE_TOP_OF_PIPE, 0),
+            Self::General => (
+                PIPELINE_STAGE_ALL_COMMANDS,
+                ACCESS_MEMORY_READ | ACCESS_MEMORY_WRITE,
+            ),
+            Self::ColorAttachment => (
+                PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT,
+                ACCESS_COLOR_ATTACHMENT_READ | ACCESS_COLOR_ATTACHMENT_WRITE,
+            ),
+            Self::DepthStencilAttachment => (
+                PIPELINE_STAGE_EARLY_FRAGMENT_TESTS | PIPELINE_STAGE_LATE_FRAGMENT_TESTS,
+                ACCESS_DEPTH_STENCIL_ATTACHMENT_READ | ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE,
+            ),
+            Self::DepthStencilReadOnly => (
+                PIPELINE_STAGE_EARLY_FRAGMENT_TESTS
+                    | PIPELINE_STAGE_LATE_FRAGMENT_TESTS
+                    | PIPELINE_STAGE_FRAGMENT_SHADER,
+                ACCESS_DEPTH_STENCIL_ATTACHMENT_READ | ACCESS_SHADER_READ,
+            ),
+            Self::ShaderReadOnly => (
+                PIPELINE_STAGE_FRAGMENT_SHADER | PIPELINE_STAGE_COMPUTE_SHADER,
+                ACCESS_SHADER_READ,
+            ),
+            Self::TransferSrc => (PIPELINE_STAGE_TRANSFER, ACCESS_TRANSFER_READ),
+            Self::TransferDst => (PIPELINE_STAGE_TRANSFER, ACCESS_TRANSFER_WRITE),
+            //presentation is ordered by semaphores, this matches waiting on acquire at output
+            Self::PresentSrc => (PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT, 0),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct AttachmentDescription {
     pub format: Format,
@@ -4286,6 +6104,7 @@ pub struct RenderPassCreateInfo<'a> {
 pub struct RenderPass {
     device: Rc<Device>,
     handle: ffi::RenderPass,
+    color_attachment_counts: Vec<u32>,
 }
 
 impl RenderPass {
@@ -4413,6 +6232,11 @@ impl RenderPass {
             })
             .collect::<Vec<_>>();
 
+        let color_attachment_counts = color_attachments
+            .iter()
+            .map(|attachments| attachments.len() as u32)
+            .collect::<Vec<_>>();
+
         let dependencies = create_info
             .dependencies
             .iter()
@@ -4454,7 +6278,11 @@ impl RenderPass {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let render_pass = Self { device, handle };
+                let render_pass = Self {
+                    device,
+                    handle,
+                    color_attachment_counts,
+                };
 
                 Ok(render_pass)
             }
@@ -4463,6 +6291,11 @@ impl RenderPass {
             _ => panic!("unexpected result: {:?}", result),
         }
     }
+
+    /// Number of color attachments `subpass` writes, `None` if there is no such subpass.
+    pub fn color_attachment_count(&self, subpass: u32) -> Option<u32> {
+        self.color_attachment_counts.get(subpass as usize).copied()
+    }
 }
 
 impl Drop for RenderPass {
@@ -4472,13 +6305,17 @@ impl Drop for RenderPass {
 }
 
 pub struct ComputePipelineCreateInfo<'a> {
+    pub flags: u32,
     pub stage: PipelineShaderStageCreateInfo<'a>,
     pub layout: &'a PipelineLayout,
-    pub base_pipeline: Option<Pipeline>,
+    pub base_pipeline: Option<&'a Pipeline>,
     pub base_pipeline_index: i32,
 }
 
 pub struct GraphicsPipelineCreateInfo<'a> {
+    /// `PIPELINE_CREATE_DERIVATIVE` is added when there is a base pipeline, the base itself
+    /// needs `PIPELINE_CREATE_ALLOW_DERIVATIVES`.
+    pub flags: u32,
     pub stages: &'a [PipelineShaderStageCreateInfo<'a>],
     pub vertex_input_state: &'a PipelineVertexInputStateCreateInfo<'a>,
     pub input_assembly_state: &'a PipelineInputAssemblyStateCreateInfo,
@@ -4490,12 +6327,56 @@ pub struct GraphicsPipelineCreateInfo<'a> {
     pub color_blend_state: &'a PipelineColorBlendStateCreateInfo<'a>,
     pub dynamic_state: &'a PipelineDynamicStateCreateInfo<'a>,
     pub layout: &'a PipelineLayout,
-    pub render_pass: &'a RenderPass,
+    /// `None` when the pipeline is used with `Commands::begin_rendering`, in which case
+    /// `rendering` describes the attachments instead.
+    pub render_pass: Option<&'a RenderPass>,
+    pub rendering: Option<PipelineRenderingCreateInfo<'a>>,
     pub subpass: u32,
-    pub base_pipeline: Option<Pipeline>,
+    /// Either an existing pipeline or the index of an earlier create info in the same call
+    /// to derive from, `base_pipeline_index` is -1 when unused.
+    pub base_pipeline: Option<&'a Pipeline>,
     pub base_pipeline_index: i32,
 }
 
+//takes (flags, has base pipeline, base pipeline index) per create info and adds the derivative
+//flag where needed. a base index has to point at an earlier create info that allows derivatives
+fn derivative_flags(create_infos: &'_ [(u32, bool, i32)]) -> Result<Vec<u32>, Error> {
+    let mut flags = Vec::with_capacity(create_infos.len());
+
+    for (i, &(create_flags, has_base_pipeline, base_pipeline_index)) in
+        create_infos.iter().enumerate()
+    {
+        if base_pipeline_index < 0 {
+            let derivative = if has_base_pipeline {
+                PIPELINE_CREATE_DERIVATIVE
+            } else {
+                0
+            };
+
+            flags.push(create_flags | derivative);
+
+            continue;
+        }
+
+        if has_base_pipeline || base_pipeline_index as usize >= i {
+            return Err(Error::InvalidBasePipeline);
+        }
+
+        if create_infos[base_pipeline_index as usize].0 & PIPELINE_CREATE_ALLOW_DERIVATIVES == 0 {
+            return Err(Error::InvalidBasePipeline);
+        }
+
+        flags.push(create_flags | PIPELINE_CREATE_DERIVATIVE);
+    }
+
+    Ok(flags)
+}
+
+pub struct PipelineRenderingCreateInfo<'a> {
+    pub color_attachment_formats: &'a [Format],
+    pub depth_attachment_format: Option<Format>,
+}
+
 pub struct PipelineCache {
     handle: ffi::PipelineCache,
 }
@@ -4511,6 +6392,47 @@ impl Pipeline {
         cache: Option<PipelineCache>,
         create_infos: &'_ [GraphicsPipelineCreateInfo],
     ) -> Result<Vec<Self>, Error> {
+        let flags = derivative_flags(
+            &create_infos
+                .iter()
+                .map(|create_info| {
+                    (
+                        create_info.flags,
+                        create_info.base_pipeline.is_some(),
+                        create_info.base_pipeline_index,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )?;
+
+        //every color attachment the pipeline renders to needs its own blend state
+        for create_info in create_infos {
+            let color_attachment_count = match (create_info.render_pass, &create_info.rendering) {
+                (Some(render_pass), _) => render_pass.color_attachment_count(create_info.subpass),
+                (None, Some(rendering)) => Some(rendering.color_attachment_formats.len() as u32),
+                (None, None) => None,
+            };
+
+            if color_attachment_count != Some(create_info.color_blend_state.attachments.len() as _)
+            {
+                return Err(Error::AttachmentCountMismatch);
+            }
+
+            if create_info.color_blend_state.logic_op_enable && !device.enabled_features.logic_op {
+                return Err(Error::FeatureNotPresent);
+            }
+
+            //the vertex input and input assembly states are ignored for mesh pipelines
+            let mesh_stages = create_info
+                .stages
+                .iter()
+                .any(|stage| stage.stage & (SHADER_STAGE_TASK | SHADER_STAGE_MESH) != 0);
+
+            if mesh_stages && device.draw_mesh_tasks.is_none() {
+                return Err(Error::ExtensionNotPresent);
+            }
+        }
+
         let entry_points = create_infos
             .iter()
             .map(|create_info| {
@@ -4681,7 +6603,7 @@ impl Pipeline {
                 rasterizer_discard_enable: create_info.rasterization_state.rasterizer_discard_enable
                     as _,
                 polygon_mode: create_info.rasterization_state.polygon_mode.into(),
-                cull_mode: create_info.rasterization_state.cull_mode,
+                cull_mode: create_info.rasterization_state.cull_mode.into(),
                 front_face: create_info.rasterization_state.front_face.into(),
                 depth_bias_enable: create_info.rasterization_state.depth_bias_enable as _,
                 depth_bias_constant_factor: create_info
@@ -4831,14 +6753,50 @@ impl Pipeline {
             })
             .collect::<Vec<_>>();
 
-        let create_infos = create_infos
+        let rendering_color_formats = create_infos
+            .iter()
+            .map(|create_info| {
+                create_info.rendering.as_ref().map_or(vec![], |rendering| {
+                    rendering
+                        .color_attachment_formats
+                        .iter()
+                        .map(|&format| format.into())
+                        .collect::<Vec<ffi::Format>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let rendering_create_infos = create_infos
             .iter()
             .enumerate()
-            .map(|(i, create_info)| ffi::GraphicsPipelineCreateInfo {
-                structure_type: ffi::StructureType::GraphicsPipelineCreateInfo,
-                p_next: ptr::null(),
-                flags: 0,
-                stage_count: stages[i].len() as _,
+            .map(|(i, create_info)| {
+                create_info
+                    .rendering
+                    .as_ref()
+                    .map(|rendering| ffi::PipelineRenderingCreateInfo {
+                        structure_type: ffi::StructureType::PipelineRenderingCreateInfo,
+                        p_next: ptr::null(),
+                        view_mask: 0,
+                        color_attachment_count: rendering_color_formats[i].len() as _,
+                        color_attachment_formats: rendering_color_formats[i].as_ptr(),
+                        depth_attachment_format: rendering
+                            .depth_attachment_format
+                            .map_or(ffi::Format::Undefined, Into::into),
+                        stencil_attachment_format: ffi::Format::Undefined,
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let create_infos = create_infos
+            .iter()
+            .enumerate()
+            .map(|(i, create_info)| ffi::GraphicsPipelineCreateInfo {
+                structure_type: ffi::StructureType::GraphicsPipelineCreateInfo,
+                p_next: rendering_create_infos[i]
+                    .as_ref()
+                    .map_or(ptr::null(), |rendering| rendering as *const _ as _),
+                flags: flags[i] as _,
+                stage_count: stages[i].len() as _,
                 stages: stages[i].as_ptr(),
                 vertex_input_state: &vertex_input_states[i],
                 input_assembly_state: &input_assembly_states[i],
@@ -4850,11 +6808,12 @@ impl Pipeline {
                 color_blend_state: &color_blend_states[i],
                 dynamic_state: &dynamic_states[i],
                 layout: create_info.layout.handle,
-                render_pass: create_info.render_pass.handle,
+                render_pass: create_info
+                    .render_pass
+                    .map_or(ffi::RenderPass::null(), |render_pass| render_pass.handle),
                 subpass: create_info.subpass as _,
                 base_pipeline_handle: create_info
                     .base_pipeline
-                    .as_ref()
                     .map_or(ffi::Pipeline::null(), |pipeline| pipeline.handle),
                 base_pipeline_index: create_info.base_pipeline_index,
             })
@@ -4899,6 +6858,19 @@ impl Pipeline {
         cache: Option<PipelineCache>,
         create_infos: &'_ [ComputePipelineCreateInfo],
     ) -> Result<Vec<Self>, Error> {
+        let flags = derivative_flags(
+            &create_infos
+                .iter()
+                .map(|create_info| {
+                    (
+                        create_info.flags,
+                        create_info.base_pipeline.is_some(),
+                        create_info.base_pipeline_index,
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )?;
+
         let entry_points = create_infos
             .iter()
             .map(|create_info| CString::new(create_info.stage.entry_point).unwrap())
@@ -4924,12 +6896,11 @@ impl Pipeline {
             .map(|(i, create_info)| ffi::ComputePipelineCreateInfo {
                 structure_type: ffi::StructureType::ComputePipelineCreateInfo,
                 p_next: ptr::null(),
-                flags: 0,
+                flags: flags[i] as _,
                 stage: stages[i],
                 layout: create_info.layout.handle,
                 base_pipeline_handle: create_info
                     .base_pipeline
-                    .as_ref()
                     .map_or(ffi::Pipeline::null(), |pipeline| pipeline.handle),
                 base_pipeline_index: create_info.base_pipeline_index,
             })
@@ -4990,7 +6961,18 @@ pub struct Framebuffer {
 }
 
 impl Framebuffer {
+    /// Every attachment has to be a view of a single mip level with at least `layers` layers.
     pub fn new(device: Rc<Device>, create_info: FramebufferCreateInfo) -> Result<Self, Error> {
+        for image_view in create_info.attachments {
+            if image_view.level_count != 1 {
+                return Err(Error::InvalidMipRange);
+            }
+
+            if image_view.layer_count < create_info.layers {
+                return Err(Error::InvalidLayerRange);
+            }
+        }
+
         let attachments = create_info
             .attachments
             .iter()
@@ -5083,6 +7065,12 @@ impl CommandPool {
             _ => panic!("unexpected result: {:?}", result),
         }
     }
+
+    /// Hands memory left over from freed or reset command buffers back to the system. Needs
+    /// vulkan 1.1.
+    pub fn trim(&mut self) {
+        unsafe { ffi::vkTrimCommandPool(self.device.handle, self.handle, 0) };
+    }
 }
 
 impl Drop for CommandPool {
@@ -5147,11 +7135,45 @@ impl CommandBuffer {
         }
     }
 
+    /// Returns `command_buffers` to `command_pool` they were allocated from, otherwise they
+    /// live as long as the pool. None of them may still be executing.
+    pub fn free(command_pool: &CommandPool, command_buffers: Vec<Self>) {
+        let handles = command_buffers
+            .iter()
+            .map(|command_buffer| command_buffer.handle)
+            .collect::<Vec<_>>();
+
+        if handles.is_empty() {
+            return;
+        }
+
+        unsafe {
+            ffi::vkFreeCommandBuffers(
+                command_pool.device.handle,
+                command_pool.handle,
+                handles.len() as _,
+                handles.as_ptr(),
+            )
+        };
+    }
+
     pub fn record<'a>(&'a mut self, mut script: impl FnMut(Commands<'_>)) -> Result<(), Error> {
+        self.record_with_flags(0, |commands| {
+            script(commands);
+            Ok(())
+        })
+    }
+
+    //the buffer is ended even if the script fails, so it can be reset or freed afterwards
+    fn record_with_flags(
+        &mut self,
+        flags: u32,
+        script: impl FnOnce(Commands<'_>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
         let begin_info = ffi::CommandBufferBeginInfo {
             structure_type: ffi::StructureType::CommandBufferBeginInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags,
             inheritence_info: ptr::null(),
         };
 
@@ -5168,12 +7190,12 @@ impl CommandBuffer {
             command_buffer: self,
         };
 
-        script(commands);
+        let script_result = script(commands);
 
         let result = unsafe { ffi::vkEndCommandBuffer(self.handle) };
 
         match result {
-            ffi::Result::Success => Ok(()),
+            ffi::Result::Success => script_result,
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
             ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
             _ => panic!("unexpected result: {:?}", result),
@@ -5196,28 +7218,17 @@ pub struct Commands<'a> {
 }
 
 impl Commands<'_> {
-    pub fn begin_render_pass(&mut self, begin_info: RenderPassBeginInfo<'_>) {
-        let mut clear_values = vec![];
-
-        clear_values.extend(
-            begin_info
-                .color_clear_values
-                .into_iter()
-                .map(|&clear_value| ffi::ClearValue {
-                    color: ffi::ClearColorValue { rgba: clear_value },
-                }),
-        );
-
-        if let Some(clear_value) = begin_info.depth_stencil_clear_value {
-            let depth_stencil_clear_value = ffi::ClearValue {
-                depth_stencil: ffi::ClearDepthStencilValue {
-                    depth: clear_value.0,
-                    stencil: clear_value.1,
-                },
-            };
-
-            clear_values.push(depth_stencil_clear_value);
-        }
+    /// Starts `begin_info.render_pass`, draws are recorded through the returned
+    /// `RenderPassCommands` and the pass ends when it is dropped.
+    pub fn begin_render_pass(
+        &mut self,
+        begin_info: RenderPassBeginInfo<'_>,
+    ) -> RenderPassCommands<'_> {
+        let clear_values = begin_info
+            .clear_values
+            .iter()
+            .map(|&clear_value| clear_value.into())
+            .collect::<Vec<ffi::ClearValue>>();
 
         let begin_info = ffi::RenderPassBeginInfo {
             structure_type: ffi::StructureType::RenderPassBeginInfo,
@@ -5245,16 +7256,118 @@ impl Commands<'_> {
                 ffi::SubpassContents::Inline,
             )
         };
+
+        RenderPassCommands {
+            command_buffer: self.command_buffer,
+            dynamic: false,
+        }
+    }
+
+    /// Renders straight into image views without a render pass or framebuffer. Needs a device
+    /// created with `KHR_DYNAMIC_RENDERING` (or on vulkan 1.3), and pipelines created with
+    /// `rendering` set to matching formats. Like `begin_render_pass` rendering ends when the
+    /// returned `RenderPassCommands` is dropped. Fails with `ExtensionNotPresent` otherwise.
+    pub fn begin_rendering(
+        &mut self,
+        rendering_info: RenderingInfo<'_>,
+    ) -> Result<RenderPassCommands<'_>, Error> {
+        let (begin_rendering, _) = self
+            .command_buffer
+            .device
+            .dynamic_rendering
+            .ok_or(Error::ExtensionNotPresent)?;
+
+        let color_attachments = rendering_info
+            .color_attachments
+            .iter()
+            .map(Into::into)
+            .collect::<Vec<ffi::RenderingAttachmentInfo>>();
+
+        let depth_attachment = rendering_info
+            .depth_attachment
+            .as_ref()
+            .map(ffi::RenderingAttachmentInfo::from);
+
+        let rendering_info = ffi::RenderingInfo {
+            structure_type: ffi::StructureType::RenderingInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            render_area: ffi::Rect2d {
+                offset: [
+                    rendering_info.render_area.offset.0,
+                    rendering_info.render_area.offset.1,
+                ],
+                extent: [
+                    rendering_info.render_area.extent.0,
+                    rendering_info.render_area.extent.1,
+                ],
+            },
+            layer_count: 1,
+            view_mask: 0,
+            color_attachment_count: color_attachments.len() as _,
+            color_attachments: color_attachments.as_ptr(),
+            depth_attachment: depth_attachment
+                .as_ref()
+                .map_or(ptr::null(), |attachment| attachment as *const _),
+            stencil_attachment: ptr::null(),
+        };
+
+        unsafe { begin_rendering(self.command_buffer.handle, &rendering_info) };
+
+        Ok(RenderPassCommands {
+            command_buffer: self.command_buffer,
+            dynamic: true,
+        })
     }
 
-    pub fn end_render_pass(&mut self) {
-        unsafe { ffi::vkCmdEndRenderPass(self.command_buffer.handle) };
+    //labels only matter to debugging tools, so without debug utils they do nothing
+    pub fn begin_label(&mut self, name: &str, color: [f32; 4]) {
+        let f = match self.command_buffer.device.instance.debug_utils.cmd_begin_label {
+            Some(f) => f,
+            None => return,
+        };
+
+        let label_name = debug_name(name);
+
+        let label = ffi::DebugUtilsLabel {
+            structure_type: ffi::StructureType::DebugUtilsLabel,
+            p_next: ptr::null(),
+            label_name: label_name.as_ptr(),
+            color,
+        };
+
+        unsafe { f(self.command_buffer.handle, &label) };
     }
 
-    pub fn next_subpass(&mut self) {
-        unsafe { ffi::vkCmdNextSubpass(self.command_buffer.handle, ffi::SubpassContents::Inline) };
+    pub fn end_label(&mut self) {
+        let f = match self.command_buffer.device.instance.debug_utils.cmd_end_label {
+            Some(f) => f,
+            None => return,
+        };
+
+        unsafe { f(self.command_buffer.handle) };
+    }
+
+    pub fn insert_label(&mut self, name: &str, color: [f32; 4]) {
+        let f = match self.command_buffer.device.instance.debug_utils.cmd_insert_label {
+            Some(f) => f,
+            None => return,
+        };
+
+        let label_name = debug_name(name);
+
+        let label = ffi::DebugUtilsLabel {
+            structure_type: ffi::StructureType::DebugUtilsLabel,
+            p_next: ptr::null(),
+            label_name: label_name.as_ptr(),
+            color,
+        };
+
+        unsafe { f(self.command_buffer.handle, &label) };
     }
 
+    /// Graphics and compute bind points keep separate state, so binding a compute pipeline and
+    /// its descriptor sets between draws leaves the bound graphics pipeline untouched.
     pub fn bind_pipeline(&mut self, bind_point: PipelineBindPoint, pipeline: &Pipeline) {
         unsafe {
             ffi::vkCmdBindPipeline(
@@ -5292,79 +7405,60 @@ impl Commands<'_> {
         };
     }
 
-    pub fn bind_vertex_buffers(
-        &mut self,
-        first_binding: u32,
-        buffers: &'_ [&'_ Buffer],
-        offsets: &'_ [usize],
-    ) {
-        let buffers = buffers
-            .iter()
-            .map(|buffer| buffer.handle)
-            .collect::<Vec<_>>();
-        let offsets = offsets
+    pub fn set_viewport(&mut self, first_viewport: u32, viewports: &[Viewport]) {
+        let viewports = viewports
             .iter()
-            .map(|&offset| offset as _)
-            .collect::<Vec<_>>();
-
-        assert_eq!(buffers.len(), offsets.len());
+            .map(|viewport| viewport.into())
+            .collect::<Vec<ffi::Viewport>>();
 
         unsafe {
-            ffi::vkCmdBindVertexBuffers(
+            ffi::vkCmdSetViewport(
                 self.command_buffer.handle,
-                first_binding,
-                buffers.len() as _,
-                buffers.as_ptr(),
-                offsets.as_ptr(),
+                first_viewport,
+                viewports.len() as _,
+                viewports.as_ptr(),
             )
         };
     }
 
-    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize, index_type: IndexType) {
+    pub fn set_scissor(&mut self, first_scissor: u32, scissors: &[Rect2d]) {
+        let scissors = scissors
+            .iter()
+            .map(|scissor| scissor.into())
+            .collect::<Vec<ffi::Rect2d>>();
+
         unsafe {
-            ffi::vkCmdBindIndexBuffer(
+            ffi::vkCmdSetScissor(
                 self.command_buffer.handle,
-                buffer.handle,
-                offset as _,
-                index_type.into(),
+                first_scissor,
+                scissors.len() as _,
+                scissors.as_ptr(),
             )
         };
     }
 
-    pub fn draw(
-        &mut self,
-        vertex_count: u32,
-        instance_count: u32,
-        first_vertex: u32,
-        first_instance: u32,
-    ) {
-        unsafe {
-            ffi::vkCmdDraw(
-                self.command_buffer.handle,
-                vertex_count,
-                instance_count,
-                first_vertex,
-                first_instance,
-            )
-        };
+    pub fn set_line_width(&mut self, line_width: f32) {
+        debug_assert!(
+            line_width == 1.0 || self.command_buffer.device.enabled_features.wide_lines,
+            "line width other than 1.0 requires the wide lines feature"
+        );
+
+        unsafe { ffi::vkCmdSetLineWidth(self.command_buffer.handle, line_width) };
     }
 
-    pub fn draw_indexed(
-        &mut self,
-        index_count: u32,
-        instance_count: u32,
-        first_index: u32,
-        vertex_offset: i32,
-        first_instance: u32,
-    ) {
+    /// Only used by pipelines with `DynamicState::DepthBias` and `depth_bias_enable` set.
+    pub fn set_depth_bias(&mut self, constant_factor: f32, clamp: f32, slope_factor: f32) {
+        debug_assert!(
+            clamp == 0.0 || self.command_buffer.device.enabled_features.depth_bias_clamp,
+            "depth bias clamp other than 0.0 requires the depth bias clamp feature"
+        );
+
         unsafe {
-            ffi::vkCmdDrawIndexed(
+            ffi::vkCmdSetDepthBias(
                 self.command_buffer.handle,
-                index_count,
-                instance_count,
-                first_index,
-                vertex_offset,
-                first_instance,
+                constant_factor,
+                clamp,
+                slope_factor,
             )
         };
     }
@@ -5413,30 +7507,7 @@ impl Commands<'_> {
         dst_image_layout: ImageLayout,
         regions: &'_ [BufferImageCopy],
     ) {
-        let regions = regions
-            .iter()
-            .map(|copy| ffi::BufferImageCopy {
-                buffer_offset: copy.buffer_offset as _,
-                buffer_row_length: copy.buffer_row_length as _,
-                buffer_image_height: copy.buffer_image_height as _,
-                image_subresource: ffi::ImageSubresourceLayers {
-                    aspect_mask: copy.image_subresource.aspect_mask as _,
-                    mip_level: copy.image_subresource.mip_level as _,
-                    base_array_layer: copy.image_subresource.base_array_layer as _,
-                    layer_count: copy.image_subresource.layer_count as _,
-                },
-                image_offset: [
-                    copy.image_offset.0 as _,
-                    copy.image_offset.1 as _,
-                    copy.image_offset.2 as _,
-                ],
-                image_extent: [
-                    copy.image_extent.0 as _,
-                    copy.image_extent.1 as _,
-                    copy.image_extent.2 as _,
-                ],
-            })
-            .collect::<Vec<_>>();
+        let regions = regions.iter().map(Into::into).collect::<Vec<_>>();
 
         unsafe {
             ffi::vkCmdCopyBufferToImage(
@@ -5450,6 +7521,29 @@ impl Commands<'_> {
         };
     }
 
+    /// Copies image texels into `dst_buffer` for readback. `src_image` must be in
+    /// `TransferSrc` (or `General`) layout and created with `IMAGE_USAGE_TRANSFER_SRC`.
+    pub fn copy_image_to_buffer(
+        &mut self,
+        src_image: &Image,
+        src_image_layout: ImageLayout,
+        dst_buffer: &mut Buffer,
+        regions: &'_ [BufferImageCopy],
+    ) {
+        let regions = regions.iter().map(Into::into).collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdCopyImageToBuffer(
+                self.command_buffer.handle,
+                src_image.handle,
+                src_image_layout.into(),
+                dst_buffer.handle,
+                regions.len() as _,
+                regions.as_ptr(),
+            )
+        };
+    }
+
     pub fn pipeline_barrier(
         &mut self,
         src_stage_mask: u32,
@@ -5459,71 +7553,18 @@ impl Commands<'_> {
         buffer_memory_barriers: &'_ [BufferMemoryBarrier],
         image_memory_barriers: &'_ [ImageMemoryBarrier],
     ) {
-        let memory_barriers = memory_barriers
-            .iter()
-            .map(|barrier| ffi::MemoryBarrier {
-                structure_type: ffi::StructureType::MemoryBarrier,
-                p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
-            })
-            .collect::<Vec<_>>();
-
-        let memory_barriers_ptr = if memory_barriers.len() > 0 {
-            memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
+        let memory_barriers = memory_barriers.iter().map(Into::into).collect::<Vec<_>>();
 
         let buffer_memory_barriers = buffer_memory_barriers
             .iter()
-            .map(|barrier| ffi::BufferMemoryBarrier {
-                structure_type: ffi::StructureType::BufferMemoryBarrier,
-                p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
-                src_queue_family_index: barrier.src_queue_family_index as _,
-                dst_queue_family_index: barrier.dst_queue_family_index as _,
-                buffer: barrier.buffer.handle,
-                offset: barrier.offset as _,
-                size: barrier.size as _,
-            })
+            .map(Into::into)
             .collect::<Vec<_>>();
 
-        let buffer_memory_barriers_ptr = if buffer_memory_barriers.len() > 0 {
-            buffer_memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
-
         let image_memory_barriers = image_memory_barriers
             .iter()
-            .map(|barrier| ffi::ImageMemoryBarrier {
-                structure_type: ffi::StructureType::ImageMemoryBarrier,
-                p_next: ptr::null(),
-                src_access_mask: barrier.src_access_mask as _,
-                dst_access_mask: barrier.dst_access_mask as _,
-                old_layout: barrier.old_layout.into(),
-                new_layout: barrier.new_layout.into(),
-                src_queue_family_index: barrier.src_queue_family_index as _,
-                dst_queue_family_index: barrier.dst_queue_family_index as _,
-                image: barrier.image.handle,
-                subresource_range: ffi::ImageSubresourceRange {
-                    aspect_mask: barrier.subresource_range.aspect_mask,
-                    base_mip_level: barrier.subresource_range.base_mip_level,
-                    level_count: barrier.subresource_range.level_count,
-                    base_array_layer: barrier.subresource_range.base_array_layer,
-                    layer_count: barrier.subresource_range.layer_count,
-                },
-            })
+            .map(Into::into)
             .collect::<Vec<_>>();
 
-        let image_memory_barriers_ptr = if image_memory_barriers.len() > 0 {
-            image_memory_barriers.as_ptr()
-        } else {
-            ptr::null()
-        };
-
         unsafe {
             ffi::vkCmdPipelineBarrier(
                 self.command_buffer.handle,
@@ -5531,72 +7572,707 @@ impl Commands<'_> {
                 dst_stage_mask as _,
                 dependency_flags as _,
                 memory_barriers.len() as _,
-                memory_barriers_ptr,
+                slice_ptr(&memory_barriers),
                 buffer_memory_barriers.len() as _,
-                buffer_memory_barriers_ptr,
+                slice_ptr(&buffer_memory_barriers),
                 image_memory_barriers.len() as _,
-                image_memory_barriers_ptr,
+                slice_ptr(&image_memory_barriers),
             )
         };
     }
-}
-
-pub struct RenderPassBeginInfo<'a> {
-    pub render_pass: &'a RenderPass,
-    pub framebuffer: &'a Framebuffer,
-    pub render_area: Rect2d,
-    pub color_clear_values: &'a [[f32; 4]],
-    pub depth_stencil_clear_value: Option<(f32, u32)>,
-}
-
-pub struct SemaphoreCreateInfo {}
 
-pub struct Semaphore {
-    device: Rc<Device>,
-    handle: ffi::Semaphore,
-}
+    pub fn reset_query_pool(&mut self, query_pool: &QueryPool, first_query: u32, query_count: u32) {
+        unsafe {
+            ffi::vkCmdResetQueryPool(
+                self.command_buffer.handle,
+                query_pool.handle,
+                first_query,
+                query_count,
+            )
+        };
+    }
 
-impl Semaphore {
-    pub fn new(device: Rc<Device>, create_info: SemaphoreCreateInfo) -> Result<Self, Error> {
-        let create_info = ffi::SemaphoreCreateInfo {
-            structure_type: ffi::StructureType::SemaphoreCreateInfo,
-            p_next: ptr::null(),
-            flags: 0,
+    /// Writes the time once every command before it has finished `stage`.
+    pub fn write_timestamp(&mut self, stage: u32, query_pool: &QueryPool, query: u32) {
+        unsafe {
+            ffi::vkCmdWriteTimestamp(
+                self.command_buffer.handle,
+                stage as _,
+                query_pool.handle,
+                query,
+            )
         };
+    }
 
-        let mut handle = MaybeUninit::<ffi::Semaphore>::uninit();
+    /// Copies results of `query_count` queries into `dst_buffer` on the gpu, `stride` bytes
+    /// apart starting at `dst_offset`, so reading them back doesn't stall the host. `flags` are
+    /// `QUERY_RESULT_*`, with `QUERY_RESULT_64` `stride` and `dst_offset` are multiples of 8,
+    /// otherwise of 4. Like a transfer write the copy needs a barrier before the buffer is read.
+    #[allow(clippy::too_many_arguments)]
+    pub fn copy_query_pool_results(
+        &mut self,
+        query_pool: &QueryPool,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &mut Buffer,
+        dst_offset: u64,
+        stride: u64,
+        flags: u32,
+    ) -> Result<(), Error> {
+        if first_query.saturating_add(query_count) > query_pool.query_count {
+            return Err(Error::OutOfBounds);
+        }
 
-        let result = unsafe {
-            ffi::vkCreateSemaphore(
-                device.handle,
-                &create_info,
-                ptr::null(),
-                handle.as_mut_ptr(),
+        unsafe {
+            ffi::vkCmdCopyQueryPoolResults(
+                self.command_buffer.handle,
+                query_pool.handle,
+                first_query,
+                query_count,
+                dst_buffer.handle,
+                dst_offset,
+                stride,
+                flags as _,
             )
         };
 
-        match result {
-            ffi::Result::Success => {
-                let handle = unsafe { handle.assume_init() };
-
-                let semaphore = Self { device, handle };
+        Ok(())
+    }
 
-                Ok(semaphore)
-            }
-            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
-            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
-            _ => panic!("unexpected result: {:?}", result),
-        }
+    /// Signals `event` once every command before it has finished `stage_mask`.
+    pub fn set_event(&mut self, event: &Event, stage_mask: u32) {
+        unsafe { ffi::vkCmdSetEvent(self.command_buffer.handle, event.handle, stage_mask as _) };
     }
-}
 
-impl Drop for Semaphore {
-    fn drop(&mut self) {
-        unsafe { ffi::vkDestroySemaphore(self.device.handle, self.handle, ptr::null()) };
+    pub fn reset_event(&mut self, event: &Event, stage_mask: u32) {
+        unsafe { ffi::vkCmdResetEvent(self.command_buffer.handle, event.handle, stage_mask as _) };
     }
-}
 
-pub struct FenceCreateInfo {}
+    /// The second half of a split barrier, `src_stage_mask` has to include the stages the
+    /// events were set with.
+    pub fn wait_events(
+        &mut self,
+        events: &'_ [&'_ Event],
+        src_stage_mask: u32,
+        dst_stage_mask: u32,
+        memory_barriers: &'_ [MemoryBarrier],
+        buffer_memory_barriers: &'_ [BufferMemoryBarrier],
+        image_memory_barriers: &'_ [ImageMemoryBarrier],
+    ) {
+        let events = events.iter().map(|event| event.handle).collect::<Vec<_>>();
+
+        let memory_barriers = memory_barriers.iter().map(Into::into).collect::<Vec<_>>();
+
+        let buffer_memory_barriers = buffer_memory_barriers
+            .iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+
+        let image_memory_barriers = image_memory_barriers
+            .iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+
+        unsafe {
+            ffi::vkCmdWaitEvents(
+                self.command_buffer.handle,
+                events.len() as _,
+                events.as_ptr(),
+                src_stage_mask as _,
+                dst_stage_mask as _,
+                memory_barriers.len() as _,
+                slice_ptr(&memory_barriers),
+                buffer_memory_barriers.len() as _,
+                slice_ptr(&buffer_memory_barriers),
+                image_memory_barriers.len() as _,
+                slice_ptr(&image_memory_barriers),
+            )
+        };
+    }
+
+    /// Transitions `image` between layouts, inferring stage and access masks from how images in
+    /// `old_layout` and `new_layout` are usually used. Use `transition_image_with_masks` when the
+    /// image is used somewhere unusual, e.g. sampled from a vertex shader.
+    pub fn transition_image(
+        &mut self,
+        image: &Image,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+        subresource_range: ImageSubresourceRange,
+    ) {
+        self.transition_image_with_masks(
+            image,
+            old_layout,
+            new_layout,
+            subresource_range,
+            old_layout.usage(),
+            new_layout.usage(),
+        );
+    }
+
+    /// Same as `transition_image`, with `src` and `dst` given as `(stage mask, access mask)`.
+    pub fn transition_image_with_masks(
+        &mut self,
+        image: &Image,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+        subresource_range: ImageSubresourceRange,
+        src: (u32, u32),
+        dst: (u32, u32),
+    ) {
+        let barrier = ImageMemoryBarrier {
+            src_access_mask: src.1,
+            dst_access_mask: dst.1,
+            old_layout,
+            new_layout,
+            src_queue_family_index: QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: QUEUE_FAMILY_IGNORED,
+            image,
+            subresource_range,
+        };
+
+        self.pipeline_barrier(src.0, dst.0, 0, &[], &[], &[barrier]);
+    }
+}
+
+/// The layout `draw_indexed_indirect_count` reads from its buffer.
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+pub struct DrawIndexedIndirectCommand {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    pub first_instance: u32,
+}
+
+pub struct RenderPassBeginInfo<'a> {
+    pub render_pass: &'a RenderPass,
+    pub framebuffer: &'a Framebuffer,
+    pub render_area: Rect2d,
+    /// One per attachment, in attachment order.
+    pub clear_values: &'a [ClearValue],
+}
+
+#[derive(Clone, Copy)]
+pub enum ClearValue {
+    Color([f32; 4]),
+    DepthStencil { depth: f32, stencil: u32 },
+}
+
+/// Commands recorded inside a render pass or dynamic rendering. It borrows the `Commands` it
+/// was started from, so dispatches, copies and barriers can't be recorded until it is dropped,
+/// which ends the pass.
+pub struct RenderPassCommands<'a> {
+    command_buffer: &'a mut CommandBuffer,
+    //started by begin_rendering instead of begin_render_pass
+    dynamic: bool,
+}
+
+impl RenderPassCommands<'_> {
+    //state commands are valid both inside and outside a pass
+    fn commands(&mut self) -> Commands<'_> {
+        Commands {
+            command_buffer: self.command_buffer,
+        }
+    }
+
+    pub fn end(self) {}
+
+    pub fn next_subpass(&mut self) {
+        debug_assert!(!self.dynamic, "dynamic rendering has no subpasses");
+
+        unsafe { ffi::vkCmdNextSubpass(self.command_buffer.handle, ffi::SubpassContents::Inline) };
+    }
+
+    pub fn begin_label(&mut self, name: &str, color: [f32; 4]) {
+        self.commands().begin_label(name, color);
+    }
+
+    pub fn end_label(&mut self) {
+        self.commands().end_label();
+    }
+
+    pub fn insert_label(&mut self, name: &str, color: [f32; 4]) {
+        self.commands().insert_label(name, color);
+    }
+
+    pub fn bind_pipeline(&mut self, bind_point: PipelineBindPoint, pipeline: &Pipeline) {
+        self.commands().bind_pipeline(bind_point, pipeline);
+    }
+
+    pub fn bind_descriptor_sets(
+        &mut self,
+        bind_point: PipelineBindPoint,
+        layout: &'_ PipelineLayout,
+        first_set: u32,
+        descriptor_sets: &'_ [&'_ DescriptorSet],
+        dynamic_offsets: &'_ [u32],
+    ) {
+        self.commands()
+            .bind_descriptor_sets(bind_point, layout, first_set, descriptor_sets, dynamic_offsets);
+    }
+
+    pub fn set_viewport(&mut self, first_viewport: u32, viewports: &[Viewport]) {
+        self.commands().set_viewport(first_viewport, viewports);
+    }
+
+    pub fn set_scissor(&mut self, first_scissor: u32, scissors: &[Rect2d]) {
+        self.commands().set_scissor(first_scissor, scissors);
+    }
+
+    pub fn set_line_width(&mut self, line_width: f32) {
+        self.commands().set_line_width(line_width);
+    }
+
+    pub fn set_depth_bias(&mut self, constant_factor: f32, clamp: f32, slope_factor: f32) {
+        self.commands().set_depth_bias(constant_factor, clamp, slope_factor);
+    }
+
+    pub fn bind_vertex_buffers(
+        &mut self,
+        first_binding: u32,
+        buffers: &'_ [&'_ Buffer],
+        offsets: &'_ [usize],
+    ) {
+        let buffers = buffers
+            .iter()
+            .map(|buffer| buffer.handle)
+            .collect::<Vec<_>>();
+        let offsets = offsets
+            .iter()
+            .map(|&offset| offset as _)
+            .collect::<Vec<_>>();
+
+        assert_eq!(buffers.len(), offsets.len());
+
+        unsafe {
+            ffi::vkCmdBindVertexBuffers(
+                self.command_buffer.handle,
+                first_binding,
+                buffers.len() as _,
+                buffers.as_ptr(),
+                offsets.as_ptr(),
+            )
+        };
+    }
+
+    pub fn bind_index_buffer(&mut self, buffer: &'_ Buffer, offset: usize, index_type: IndexType) {
+        unsafe {
+            ffi::vkCmdBindIndexBuffer(
+                self.command_buffer.handle,
+                buffer.handle,
+                offset as _,
+                index_type.into(),
+            )
+        };
+    }
+
+    pub fn draw(
+        &mut self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            ffi::vkCmdDraw(
+                self.command_buffer.handle,
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            )
+        };
+    }
+
+    pub fn draw_indexed(
+        &mut self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            ffi::vkCmdDrawIndexed(
+                self.command_buffer.handle,
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            )
+        };
+    }
+
+    /// Reads up to `max_draw_count` `DrawIndexedIndirectCommand`s `stride` bytes apart from
+    /// `buffer`, with the actual count read as a u32 from `count_buffer`. Both buffers need
+    /// `BufferUsageFlags::INDIRECT`. Fails with `ExtensionNotPresent` unless the device was
+    /// created with `KHR_DRAW_INDIRECT_COUNT`.
+    pub fn draw_indexed_indirect_count(
+        &mut self,
+        buffer: &Buffer,
+        offset: u64,
+        count_buffer: &Buffer,
+        count_offset: u64,
+        max_draw_count: u32,
+        stride: u32,
+    ) -> Result<(), Error> {
+        let draw_indexed_indirect_count = self
+            .command_buffer
+            .device
+            .draw_indirect_count
+            .ok_or(Error::ExtensionNotPresent)?;
+
+        unsafe {
+            draw_indexed_indirect_count(
+                self.command_buffer.handle,
+                buffer.handle,
+                offset,
+                count_buffer.handle,
+                count_offset,
+                max_draw_count,
+                stride,
+            )
+        };
+
+        Ok(())
+    }
+
+    /// Launches task shader workgroups, or mesh shader workgroups if the pipeline has no task
+    /// stage. Fails with `ExtensionNotPresent` unless the device was created with
+    /// `EXT_MESH_SHADER`.
+    pub fn draw_mesh_tasks(
+        &mut self,
+        group_count_x: u32,
+        group_count_y: u32,
+        group_count_z: u32,
+    ) -> Result<(), Error> {
+        let draw_mesh_tasks = self
+            .command_buffer
+            .device
+            .draw_mesh_tasks
+            .ok_or(Error::ExtensionNotPresent)?;
+
+        unsafe {
+            draw_mesh_tasks(
+                self.command_buffer.handle,
+                group_count_x,
+                group_count_y,
+                group_count_z,
+            )
+        };
+
+        Ok(())
+    }
+}
+
+impl Drop for RenderPassCommands<'_> {
+    fn drop(&mut self) {
+        //dynamic is only set by begin_rendering, which checked for the entry points
+        if self.dynamic {
+            if let Some((_, end_rendering)) = self.command_buffer.device.dynamic_rendering {
+                unsafe { end_rendering(self.command_buffer.handle) };
+            }
+        } else {
+            unsafe { ffi::vkCmdEndRenderPass(self.command_buffer.handle) };
+        }
+    }
+}
+
+impl From<ClearValue> for ffi::ClearValue {
+    fn from(clear_value: ClearValue) -> Self {
+        match clear_value {
+            ClearValue::Color(rgba) => Self {
+                color: ffi::ClearColorValue { rgba },
+            },
+            ClearValue::DepthStencil { depth, stencil } => Self {
+                depth_stencil: ffi::ClearDepthStencilValue { depth, stencil },
+            },
+        }
+    }
+}
+
+pub struct RenderingAttachmentInfo<'a> {
+    pub image_view: &'a ImageView,
+    pub image_layout: ImageLayout,
+    pub load_op: AttachmentLoadOp,
+    pub store_op: AttachmentStoreOp,
+    pub clear_value: ClearValue,
+}
+
+impl From<&RenderingAttachmentInfo<'_>> for ffi::RenderingAttachmentInfo {
+    fn from(attachment: &RenderingAttachmentInfo<'_>) -> Self {
+        Self {
+            structure_type: ffi::StructureType::RenderingAttachmentInfo,
+            p_next: ptr::null(),
+            image_view: attachment.image_view.handle,
+            image_layout: attachment.image_layout.into(),
+            resolve_mode: 0,
+            resolve_image_view: ffi::ImageView::null(),
+            resolve_image_layout: ffi::ImageLayout::Undefined,
+            load_op: attachment.load_op.into(),
+            store_op: attachment.store_op.into(),
+            clear_value: attachment.clear_value.into(),
+        }
+    }
+}
+
+pub struct RenderingInfo<'a> {
+    pub render_area: Rect2d,
+    pub color_attachments: &'a [RenderingAttachmentInfo<'a>],
+    pub depth_attachment: Option<RenderingAttachmentInfo<'a>>,
+}
+
+pub struct SemaphoreCreateInfo {}
+
+pub struct Semaphore {
+    device: Rc<Device>,
+    handle: ffi::Semaphore,
+}
+
+impl Semaphore {
+    pub fn new(device: Rc<Device>, create_info: SemaphoreCreateInfo) -> Result<Self, Error> {
+        let create_info = ffi::SemaphoreCreateInfo {
+            structure_type: ffi::StructureType::SemaphoreCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Semaphore>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateSemaphore(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let semaphore = Self { device, handle };
+
+                Ok(semaphore)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    /// A timeline semaphore counting up from `initial_value`, signaled and waited on with
+    /// `Queue::submit_timeline`. Fails with `FeatureNotPresent` unless the device was created
+    /// with `KHR_TIMELINE_SEMAPHORE`.
+    pub fn new_timeline(device: Rc<Device>, initial_value: u64) -> Result<Self, Error> {
+        if !device.timeline_semaphore {
+            return Err(Error::FeatureNotPresent);
+        }
+        let type_create_info = ffi::SemaphoreTypeCreateInfo {
+            structure_type: ffi::StructureType::SemaphoreTypeCreateInfo,
+            p_next: ptr::null(),
+            semaphore_type: 1,
+            initial_value,
+        };
+
+        let create_info = ffi::SemaphoreCreateInfo {
+            structure_type: ffi::StructureType::SemaphoreCreateInfo,
+            p_next: &type_create_info as *const _ as _,
+            flags: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Semaphore>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateSemaphore(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let semaphore = Self { device, handle };
+
+                Ok(semaphore)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
+
+impl Drop for Semaphore {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroySemaphore(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+pub struct EventCreateInfo {}
+
+/// Starts out unsignaled, can be set and reset from both the host and command buffers.
+pub struct Event {
+    device: Rc<Device>,
+    handle: ffi::Event,
+}
+
+impl Event {
+    pub fn new(device: Rc<Device>, create_info: EventCreateInfo) -> Result<Self, Error> {
+        let create_info = ffi::EventCreateInfo {
+            structure_type: ffi::StructureType::EventCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::Event>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateEvent(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let event = Self { device, handle };
+
+                Ok(event)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    /// Whether the event is currently set.
+    pub fn status(&self) -> Result<bool, Error> {
+        let result = unsafe { ffi::vkGetEventStatus(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::EventSet => Ok(true),
+            ffi::Result::EventReset => Ok(false),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            ffi::Result::DeviceLost => Err(Error::DeviceLost),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    pub fn set(&mut self) -> Result<(), Error> {
+        let result = unsafe { ffi::vkSetEvent(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    pub fn reset(&mut self) -> Result<(), Error> {
+        let result = unsafe { ffi::vkResetEvent(self.device.handle, self.handle) };
+
+        match result {
+            ffi::Result::Success => Ok(()),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroyEvent(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum QueryType {
+    /// Ticks of `PhysicalDeviceLimits::timestamp_period` nanoseconds, only the low
+    /// `timestamp_valid_bits` of the queue family are meaningful.
+    Timestamp,
+}
+
+pub struct QueryPoolCreateInfo {
+    pub query_type: QueryType,
+    pub query_count: u32,
+}
+
+/// Queries have to be reset with `Commands::reset_query_pool` before their first use.
+pub struct QueryPool {
+    device: Rc<Device>,
+    handle: ffi::QueryPool,
+    query_count: u32,
+}
+
+impl QueryPool {
+    pub fn new(device: Rc<Device>, create_info: QueryPoolCreateInfo) -> Result<Self, Error> {
+        let query_count = create_info.query_count;
+
+        let create_info = ffi::QueryPoolCreateInfo {
+            structure_type: ffi::StructureType::QueryPoolCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            query_type: create_info.query_type.into(),
+            query_count,
+            pipeline_statistics: 0,
+        };
+
+        let mut handle = MaybeUninit::<ffi::QueryPool>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateQueryPool(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let query_pool = Self {
+                    device,
+                    handle,
+                    query_count,
+                };
+
+                Ok(query_pool)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    pub fn query_count(&self) -> u32 {
+        self.query_count
+    }
+}
+
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroyQueryPool(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+pub struct FenceCreateInfo {}
 
 pub struct Fence {
     device: Rc<Device>,
@@ -5698,19 +8374,61 @@ impl Fence {
             _ => panic!("unexpected result: {:?}", result),
         }
     }
-}
+}
+
+impl Drop for Fence {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroyFence(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+pub struct SubmitInfo<'a> {
+    pub wait_semaphores: &'a [&'a Semaphore],
+    /// One stage mask per wait semaphore.
+    pub wait_stages: &'a [u32],
+    pub signal_semaphores: &'a [&'a mut Semaphore],
+    pub command_buffers: &'a [&'a CommandBuffer],
+}
+
+pub struct TimelineSemaphoreSubmitInfo<'a> {
+    pub wait_values: &'a [u64],
+    pub signal_values: &'a [u64],
+}
+
+//vulkan reads one stage mask per wait semaphore, and one value per semaphore when there are
+//timeline infos
+fn check_submit_counts(
+    submit_infos: &[SubmitInfo],
+    timeline_infos: Option<&[TimelineSemaphoreSubmitInfo]>,
+) -> Result<(), Error> {
+    if submit_infos
+        .iter()
+        .any(|submit_info| submit_info.wait_stages.len() != submit_info.wait_semaphores.len())
+    {
+        return Err(Error::WaitStageCountMismatch);
+    }
+
+    let Some(timeline_infos) = timeline_infos else {
+        return Ok(());
+    };
+
+    if submit_infos.len() != timeline_infos.len() {
+        return Err(Error::SemaphoreValueCountMismatch);
+    }
 
-impl Drop for Fence {
-    fn drop(&mut self) {
-        unsafe { ffi::vkDestroyFence(self.device.handle, self.handle, ptr::null()) };
+    let counts_match = submit_infos
+        .iter()
+        .zip(timeline_infos)
+        .all(|(submit, timeline)| {
+            submit.wait_semaphores.len() == timeline.wait_values.len()
+                && submit.signal_semaphores.len() == timeline.signal_values.len()
+        });
+
+    if !counts_match {
+        return Err(Error::SemaphoreValueCountMismatch);
     }
-}
 
-pub struct SubmitInfo<'a> {
-    pub wait_semaphores: &'a [&'a Semaphore],
-    pub wait_stages: &'a [u32],
-    pub signal_semaphores: &'a [&'a mut Semaphore],
-    pub command_buffers: &'a [&'a CommandBuffer],
+    Ok(())
 }
 
 pub struct PresentInfo<'a> {
@@ -5725,13 +8443,17 @@ pub struct Buffer {
 }
 
 impl Buffer {
-    pub fn new(device: Rc<Device>, size: u64, usage: u32) -> Result<Self, Error> {
+    pub fn new(
+        device: Rc<Device>,
+        size: u64,
+        usage: impl Into<BufferUsageFlags>,
+    ) -> Result<Self, Error> {
         let create_info = ffi::BufferCreateInfo {
             structure_type: ffi::StructureType::BufferCreateInfo,
             p_next: ptr::null(),
             flags: 0,
             size: size as _,
-            usage: usage as _,
+            usage: usage.into().bits(),
             sharing_mode: ffi::SharingMode::Exclusive,
             queue_family_index_count: 0,
             queue_family_indices: ptr::null(),
@@ -5779,13 +8501,25 @@ impl Buffer {
         MemoryRequirements {
             size: memory_requirements.size,
             alignment: memory_requirements.alignment,
-            memory_type: memory_requirements.memory_type,
+            memory_type_bits: memory_requirements.memory_type_bits,
         }
     }
 
+    #[must_use = "an unbound buffer cannot be used"]
     pub fn bind_memory(&mut self, memory: &Memory) -> Result<(), Error> {
-        let result =
-            unsafe { ffi::vkBindBufferMemory(self.device.handle, self.handle, memory.handle, 0) };
+        self.bind_memory_at(memory, 0)
+    }
+
+    /// Fails with `MisalignedOffset` if `offset` breaks the buffer's alignment requirement.
+    #[must_use = "an unbound buffer cannot be used"]
+    pub fn bind_memory_at(&mut self, memory: &Memory, offset: u64) -> Result<(), Error> {
+        if !offset.is_multiple_of(self.memory_requirements().alignment) {
+            return Err(Error::MisalignedOffset);
+        }
+
+        let result = unsafe {
+            ffi::vkBindBufferMemory(self.device.handle, self.handle, memory.handle, offset)
+        };
 
         match result {
             ffi::Result::Success => Ok(()),
@@ -5803,12 +8537,101 @@ impl Drop for Buffer {
     }
 }
 
-#[derive(Clone, Copy)]
+/// Format and range of a buffer viewed as texels, for texel buffer descriptors.
+/// `range` may be `WHOLE_SIZE` to view everything past `offset`.
+pub struct BufferViewCreateInfo<'a> {
+    pub buffer: &'a Buffer,
+    pub format: Format,
+    pub offset: u64,
+    pub range: u64,
+}
+
+pub struct BufferView {
+    device: Rc<Device>,
+    handle: ffi::BufferView,
+}
+
+impl BufferView {
+    pub fn new(device: Rc<Device>, create_info: BufferViewCreateInfo<'_>) -> Result<Self, Error> {
+        let create_info = ffi::BufferViewCreateInfo {
+            structure_type: ffi::StructureType::BufferViewCreateInfo,
+            p_next: ptr::null(),
+            flags: 0,
+            buffer: create_info.buffer.handle,
+            format: create_info.format.into(),
+            offset: create_info.offset,
+            range: create_info.range,
+        };
+
+        let mut handle = MaybeUninit::<ffi::BufferView>::uninit();
+
+        let result = unsafe {
+            ffi::vkCreateBufferView(
+                device.handle,
+                &create_info,
+                ptr::null(),
+                handle.as_mut_ptr(),
+            )
+        };
+
+        match result {
+            ffi::Result::Success => {
+                let handle = unsafe { handle.assume_init() };
+
+                let buffer_view = Self { device, handle };
+
+                Ok(buffer_view)
+            }
+            ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
+            ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
+
+impl Drop for BufferView {
+    fn drop(&mut self) {
+        unsafe { ffi::vkDestroyBufferView(self.device.handle, self.handle, ptr::null()) };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DescriptorType {
     CombinedImageSampler,
     StorageImage,
+    UniformTexelBuffer,
+    StorageTexelBuffer,
     UniformBuffer,
     StorageBuffer,
+    /// Like `UniformBuffer`, but the offset written to the descriptor is only a base that
+    /// `Commands::bind_descriptor_sets` adds its `dynamic_offsets` to, one per dynamic binding in
+    /// binding order. Both must be multiples of `min_uniform_buffer_offset_alignment`, and the
+    /// descriptor's range must still fit in the buffer after the dynamic offset is added.
+    UniformBufferDynamic,
+    /// `StorageBuffer` equivalent of `UniformBufferDynamic`, aligned to
+    /// `min_storage_buffer_offset_alignment`.
+    StorageBufferDynamic,
+}
+
+impl DescriptorType {
+    //buffer descriptors need their offset aligned to the device limit for their kind
+    fn check_offset(self, offset: u64, limits: &PhysicalDeviceLimits) -> Result<(), Error> {
+        let alignment = match self {
+            Self::UniformBuffer | Self::UniformBufferDynamic => {
+                limits.min_uniform_buffer_offset_alignment
+            }
+            Self::StorageBuffer | Self::StorageBufferDynamic => {
+                limits.min_storage_buffer_offset_alignment
+            }
+            _ => return Ok(()),
+        };
+
+        if !offset.is_multiple_of(alignment) {
+            return Err(Error::MisalignedOffset);
+        }
+
+        Ok(())
+    }
 }
 
 pub struct DescriptorSetLayoutBinding {
@@ -5816,6 +8639,9 @@ pub struct DescriptorSetLayoutBinding {
     pub descriptor_type: DescriptorType,
     pub descriptor_count: u32,
     pub stage: u32,
+    /// `DESCRIPTOR_BINDING_*` flags, e.g. `PARTIALLY_BOUND` for arrays that are only filled
+    /// as far as the shader reads them.
+    pub flags: u32,
 }
 
 pub struct DescriptorSetLayoutCreateInfo<'a> {
@@ -5825,6 +8651,21 @@ pub struct DescriptorSetLayoutCreateInfo<'a> {
 pub struct DescriptorSetLayout {
     device: Rc<Device>,
     handle: ffi::DescriptorSetLayout,
+    //(binding, descriptor_count) for bounds checking writes to sets of this layout
+    binding_counts: Rc<[(u32, u32)]>,
+    descriptor_types: Vec<(DescriptorType, u32)>,
+}
+
+//the layout itself has to be marked as well for bindings updated after binding
+fn layout_create_flags(binding_flags: &[u32]) -> u32 {
+    if binding_flags
+        .iter()
+        .any(|&flags| flags & DESCRIPTOR_BINDING_UPDATE_AFTER_BIND != 0)
+    {
+        DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL
+    } else {
+        0
+    }
 }
 
 impl DescriptorSetLayout {
@@ -5832,6 +8673,18 @@ impl DescriptorSetLayout {
         device: Rc<Device>,
         create_info: DescriptorSetLayoutCreateInfo<'_>,
     ) -> Result<Self, Error> {
+        let binding_counts = create_info
+            .bindings
+            .iter()
+            .map(|binding| (binding.binding, binding.descriptor_count))
+            .collect::<Rc<[_]>>();
+
+        let descriptor_types = create_info
+            .bindings
+            .iter()
+            .map(|binding| (binding.descriptor_type, binding.descriptor_count))
+            .collect::<Vec<_>>();
+
         let bindings = create_info
             .bindings
             .iter()
@@ -5844,10 +8697,35 @@ impl DescriptorSetLayout {
             })
             .collect::<Vec<_>>();
 
+        let binding_flags = create_info
+            .bindings
+            .iter()
+            .map(|binding| binding.flags as ffi::Flags)
+            .collect::<Vec<_>>();
+
+        let has_binding_flags = binding_flags.iter().any(|&flags| flags != 0);
+
+        if has_binding_flags && !device.descriptor_indexing {
+            return Err(Error::ExtensionNotPresent);
+        }
+
+        let flags = layout_create_flags(&binding_flags);
+
+        let binding_flags_create_info = ffi::DescriptorSetLayoutBindingFlagsCreateInfo {
+            structure_type: ffi::StructureType::DescriptorSetLayoutBindingFlagsCreateInfo,
+            p_next: ptr::null(),
+            binding_count: binding_flags.len() as _,
+            binding_flags: binding_flags.as_ptr(),
+        };
+
         let create_info = ffi::DescriptorSetLayoutCreateInfo {
             structure_type: ffi::StructureType::DescriptorSetLayoutCreateInfo,
-            p_next: ptr::null(),
-            flags: 0,
+            p_next: if has_binding_flags {
+                &binding_flags_create_info as *const _ as _
+            } else {
+                ptr::null()
+            },
+            flags,
             binding_count: create_info.bindings.len() as _,
             bindings: bindings.as_ptr(),
         };
@@ -5867,7 +8745,12 @@ impl DescriptorSetLayout {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let descriptor_set_layout = Self { device, handle };
+                let descriptor_set_layout = Self {
+                    device,
+                    handle,
+                    binding_counts,
+                    descriptor_types,
+                };
 
                 Ok(descriptor_set_layout)
             }
@@ -5889,9 +8772,29 @@ pub struct DescriptorSetAllocateInfo<'a> {
     pub set_layouts: &'a [&'a DescriptorSetLayout],
 }
 
+//`count` descriptors from `array_element` on have to fit in the array of `binding`
+fn check_array_range(
+    binding_counts: &[(u32, u32)],
+    binding: u32,
+    array_element: u32,
+    count: u32,
+) -> Result<(), Error> {
+    let binding_count = binding_counts
+        .iter()
+        .find(|(b, _)| *b == binding)
+        .map(|(_, count)| *count)
+        .ok_or(Error::OutOfBounds)?;
+
+    match array_element.checked_add(count) {
+        Some(end) if end <= binding_count => Ok(()),
+        _ => Err(Error::OutOfBounds),
+    }
+}
+
 pub struct DescriptorSet {
     device: Rc<Device>,
     handle: ffi::DescriptorSet,
+    binding_counts: Rc<[(u32, u32)]>,
 }
 
 impl DescriptorSet {
@@ -5899,6 +8802,49 @@ impl DescriptorSet {
         device: Rc<Device>,
         allocate_info: DescriptorSetAllocateInfo<'_>,
     ) -> Result<Vec<Self>, Error> {
+        let layouts = allocate_info.set_layouts;
+
+        //checked here since the driver only reports a bare OutOfPoolMemory
+        let mut capacity = allocate_info.descriptor_pool.capacity.borrow_mut();
+
+        let (remaining_sets, remaining_descriptors) = &mut *capacity;
+
+        if layouts.len() as u32 > *remaining_sets {
+            Err(Error::OutOfPoolSets {
+                requested: layouts.len() as _,
+                remaining: *remaining_sets,
+            })?
+        }
+
+        let mut requested_descriptors = Vec::<(DescriptorType, u32)>::new();
+
+        for &(descriptor_type, descriptor_count) in
+            layouts.iter().flat_map(|layout| &layout.descriptor_types)
+        {
+            match requested_descriptors
+                .iter_mut()
+                .find(|(ty, _)| *ty == descriptor_type)
+            {
+                Some((_, count)) => *count += descriptor_count,
+                None => requested_descriptors.push((descriptor_type, descriptor_count)),
+            }
+        }
+
+        for &(descriptor_type, requested) in &requested_descriptors {
+            let remaining = remaining_descriptors
+                .iter()
+                .find(|(ty, _)| *ty == descriptor_type)
+                .map_or(0, |(_, count)| *count);
+
+            if requested > remaining {
+                Err(Error::OutOfPoolDescriptors {
+                    descriptor_type,
+                    requested,
+                    remaining,
+                })?
+            }
+        }
+
         let set_layouts = allocate_info
             .set_layouts
             .iter()
@@ -5926,12 +8872,24 @@ impl DescriptorSet {
 
                 let descriptor_sets = handles
                     .into_iter()
-                    .map(|handle| Self {
+                    .zip(layouts)
+                    .map(|(handle, set_layout)| Self {
                         device: device.clone(),
                         handle,
+                        binding_counts: set_layout.binding_counts.clone(),
                     })
                     .collect::<Vec<_>>();
 
+                *remaining_sets -= descriptor_sets.len() as u32;
+
+                for (descriptor_type, requested) in requested_descriptors {
+                    for (ty, count) in remaining_descriptors.iter_mut() {
+                        if *ty == descriptor_type {
+                            *count -= requested;
+                        }
+                    }
+                }
+
                 Ok(descriptor_sets)
             }
             ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory),
@@ -5942,9 +8900,17 @@ impl DescriptorSet {
         }
     }
 
-    pub fn update(writes: &'_ [WriteDescriptorSet], copies: &'_ [CopyDescriptorSet]) {
+    /// Each write covers `descriptor_count` elements of its binding's array starting at
+    /// `dst_array_element`, and needs exactly that many infos of the kind its descriptor type
+    /// reads. Fails with `DescriptorCountMismatch` if it doesn't, `OutOfBounds` if the range
+    /// runs past the end of the binding, and `MisalignedOffset` if a buffer offset breaks the
+    /// device's offset alignment.
+    pub fn update(
+        writes: &'_ [WriteDescriptorSet],
+        copies: &'_ [CopyDescriptorSet],
+    ) -> Result<(), Error> {
         if writes.len() == 0 && copies.len() == 0 {
-            return;
+            return Ok(());
         }
 
         let same_device_writes = writes
@@ -5965,6 +8931,55 @@ impl DescriptorSet {
             copies[0].dst_set.device.clone()
         };
 
+        for write in writes {
+            let info_count = match write.descriptor_type {
+                DescriptorType::CombinedImageSampler | DescriptorType::StorageImage => {
+                    write.image_infos.len()
+                }
+                DescriptorType::UniformTexelBuffer | DescriptorType::StorageTexelBuffer => {
+                    write.texel_buffer_views.len()
+                }
+                DescriptorType::UniformBuffer
+                | DescriptorType::StorageBuffer
+                | DescriptorType::UniformBufferDynamic
+                | DescriptorType::StorageBufferDynamic => write.buffer_infos.len(),
+            };
+
+            if info_count != write.descriptor_count as usize {
+                return Err(Error::DescriptorCountMismatch);
+            }
+
+            check_array_range(
+                &write.dst_set.binding_counts,
+                write.dst_binding,
+                write.dst_array_element,
+                write.descriptor_count,
+            )?;
+        }
+
+        for copy in copies {
+            check_array_range(
+                &copy.src_set.binding_counts,
+                copy.src_binding,
+                copy.src_array_element,
+                copy.descriptor_count,
+            )?;
+            check_array_range(
+                &copy.dst_set.binding_counts,
+                copy.dst_binding,
+                copy.dst_array_element,
+                copy.descriptor_count,
+            )?;
+        }
+
+        for write in writes {
+            for buffer_info in write.buffer_infos {
+                write
+                    .descriptor_type
+                    .check_offset(buffer_info.offset as _, &device.limits)?;
+            }
+        }
+
         let write_buffer_infos = writes
             .iter()
             .map(|write| {
@@ -6019,6 +9034,29 @@ impl DescriptorSet {
             })
             .collect::<Vec<_>>();
 
+        let write_texel_buffer_views = writes
+            .iter()
+            .map(|write| {
+                write
+                    .texel_buffer_views
+                    .iter()
+                    .map(|buffer_view| buffer_view.handle)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let write_texel_buffer_view_ptrs = writes
+            .iter()
+            .enumerate()
+            .map(|(i, write)| {
+                if !write.texel_buffer_views.is_empty() {
+                    write_texel_buffer_views[i].as_ptr()
+                } else {
+                    ptr::null()
+                }
+            })
+            .collect::<Vec<_>>();
+
         let writes = writes
             .iter()
             .enumerate()
@@ -6032,7 +9070,7 @@ impl DescriptorSet {
                 descriptor_type: write.descriptor_type.into(),
                 image_infos: write_image_info_ptrs[i],
                 buffer_infos: write_buffer_info_ptrs[i],
-                texel_buffer_view: ptr::null(),
+                texel_buffer_view: write_texel_buffer_view_ptrs[i],
             })
             .collect::<Vec<_>>();
 
@@ -6060,6 +9098,8 @@ impl DescriptorSet {
                 copies.as_ptr(),
             )
         };
+
+        Ok(())
     }
 }
 
@@ -6083,6 +9123,7 @@ pub struct WriteDescriptorSet<'a> {
     pub descriptor_type: DescriptorType,
     pub buffer_infos: &'a [DescriptorBufferInfo<'a>],
     pub image_infos: &'a [DescriptorImageInfo<'a>],
+    pub texel_buffer_views: &'a [&'a BufferView],
 }
 
 pub struct CopyDescriptorSet<'a> {
@@ -6101,6 +9142,7 @@ pub struct DescriptorPoolSize {
 }
 
 pub struct DescriptorPoolCreateInfo<'a> {
+    pub flags: u32,
     pub max_sets: u32,
     pub pool_sizes: &'a [DescriptorPoolSize],
 }
@@ -6108,6 +9150,8 @@ pub struct DescriptorPoolCreateInfo<'a> {
 pub struct DescriptorPool {
     device: Rc<Device>,
     handle: ffi::DescriptorPool,
+    //(sets, descriptors per type) left, sets are never freed back to the pool
+    capacity: RefCell<(u32, Vec<(DescriptorType, u32)>)>,
 }
 
 impl DescriptorPool {
@@ -6115,6 +9159,27 @@ impl DescriptorPool {
         device: Rc<Device>,
         create_info: DescriptorPoolCreateInfo<'_>,
     ) -> Result<Self, Error> {
+        let mut remaining_descriptors = Vec::<(DescriptorType, u32)>::new();
+
+        for pool_size in create_info.pool_sizes {
+            match remaining_descriptors
+                .iter_mut()
+                .find(|(ty, _)| *ty == pool_size.descriptor_type)
+            {
+                Some((_, count)) => *count += pool_size.descriptor_count,
+                None => remaining_descriptors
+                    .push((pool_size.descriptor_type, pool_size.descriptor_count)),
+            }
+        }
+
+        let capacity = RefCell::new((create_info.max_sets, remaining_descriptors));
+
+        if create_info.flags & DESCRIPTOR_POOL_CREATE_UPDATE_AFTER_BIND != 0
+            && !device.descriptor_indexing
+        {
+            return Err(Error::ExtensionNotPresent);
+        }
+
         let pool_sizes = create_info
             .pool_sizes
             .iter()
@@ -6127,7 +9192,7 @@ impl DescriptorPool {
         let create_info = ffi::DescriptorPoolCreateInfo {
             structure_type: ffi::StructureType::DescriptorPoolCreateInfo,
             p_next: ptr::null(),
-            flags: 0,
+            flags: create_info.flags,
             max_sets: create_info.max_sets,
             pool_size_count: create_info.pool_sizes.len() as _,
             pool_sizes: pool_sizes.as_ptr(),
@@ -6148,7 +9213,11 @@ impl DescriptorPool {
             ffi::Result::Success => {
                 let handle = unsafe { handle.assume_init() };
 
-                let descriptor_pool = Self { device, handle };
+                let descriptor_pool = Self {
+                    device,
+                    handle,
+                    capacity,
+                };
 
                 Ok(descriptor_pool)
             }
@@ -6181,10 +9250,55 @@ pub struct MemoryProperties {
     memory_heaps: Vec<MemoryHeap>,
 }
 
+#[derive(Clone, Copy)]
 pub struct MemoryRequirements {
     size: u64,
     alignment: u64,
-    memory_type: u32,
+    memory_type_bits: u32,
+}
+
+impl MemoryProperties {
+    /// Finds the first type in `memory_type_bits` with every flag in `property_flags`. If there
+    /// is none the flags that only affect speed are dropped, first `HOST_CACHED` and then
+    /// `DEVICE_LOCAL`, so `DEVICE_LOCAL | HOST_VISIBLE` still finds plain host visible memory
+    /// on discrete gpus without resizable bar. `HOST_VISIBLE` and `HOST_COHERENT` are never
+    /// dropped since mapping and unflushed writes depend on them, and neither is the last
+    /// requested flag, so asking for only `DEVICE_LOCAL` never hands back host memory.
+    pub fn find_memory_type(&self, memory_type_bits: u32, property_flags: u32) -> Option<u32> {
+        let relaxed = [
+            property_flags,
+            property_flags & !MEMORY_PROPERTY_HOST_CACHED,
+            property_flags & !(MEMORY_PROPERTY_HOST_CACHED | MEMORY_PROPERTY_DEVICE_LOCAL),
+        ];
+
+        let mut relaxed = relaxed
+            .into_iter()
+            .filter(|&relaxed| relaxed != 0 || property_flags == 0);
+
+        relaxed.find_map(|property_flags| {
+            (0..self.memory_types.len())
+                .find(|&i| {
+                    memory_type_bits & (1 << i) != 0
+                        && self.memory_types[i].property_flags & property_flags == property_flags
+                })
+                .map(|i| i as u32)
+        })
+    }
+}
+
+impl MemoryRequirements {
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn alignment(&self) -> u64 {
+        self.alignment
+    }
+
+    /// Bitmask of the memory type indices the resource can be bound to.
+    pub fn memory_type_bits(&self) -> u32 {
+        self.memory_type_bits
+    }
 }
 
 pub struct MemoryAllocateInfo {
@@ -6206,23 +9320,19 @@ impl Memory {
         properties: MemoryProperties,
         mapped: bool,
     ) -> Result<Self, Error> {
-        let size = requirements.size;
-
-        let mut memory_type_index = None;
-
-        for i in 0..properties.memory_types.len() {
-            if requirements.memory_type & (1 << i) != 0
-                && properties.memory_types[i as usize].property_flags & allocate_info.property_flags
-                    != 0
-            {
-                memory_type_index = Some(i);
-                break;
-            }
-        }
+        let memory_type_index = properties
+            .find_memory_type(requirements.memory_type_bits, allocate_info.property_flags)
+            .ok_or(Error::OutOfDeviceMemory)?;
 
-        let memory_type_index =
-            memory_type_index.unwrap_or_else(|| panic!("couldnt find valid memory type")) as _;
+        Self::allocate_from_type(device, memory_type_index, requirements.size, mapped)
+    }
 
+    pub fn allocate_from_type(
+        device: Rc<Device>,
+        memory_type_index: u32,
+        size: u64,
+        mapped: bool,
+    ) -> Result<Self, Error> {
         let allocate_info = ffi::MemoryAllocateInfo {
             structure_type: ffi::StructureType::MemoryAllocateInfo,
             p_next: ptr::null(),
@@ -6282,6 +9392,10 @@ impl Memory {
         }
     }
 
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
     pub fn write<'a, T: 'a>(
         &self,
         offset: usize,
@@ -6304,6 +9418,132 @@ impl Memory {
 
         Ok(())
     }
+
+    /// Copies `data.len()` elements starting at byte `offset` out of persistently mapped
+    /// memory, the gpu must be done writing the range.
+    pub fn read<T: Pod>(&self, offset: u64, data: &mut [T]) -> Result<(), Error> {
+        mapped_size::<T>(offset, data.len(), self.size)?;
+
+        let mem = self.mem.ok_or(Error::MemoryMapFailed)?;
+
+        unsafe {
+            ptr::copy_nonoverlapping(
+                mem.add(offset as _) as *const T,
+                data.as_mut_ptr(),
+                data.len(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Maps `len` elements of `T` starting at byte `offset`, unmapping when the guard drops.
+    /// Persistently mapped memory is reused instead of being mapped again.
+    ///
+    /// This takes `&mut self` since vulkan allows one mapping per allocation at a time and
+    /// two guards over the same range would alias. The guard is not `Send`, and the gpu
+    /// must not be using the range while it is held.
+    pub fn map<T: Pod>(&mut self, offset: u64, len: usize) -> Result<MappedSlice<'_, T>, Error> {
+        let size = mapped_size::<T>(offset, len, self.size)?;
+
+        let (data, unmap) = match self.mem {
+            Some(mem) => (unsafe { mem.add(offset as _) }, false),
+            None => {
+                let mut mem = ptr::null_mut::<u8>();
+
+                let result = unsafe {
+                    ffi::vkMapMemory(
+                        self.device.handle,
+                        self.handle,
+                        offset,
+                        size,
+                        0,
+                        &mut mem as *mut _ as _,
+                    )
+                };
+
+                match result {
+                    ffi::Result::Success => {}
+                    ffi::Result::OutOfHostMemory => Err(Error::OutOfHostMemory)?,
+                    ffi::Result::OutOfDeviceMemory => Err(Error::OutOfDeviceMemory)?,
+                    ffi::Result::MemoryMapFailed => Err(Error::MemoryMapFailed)?,
+                    _ => panic!("unexpected result: {:?}", result),
+                }
+
+                (mem, true)
+            }
+        };
+
+        Ok(MappedSlice {
+            memory: self,
+            data: data.cast(),
+            len,
+            unmap,
+        })
+    }
+}
+
+//byte size of `len` elements at `offset`, failing instead of wrapping on huge ranges
+fn mapped_size<T>(offset: u64, len: usize, memory_size: u64) -> Result<u64, Error> {
+    if !offset.is_multiple_of(mem::align_of::<T>() as u64) {
+        return Err(Error::MisalignedOffset);
+    }
+
+    let size = len
+        .checked_mul(mem::size_of::<T>())
+        .ok_or(Error::OutOfBounds)? as u64;
+
+    match offset.checked_add(size) {
+        Some(end) if end <= memory_size => Ok(size),
+        _ => Err(Error::OutOfBounds),
+    }
+}
+
+/// Types that can be read from any bytes the gpu wrote, so no `bool`, `char`, enums or
+/// references.
+///
+/// # Safety
+///
+/// Every bit pattern must be a valid value of the type, and it must not contain padding.
+pub unsafe trait Pod: Copy {}
+
+macro_rules! impl_pod {
+    ($($name : ty),*) => {
+        $(unsafe impl Pod for $name {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+pub struct MappedSlice<'a, T> {
+    memory: &'a mut Memory,
+    data: *mut T,
+    len: usize,
+    unmap: bool,
+}
+
+impl<T> Deref for MappedSlice<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { slice::from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl<T> DerefMut for MappedSlice<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { slice::from_raw_parts_mut(self.data, self.len) }
+    }
+}
+
+impl<T> Drop for MappedSlice<'_, T> {
+    fn drop(&mut self) {
+        if self.unmap {
+            unsafe { ffi::vkUnmapMemory(self.memory.device.handle, self.memory.handle) };
+        }
+    }
 }
 
 impl Drop for Memory {
@@ -6337,6 +9577,32 @@ pub struct BufferImageCopy {
     pub image_extent: Extent3d,
 }
 
+impl From<&BufferImageCopy> for ffi::BufferImageCopy {
+    fn from(copy: &BufferImageCopy) -> Self {
+        Self {
+            buffer_offset: copy.buffer_offset as _,
+            buffer_row_length: copy.buffer_row_length as _,
+            buffer_image_height: copy.buffer_image_height as _,
+            image_subresource: ffi::ImageSubresourceLayers {
+                aspect_mask: copy.image_subresource.aspect_mask as _,
+                mip_level: copy.image_subresource.mip_level as _,
+                base_array_layer: copy.image_subresource.base_array_layer as _,
+                layer_count: copy.image_subresource.layer_count as _,
+            },
+            image_offset: [
+                copy.image_offset.0 as _,
+                copy.image_offset.1 as _,
+                copy.image_offset.2 as _,
+            ],
+            image_extent: [
+                copy.image_extent.0 as _,
+                copy.image_extent.1 as _,
+                copy.image_extent.2 as _,
+            ],
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Filter {
     Nearest,
@@ -6355,6 +9621,8 @@ pub enum SamplerAddressMode {
     MirroredRepeat,
     ClampToEdge,
     ClampToBorder,
+    /// Needs vulkan 1.2 or `KHR_SAMPLER_MIRROR_CLAMP_TO_EDGE`.
+    MirrorClampToEdge,
 }
 
 #[derive(Clone, Copy)]
@@ -6367,6 +9635,15 @@ pub enum BorderColor {
     IntOpaqueWhite,
 }
 
+/// How the texels under a linear filter are combined, `Min` and `Max` take the smallest or
+/// largest texel instead of blending them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SamplerReductionMode {
+    WeightedAverage,
+    Min,
+    Max,
+}
+
 pub struct SamplerCreateInfo {
     pub mag_filter: Filter,
     pub min_filter: Filter,
@@ -6377,12 +9654,20 @@ pub struct SamplerCreateInfo {
     pub mip_lod_bias: f32,
     pub anisotropy_enable: bool,
     pub max_anisotropy: f32,
+    /// Turns the sampler into a depth comparison sampler for shadow maps. Bind it as a
+    /// `CombinedImageSampler` with a depth image view (aspect `IMAGE_ASPECT_DEPTH`) in
+    /// `DepthStencilReadOnly` layout and sample through a `sampler2DShadow`, which returns the
+    /// filtered result of `reference compare_op texel` instead of the depth value.
     pub compare_enable: bool,
     pub compare_op: CompareOp,
     pub min_lod: f32,
     pub max_lod: f32,
     pub border_color: BorderColor,
     pub unnormalized_coordinates: bool,
+    /// Needs `EXT_SAMPLER_FILTER_MINMAX`, without it the sampler falls back to
+    /// `WeightedAverage`. Check `Device::sampler_filter_minmax_enabled` if the shader relies on
+    /// it.
+    pub reduction_mode: Option<SamplerReductionMode>,
 }
 
 pub struct Sampler {
@@ -6390,11 +9675,35 @@ pub struct Sampler {
     handle: ffi::Sampler,
 }
 
-impl Sampler {
-    pub fn new(device: Rc<Device>, create_info: SamplerCreateInfo) -> Result<Self, Error> {
+impl Sampler {
+    pub fn new(device: Rc<Device>, create_info: SamplerCreateInfo) -> Result<Self, Error> {
+        if create_info.anisotropy_enable && !device.enabled_features.sampler_anisotropy {
+            return Err(Error::FeatureNotPresent);
+        }
+
+        let max_anisotropy = create_info
+            .max_anisotropy
+            .min(device.limits.max_sampler_anisotropy);
+
+        //weighted average is the default, so it needs no extension struct
+        let reduction_mode = create_info
+            .reduction_mode
+            .filter(|&mode| mode != SamplerReductionMode::WeightedAverage)
+            .filter(|_| device.sampler_filter_minmax);
+
+        let reduction_mode_create_info = reduction_mode.map(|mode| {
+            ffi::SamplerReductionModeCreateInfo {
+                structure_type: ffi::StructureType::SamplerReductionModeCreateInfo,
+                p_next: ptr::null(),
+                reduction_mode: mode.into(),
+            }
+        });
+
         let create_info = ffi::SamplerCreateInfo {
             structure_type: ffi::StructureType::SamplerCreateInfo,
-            p_next: ptr::null(),
+            p_next: reduction_mode_create_info
+                .as_ref()
+                .map_or(ptr::null(), |info| info as *const _ as _),
             flags: 0,
             mag_filter: create_info.mag_filter.into(),
             min_filter: create_info.min_filter.into(),
@@ -6404,7 +9713,7 @@ impl Sampler {
             address_mode_w: create_info.address_mode_w.into(),
             mip_lod_bias: create_info.mip_lod_bias as _,
             anisotropy_enable: create_info.anisotropy_enable as _,
-            max_anisotropy: create_info.max_anisotropy as _,
+            max_anisotropy,
             compare_enable: create_info.compare_enable as _,
             compare_op: create_info.compare_op.into(),
             min_lod: create_info.min_lod as _,
@@ -6494,3 +9803,1328 @@ pub struct BufferMemoryBarrier<'a> {
     pub offset: u64,
     pub size: u64,
 }
+
+impl From<&MemoryBarrier> for ffi::MemoryBarrier {
+    fn from(barrier: &MemoryBarrier) -> Self {
+        Self {
+            structure_type: ffi::StructureType::MemoryBarrier,
+            p_next: ptr::null(),
+            src_access_mask: barrier.src_access_mask as _,
+            dst_access_mask: barrier.dst_access_mask as _,
+        }
+    }
+}
+
+impl From<&BufferMemoryBarrier<'_>> for ffi::BufferMemoryBarrier {
+    fn from(barrier: &BufferMemoryBarrier<'_>) -> Self {
+        Self {
+            structure_type: ffi::StructureType::BufferMemoryBarrier,
+            p_next: ptr::null(),
+            src_access_mask: barrier.src_access_mask as _,
+            dst_access_mask: barrier.dst_access_mask as _,
+            src_queue_family_index: barrier.src_queue_family_index as _,
+            dst_queue_family_index: barrier.dst_queue_family_index as _,
+            buffer: barrier.buffer.handle,
+            offset: barrier.offset as _,
+            size: barrier.size as _,
+        }
+    }
+}
+
+impl From<&ImageMemoryBarrier<'_>> for ffi::ImageMemoryBarrier {
+    fn from(barrier: &ImageMemoryBarrier<'_>) -> Self {
+        Self {
+            structure_type: ffi::StructureType::ImageMemoryBarrier,
+            p_next: ptr::null(),
+            src_access_mask: barrier.src_access_mask as _,
+            dst_access_mask: barrier.dst_access_mask as _,
+            old_layout: barrier.old_layout.into(),
+            new_layout: barrier.new_layout.into(),
+            src_queue_family_index: barrier.src_queue_family_index as _,
+            dst_queue_family_index: barrier.dst_queue_family_index as _,
+            image: barrier.image.handle,
+            subresource_range: ffi::ImageSubresourceRange {
+                aspect_mask: barrier.subresource_range.aspect_mask,
+                base_mip_level: barrier.subresource_range.base_mip_level,
+                level_count: barrier.subresource_range.level_count,
+                base_array_layer: barrier.subresource_range.base_array_layer,
+                layer_count: barrier.subresource_range.layer_count,
+            },
+        }
+    }
+}
+
+//empty arrays are passed as null rather than a dangling pointer
+fn slice_ptr<T>(slice: &[T]) -> *const T {
+    if !slice.is_empty() {
+        slice.as_ptr()
+    } else {
+        ptr::null()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOST: u32 = MEMORY_PROPERTY_HOST_VISIBLE | MEMORY_PROPERTY_HOST_COHERENT;
+
+    fn memory_properties(types: &[u32]) -> MemoryProperties {
+        MemoryProperties {
+            memory_types: types
+                .iter()
+                .map(|&property_flags| MemoryType {
+                    property_flags,
+                    heap_index: 0,
+                })
+                .collect(),
+            memory_heaps: vec![MemoryHeap {
+                size: 1 << 30,
+                flags: 0,
+            }],
+        }
+    }
+
+    //every type is device local and host visible on unified memory
+    fn integrated() -> MemoryProperties {
+        memory_properties(&[
+            MEMORY_PROPERTY_DEVICE_LOCAL,
+            MEMORY_PROPERTY_DEVICE_LOCAL | HOST,
+            MEMORY_PROPERTY_DEVICE_LOCAL | HOST | MEMORY_PROPERTY_HOST_CACHED,
+        ])
+    }
+
+    //vram and system memory are separate, without resizable bar
+    fn discrete() -> MemoryProperties {
+        memory_properties(&[
+            MEMORY_PROPERTY_DEVICE_LOCAL,
+            HOST,
+            HOST | MEMORY_PROPERTY_HOST_CACHED,
+        ])
+    }
+
+    #[test]
+    fn missing_extensions_named() {
+        let available = [KHR_SWAPCHAIN.to_owned(), EXT_MEMORY_BUDGET.to_owned()];
+
+        assert!(missing_extensions(&available, &[KHR_SWAPCHAIN]).is_empty());
+        assert_eq!(
+            missing_extensions(&available, &[KHR_SWAPCHAIN, EXT_MESH_SHADER]),
+            [EXT_MESH_SHADER]
+        );
+    }
+
+    #[test]
+    fn check_extensions_names_first_missing() {
+        let available = [KHR_SWAPCHAIN.to_owned()];
+
+        assert!(check_extensions(&available, &[KHR_SWAPCHAIN]).is_ok());
+        assert!(matches!(
+            check_extensions(&available, &[EXT_MEMORY_BUDGET, KHR_SWAPCHAIN, EXT_MESH_SHADER]),
+            Err(Error::MissingExtension(EXT_MEMORY_BUDGET))
+        ));
+    }
+
+    fn limits() -> PhysicalDeviceLimits {
+        PhysicalDeviceLimits {
+            max_image_dimension_2d: 4096,
+            max_sampler_anisotropy: 16.0,
+            min_uniform_buffer_offset_alignment: 256,
+            min_storage_buffer_offset_alignment: 64,
+            timestamp_period: 1.0,
+        }
+    }
+
+    #[test]
+    fn spirv_code_magic() {
+        let code = [SPIRV_MAGIC, 0x00010000, 0, 1, 0];
+
+        assert!(matches!(spirv_code(&code), Ok(Cow::Borrowed(_))));
+
+        let swapped = code.map(u32::swap_bytes);
+
+        assert_eq!(*spirv_code(&swapped).unwrap(), code);
+
+        assert!(matches!(spirv_code(&[0xdeadbeef, 0x00010000]), Err(Error::InvalidShader)));
+        //fewer than the four bytes of the magic number
+        assert!(matches!(spirv_code(&[]), Err(Error::InvalidShader)));
+    }
+
+    #[test]
+    fn mapped_size_bounds() {
+        assert_eq!(mapped_size::<u32>(4, 3, 16).unwrap(), 12);
+        assert!(matches!(mapped_size::<u32>(8, 3, 16), Err(Error::OutOfBounds)));
+        assert!(matches!(mapped_size::<u32>(2, 1, 16), Err(Error::MisalignedOffset)));
+
+        //these would wrap around and pass the bounds check without checked arithmetic
+        assert!(matches!(mapped_size::<u64>(0, usize::MAX / 4, 16), Err(Error::OutOfBounds)));
+        assert!(matches!(mapped_size::<u32>(u64::MAX - 3, 2, 16), Err(Error::OutOfBounds)));
+    }
+
+    #[test]
+    fn debug_name_drops_nul() {
+        assert_eq!(debug_name("shadow pass").as_bytes(), b"shadow pass");
+        assert_eq!(debug_name("sha\0dow\0").as_bytes(), b"shadow");
+    }
+
+    #[test]
+    fn check_offset_dynamic() {
+        let limits = limits();
+
+        //the base offset of a dynamic descriptor plus the offset given when binding it
+        let (base, dynamic) = (256, 2 * 256);
+
+        let uniform = DescriptorType::UniformBufferDynamic;
+
+        assert!(uniform.check_offset(base, &limits).is_ok());
+        assert!(uniform.check_offset(base + dynamic, &limits).is_ok());
+        assert!(matches!(
+            uniform.check_offset(base + 64, &limits),
+            Err(Error::MisalignedOffset)
+        ));
+
+        let storage = DescriptorType::StorageBufferDynamic;
+
+        assert!(storage.check_offset(base + 64, &limits).is_ok());
+        assert!(matches!(
+            storage.check_offset(base + 32, &limits),
+            Err(Error::MisalignedOffset)
+        ));
+    }
+
+    #[test]
+    fn check_offset_static() {
+        let limits = limits();
+
+        assert!(matches!(
+            DescriptorType::UniformBuffer.check_offset(128, &limits),
+            Err(Error::MisalignedOffset)
+        ));
+        assert!(DescriptorType::StorageBuffer.check_offset(128, &limits).is_ok());
+        assert!(DescriptorType::StorageImage.check_offset(1, &limits).is_ok());
+    }
+
+    #[test]
+    fn find_memory_type_exact() {
+        let flags = MEMORY_PROPERTY_DEVICE_LOCAL | HOST;
+
+        assert_eq!(integrated().find_memory_type(!0, flags), Some(1));
+        assert_eq!(
+            discrete().find_memory_type(!0, HOST | MEMORY_PROPERTY_HOST_CACHED),
+            Some(2)
+        );
+        assert_eq!(
+            discrete().find_memory_type(!0, MEMORY_PROPERTY_DEVICE_LOCAL),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn find_memory_type_relaxed() {
+        let flags = MEMORY_PROPERTY_DEVICE_LOCAL | HOST;
+
+        //without resizable bar staging memory falls back to plain host memory
+        assert_eq!(discrete().find_memory_type(!0, flags), Some(1));
+
+        let flags = MEMORY_PROPERTY_DEVICE_LOCAL | MEMORY_PROPERTY_HOST_CACHED;
+
+        assert_eq!(discrete().find_memory_type(!0, flags), Some(0));
+    }
+
+    #[test]
+    fn find_memory_type_keeps_required_flags() {
+        let host = memory_properties(&[HOST, HOST | MEMORY_PROPERTY_HOST_CACHED]);
+
+        assert_eq!(host.find_memory_type(!0, MEMORY_PROPERTY_DEVICE_LOCAL), None);
+
+        let device = memory_properties(&[MEMORY_PROPERTY_DEVICE_LOCAL]);
+
+        assert_eq!(device.find_memory_type(!0, MEMORY_PROPERTY_HOST_CACHED), None);
+        assert_eq!(device.find_memory_type(!0, HOST), None);
+    }
+
+    #[test]
+    fn find_memory_type_respects_type_bits() {
+        let flags = MEMORY_PROPERTY_DEVICE_LOCAL;
+
+        assert_eq!(integrated().find_memory_type(0b110, flags), Some(1));
+        assert_eq!(integrated().find_memory_type(0, flags), None);
+    }
+
+    #[test]
+    fn derivative_flags_by_index() {
+        let allow = PIPELINE_CREATE_ALLOW_DERIVATIVES;
+
+        assert_eq!(
+            derivative_flags(&[(allow, false, -1), (0, false, 0)]).unwrap(),
+            [allow, PIPELINE_CREATE_DERIVATIVE]
+        );
+        //a base handle instead of an index
+        assert_eq!(
+            derivative_flags(&[(0, true, -1)]).unwrap(),
+            [PIPELINE_CREATE_DERIVATIVE]
+        );
+    }
+
+    #[test]
+    fn derivative_flags_invalid_base() {
+        let allow = PIPELINE_CREATE_ALLOW_DERIVATIVES;
+
+        assert!(matches!(
+            derivative_flags(&[(0, false, 1), (allow, false, -1)]),
+            Err(Error::InvalidBasePipeline)
+        ));
+        assert!(matches!(
+            derivative_flags(&[(0, false, 0)]),
+            Err(Error::InvalidBasePipeline)
+        ));
+        //the base has to allow derivatives
+        assert!(matches!(
+            derivative_flags(&[(0, false, -1), (0, false, 0)]),
+            Err(Error::InvalidBasePipeline)
+        ));
+        //a handle and an index at once
+        assert!(matches!(
+            derivative_flags(&[(allow, false, -1), (0, true, 0)]),
+            Err(Error::InvalidBasePipeline)
+        ));
+    }
+
+    #[test]
+    fn derivative_flags_without_base() {
+        let flags = PIPELINE_CREATE_DISABLE_OPTIMIZATION;
+
+        assert_eq!(derivative_flags(&[(flags, false, -1)]).unwrap(), [flags]);
+    }
+
+    #[test]
+    fn blend_enums_match_spec() {
+        //in the order of their spec values, which start at 0 and have no gaps
+        let factors = [
+            BlendFactor::Zero,
+            BlendFactor::One,
+            BlendFactor::SrcColor,
+            BlendFactor::OneMinusSrcColor,
+            BlendFactor::DstColor,
+            BlendFactor::OneMinusDstColor,
+            BlendFactor::SrcAlpha,
+            BlendFactor::OneMinusSrcAlpha,
+            BlendFactor::DstAlpha,
+            BlendFactor::OneMinusDstAlpha,
+            BlendFactor::ConstantColor,
+            BlendFactor::OneMinusConstantColor,
+            BlendFactor::ConstantAlpha,
+            BlendFactor::OneMinusConstantAlpha,
+            BlendFactor::SrcAlphaSaturate,
+            BlendFactor::Src1Color,
+            BlendFactor::OneMinusSrc1Color,
+            BlendFactor::Src1Alpha,
+            BlendFactor::OneMinusSrc1Alpha,
+        ];
+
+        for (value, factor) in factors.into_iter().enumerate() {
+            assert_eq!(ffi::BlendFactor::from(factor) as usize, value);
+        }
+
+        let ops = [
+            BlendOp::Add,
+            BlendOp::Subtract,
+            BlendOp::ReverseSubtract,
+            BlendOp::Min,
+            BlendOp::Max,
+        ];
+
+        for (value, op) in ops.into_iter().enumerate() {
+            assert_eq!(ffi::BlendOp::from(op) as usize, value);
+        }
+
+        let logic_ops = [
+            LogicOp::Clear,
+            LogicOp::And,
+            LogicOp::AndReverse,
+            LogicOp::Copy,
+            LogicOp::AndInverted,
+            LogicOp::NoOp,
+            LogicOp::Xor,
+            LogicOp::Or,
+            LogicOp::Nor,
+            LogicOp::Equivalent,
+            LogicOp::Invert,
+            LogicOp::OrReverse,
+            LogicOp::CopyInverted,
+            LogicOp::OrInverted,
+            LogicOp::Nand,
+            LogicOp::Set,
+        ];
+
+        for (value, logic_op) in logic_ops.into_iter().enumerate() {
+            assert_eq!(ffi::LogicOp::from(logic_op) as usize, value);
+        }
+    }
+
+    fn subresource_range(
+        base_mip_level: u32,
+        base_array_layer: u32,
+        layer_count: u32,
+    ) -> ImageSubresourceRange {
+        ImageSubresourceRange {
+            aspect_mask: IMAGE_ASPECT_COLOR,
+            base_mip_level,
+            level_count: 1,
+            base_array_layer,
+            layer_count,
+        }
+    }
+
+    #[test]
+    fn check_range_cube() {
+        let cube = ImageViewType::Cube;
+
+        assert!(cube.check_range(&subresource_range(0, 0, 6), 1, 6, true).is_ok());
+        assert!(cube.check_range(&subresource_range(0, 6, 6), 1, 12, true).is_ok());
+
+        for layer_count in [1, 5, 7, 12] {
+            assert!(matches!(
+                cube.check_range(&subresource_range(0, 0, layer_count), 1, 12, true),
+                Err(Error::InvalidLayerRange)
+            ));
+        }
+
+        //6 layers are not enough without the cube compatible flag
+        assert!(matches!(
+            cube.check_range(&subresource_range(0, 0, 6), 1, 6, false),
+            Err(Error::InvalidLayerRange)
+        ));
+        assert!(matches!(
+            cube.check_range(&subresource_range(0, 1, 6), 1, 6, true),
+            Err(Error::InvalidLayerRange)
+        ));
+
+        let cube_array = ImageViewType::CubeArray;
+
+        assert!(cube_array.check_range(&subresource_range(0, 0, 12), 1, 12, true).is_ok());
+        assert!(matches!(
+            cube_array.check_range(&subresource_range(0, 0, 9), 1, 12, true),
+            Err(Error::InvalidLayerRange)
+        ));
+    }
+
+    #[test]
+    fn check_range_mips() {
+        let two_dim = ImageViewType::TwoDim;
+
+        //the views `ImageView::of_mip` makes of a 2 mip image
+        assert!(two_dim.check_range(&subresource_range(0, 0, 1), 2, 1, false).is_ok());
+        assert!(two_dim.check_range(&subresource_range(1, 0, 1), 2, 1, false).is_ok());
+        assert!(matches!(
+            two_dim.check_range(&subresource_range(2, 0, 1), 2, 1, false),
+            Err(Error::InvalidMipRange)
+        ));
+    }
+
+    #[test]
+    fn check_range_overflow() {
+        let two_dim = ImageViewType::TwoDim;
+
+        assert!(matches!(
+            two_dim.check_range(&subresource_range(u32::MAX, 0, 1), 2, 1, false),
+            Err(Error::InvalidMipRange)
+        ));
+
+        let two_dim_array = ImageViewType::TwoDimArray;
+
+        assert!(matches!(
+            two_dim_array.check_range(&subresource_range(0, u32::MAX, 2), 1, 4, false),
+            Err(Error::InvalidLayerRange)
+        ));
+    }
+
+    #[test]
+    fn check_array_range_partial() {
+        //binding 1 is an array of 4
+        let binding_counts = [(0, 1), (1, 4)];
+
+        assert!(check_array_range(&binding_counts, 1, 0, 2).is_ok());
+        assert!(check_array_range(&binding_counts, 1, 2, 2).is_ok());
+        assert!(matches!(
+            check_array_range(&binding_counts, 1, 3, 2),
+            Err(Error::OutOfBounds)
+        ));
+        assert!(matches!(
+            check_array_range(&binding_counts, 1, u32::MAX, 2),
+            Err(Error::OutOfBounds)
+        ));
+        assert!(matches!(
+            check_array_range(&binding_counts, 2, 0, 1),
+            Err(Error::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn buffer_usage_flags_round_trip() {
+        let vertex = BufferUsageFlags::VERTEX | BufferUsageFlags::TRANSFER_DST;
+        let staging = BufferUsageFlags::TRANSFER_SRC;
+        let indirect = BufferUsageFlags::INDIRECT | BufferUsageFlags::STORAGE;
+
+        assert_eq!(u32::from(vertex), BUFFER_USAGE_VERTEX | BUFFER_USAGE_TRANSFER_DST);
+        assert_eq!(u32::from(staging), BUFFER_USAGE_TRANSFER_SRC);
+        assert_eq!(u32::from(indirect), BUFFER_USAGE_INDIRECT | BUFFER_USAGE_STORAGE);
+
+        for flags in [vertex, staging, indirect, BufferUsageFlags::empty()] {
+            assert_eq!(BufferUsageFlags::from(u32::from(flags)), flags);
+        }
+
+        let mut flags = BufferUsageFlags::empty();
+
+        flags |= BufferUsageFlags::VERTEX;
+        flags |= BufferUsageFlags::TRANSFER_DST;
+
+        assert_eq!(flags, vertex);
+        assert!(flags.contains(BufferUsageFlags::VERTEX));
+        assert!(!flags.contains(BufferUsageFlags::INDEX));
+        assert_eq!(
+            format!("{:?}", indirect | BufferUsageFlags::from(0x8000)),
+            "BufferUsageFlags(STORAGE | INDIRECT | 0x8000)"
+        );
+    }
+
+    #[test]
+    fn layout_create_flags_update_after_bind() {
+        let partially_bound = DESCRIPTOR_BINDING_PARTIALLY_BOUND;
+        let update_after_bind = DESCRIPTOR_BINDING_UPDATE_AFTER_BIND;
+
+        assert_eq!(layout_create_flags(&[]), 0);
+        assert_eq!(layout_create_flags(&[0, partially_bound]), 0);
+        assert_eq!(
+            layout_create_flags(&[0, partially_bound | update_after_bind]),
+            DESCRIPTOR_SET_LAYOUT_CREATE_UPDATE_AFTER_BIND_POOL
+        );
+    }
+
+    #[test]
+    fn check_submit_counts_mismatch() {
+        let submit_info = |wait_stages| SubmitInfo {
+            wait_semaphores: &[],
+            wait_stages,
+            signal_semaphores: &[],
+            command_buffers: &[],
+        };
+
+        let values = |signal_values| TimelineSemaphoreSubmitInfo {
+            wait_values: &[],
+            signal_values,
+        };
+
+        assert!(check_submit_counts(&[submit_info(&[])], None).is_ok());
+        assert!(check_submit_counts(&[submit_info(&[])], Some(&[values(&[])])).is_ok());
+
+        //a stage mask without a semaphore to wait on
+        assert!(matches!(
+            check_submit_counts(&[submit_info(&[PIPELINE_STAGE_TRANSFER])], None),
+            Err(Error::WaitStageCountMismatch)
+        ));
+        assert!(matches!(
+            check_submit_counts(&[submit_info(&[]), submit_info(&[])], Some(&[values(&[])])),
+            Err(Error::SemaphoreValueCountMismatch)
+        ));
+        //a value without a semaphore to signal
+        assert!(matches!(
+            check_submit_counts(&[submit_info(&[])], Some(&[values(&[1])])),
+            Err(Error::SemaphoreValueCountMismatch)
+        ));
+    }
+
+    //the first device, with one queue from a family that can run compute
+    fn test_physical_device() -> (Rc<PhysicalDevice>, u32) {
+        let application_info = ApplicationInfo {
+            application_name: "vk test",
+            application_version: (0, 1, 0).into(),
+            engine_name: "vk test",
+            engine_version: (0, 1, 0).into(),
+            api_version: (1, 2, 0).into(),
+        };
+
+        let instance = Instance::new(InstanceCreateInfo {
+            application_info: &application_info,
+            extensions: &[],
+            layers: &[],
+            debug_utils: &None,
+        })
+        .unwrap();
+
+        let physical_device = PhysicalDevice::enumerate(instance)
+            .into_iter()
+            .next()
+            .expect("no vulkan device");
+
+        let queue_family_index = physical_device
+            .queue_families()
+            .iter()
+            .position(|family| family.queue_flags & QUEUE_COMPUTE != 0)
+            .unwrap() as u32;
+
+        (physical_device, queue_family_index)
+    }
+
+    fn test_device(extensions: &[&'static str]) -> (Rc<PhysicalDevice>, Rc<Device>, u32) {
+        let (physical_device, queue_family_index) = test_physical_device();
+
+        let device = Device::new(
+            &physical_device,
+            DeviceCreateInfo {
+                queues: &[DeviceQueueCreateInfo {
+                    queue_family_index,
+                    queue_priorities: &[1.0],
+                }],
+                enabled_features: &Default::default(),
+                extensions,
+                layers: &[],
+            },
+        )
+        .unwrap();
+
+        (physical_device, device, queue_family_index)
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn device_enables_sampler_anisotropy() {
+        let (physical_device, queue_family_index) = test_physical_device();
+
+        let supported = physical_device.features().sampler_anisotropy;
+
+        let device = Device::new(
+            &physical_device,
+            DeviceCreateInfo {
+                queues: &[DeviceQueueCreateInfo {
+                    queue_family_index,
+                    queue_priorities: &[1.0],
+                }],
+                enabled_features: &PhysicalDeviceFeatures {
+                    sampler_anisotropy: true,
+                    ..Default::default()
+                },
+                extensions: &[],
+                layers: &[],
+            },
+        );
+
+        match device {
+            Ok(_) => assert!(supported),
+            Err(Error::FeatureNotPresent) => assert!(!supported),
+            Err(error) => panic!("unexpected error: {:?}", error),
+        }
+    }
+
+    //`layout(set = 0, binding = 0) buffer Out { uint value; }; void main() { value = 42; }`
+    //assembled by hand since there's no shader compiler in the vk crate
+    const WRITE_42: [u32; 90] = [
+        0x07230203, 0x00010000, 0, 14, 0, //header, bound 14
+        0x00020011, 1, //OpCapability Shader
+        0x0003000e, 0, 1, //OpMemoryModel Logical GLSL450
+        0x0005000f, 5, 1, 0x6e69616d, 0, //OpEntryPoint GLCompute %1 "main"
+        0x00060010, 1, 17, 1, 1, 1, //OpExecutionMode %1 LocalSize 1 1 1
+        0x00030047, 5, 3, //OpDecorate %5 BufferBlock
+        0x00050048, 5, 0, 35, 0, //OpMemberDecorate %5 0 Offset 0
+        0x00040047, 7, 34, 0, //OpDecorate %7 DescriptorSet 0
+        0x00040047, 7, 33, 0, //OpDecorate %7 Binding 0
+        0x00020013, 2, //%2 = OpTypeVoid
+        0x00030021, 3, 2, //%3 = OpTypeFunction %2
+        0x00040015, 4, 32, 0, //%4 = OpTypeInt 32 0
+        0x0003001e, 5, 4, //%5 = OpTypeStruct %4
+        0x00040020, 6, 2, 5, //%6 = OpTypePointer Uniform %5
+        0x0004003b, 6, 7, 2, //%7 = OpVariable %6 Uniform
+        0x00040015, 8, 32, 1, //%8 = OpTypeInt 32 1
+        0x0004002b, 8, 9, 0, //%9 = OpConstant %8 0
+        0x0004002b, 4, 10, 42, //%10 = OpConstant %4 42
+        0x00040020, 11, 2, 4, //%11 = OpTypePointer Uniform %4
+        0x00050036, 2, 1, 0, 3, //%1 = OpFunction %2 None %3
+        0x000200f8, 12, //%12 = OpLabel
+        0x00050041, 11, 13, 7, 9, //%13 = OpAccessChain %11 %7 %9
+        0x0003003e, 13, 10, //OpStore %13 %10
+        0x000100fd, //OpReturn
+        0x00010038, //OpFunctionEnd
+    ];
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn compute_dispatch_writes_buffer() {
+        let (physical_device, device, queue_family_index) = test_device(&[]);
+
+        let mut queue = device.queue(queue_family_index);
+
+        let mut buffer = Buffer::new(device.clone(), 4, BufferUsageFlags::STORAGE).unwrap();
+
+        let mut memory = Memory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                property_flags: HOST,
+            },
+            buffer.memory_requirements(),
+            physical_device.memory_properties(),
+            false,
+        )
+        .unwrap();
+
+        buffer.bind_memory(&memory).unwrap();
+
+        memory.map::<u32>(0, 1).unwrap()[0] = 0;
+
+        let set_layout = DescriptorSetLayout::new(
+            device.clone(),
+            DescriptorSetLayoutCreateInfo {
+                bindings: &[DescriptorSetLayoutBinding {
+                    binding: 0,
+                    descriptor_type: DescriptorType::StorageBuffer,
+                    descriptor_count: 1,
+                    stage: SHADER_STAGE_COMPUTE,
+                    flags: 0,
+                }],
+            },
+        )
+        .unwrap();
+
+        let descriptor_pool = DescriptorPool::new(
+            device.clone(),
+            DescriptorPoolCreateInfo {
+                flags: 0,
+                max_sets: 1,
+                pool_sizes: &[DescriptorPoolSize {
+                    descriptor_type: DescriptorType::StorageBuffer,
+                    descriptor_count: 1,
+                }],
+            },
+        )
+        .unwrap();
+
+        let descriptor_set = DescriptorSet::allocate(
+            device.clone(),
+            DescriptorSetAllocateInfo {
+                descriptor_pool: &descriptor_pool,
+                set_layouts: &[&set_layout],
+            },
+        )
+        .unwrap()
+        .remove(0);
+
+        DescriptorSet::update(
+            &[WriteDescriptorSet {
+                dst_set: &descriptor_set,
+                dst_binding: 0,
+                dst_array_element: 0,
+                descriptor_count: 1,
+                descriptor_type: DescriptorType::StorageBuffer,
+                buffer_infos: &[DescriptorBufferInfo {
+                    buffer: &buffer,
+                    offset: 0,
+                    range: 4,
+                }],
+                image_infos: &[],
+                texel_buffer_views: &[],
+            }],
+            &[],
+        )
+        .unwrap();
+
+        let module =
+            ShaderModule::new(device.clone(), ShaderModuleCreateInfo { code: &WRITE_42 }).unwrap();
+
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineLayoutCreateInfo {
+                set_layouts: &[&set_layout],
+            },
+        )
+        .unwrap();
+
+        let pipeline = Pipeline::new_compute_pipelines(
+            device.clone(),
+            None,
+            &[ComputePipelineCreateInfo {
+                flags: 0,
+                stage: PipelineShaderStageCreateInfo {
+                    stage: SHADER_STAGE_COMPUTE,
+                    module: &module,
+                    entry_point: "main",
+                },
+                layout: &layout,
+                base_pipeline: None,
+                base_pipeline_index: -1,
+            }],
+        )
+        .unwrap()
+        .remove(0);
+
+        let command_pool =
+            CommandPool::new(device.clone(), CommandPoolCreateInfo { queue_family_index }).unwrap();
+
+        device
+            .one_time_submit(&mut queue, &command_pool, |mut commands| {
+                commands.bind_pipeline(PipelineBindPoint::Compute, &pipeline);
+                commands.bind_descriptor_sets(
+                    PipelineBindPoint::Compute,
+                    &layout,
+                    0,
+                    &[&descriptor_set],
+                    &[],
+                );
+                commands.dispatch(1, 1, 1);
+                commands.pipeline_barrier(
+                    PIPELINE_STAGE_COMPUTE_SHADER,
+                    PIPELINE_STAGE_HOST,
+                    0,
+                    &[MemoryBarrier {
+                        src_access_mask: ACCESS_SHADER_WRITE,
+                        dst_access_mask: ACCESS_HOST_READ,
+                    }],
+                    &[],
+                    &[],
+                );
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(memory.map::<u32>(0, 1).unwrap()[0], 42);
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn event_host_set_and_reset() {
+        let (_, device, _) = test_device(&[]);
+
+        let mut event = Event::new(device, EventCreateInfo {}).unwrap();
+
+        assert!(!event.status().unwrap());
+
+        event.set().unwrap();
+
+        assert!(event.status().unwrap());
+
+        event.reset().unwrap();
+
+        assert!(!event.status().unwrap());
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn image_view_of_each_mip() {
+        let (physical_device, device, _) = test_device(&[]);
+
+        let mut image = Image::new(
+            device.clone(),
+            ImageCreateInfo {
+                flags: 0,
+                image_type: ImageType::TwoDim,
+                format: Format::Rgba8Srgb,
+                extent: (64, 64, 1),
+                mip_levels: 2,
+                array_layers: 1,
+                samples: 1,
+                tiling: ImageTiling::Optimal,
+                image_usage: IMAGE_USAGE_SAMPLED | IMAGE_USAGE_COLOR_ATTACHMENT,
+                initial_layout: ImageLayout::Undefined,
+            },
+        )
+        .unwrap();
+
+        let memory = Memory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                property_flags: MEMORY_PROPERTY_DEVICE_LOCAL,
+            },
+            image.memory_requirements(),
+            physical_device.memory_properties(),
+            false,
+        )
+        .unwrap();
+
+        image.bind_memory(&memory).unwrap();
+
+        for mip_level in 0..2 {
+            ImageView::of_mip(device.clone(), &image, Format::Rgba8Srgb, mip_level).unwrap();
+        }
+
+        assert!(matches!(
+            ImageView::of_mip(device.clone(), &image, Format::Rgba8Srgb, 2),
+            Err(Error::InvalidMipRange)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn descriptor_set_partial_array_write() {
+        let (physical_device, device, _) = test_device(&[]);
+
+        let mut buffer = Buffer::new(device.clone(), 4, BufferUsageFlags::STORAGE).unwrap();
+
+        let memory = Memory::allocate(
+            device.clone(),
+            MemoryAllocateInfo {
+                property_flags: MEMORY_PROPERTY_DEVICE_LOCAL,
+            },
+            buffer.memory_requirements(),
+            physical_device.memory_properties(),
+            false,
+        )
+        .unwrap();
+
+        buffer.bind_memory(&memory).unwrap();
+
+        let set_layout = DescriptorSetLayout::new(
+            device.clone(),
+            DescriptorSetLayoutCreateInfo {
+                bindings: &[DescriptorSetLayoutBinding {
+                    binding: 0,
+                    descriptor_type: DescriptorType::StorageBuffer,
+                    descriptor_count: 4,
+                    stage: SHADER_STAGE_COMPUTE,
+                    flags: 0,
+                }],
+            },
+        )
+        .unwrap();
+
+        let descriptor_pool = DescriptorPool::new(
+            device.clone(),
+            DescriptorPoolCreateInfo {
+                flags: 0,
+                max_sets: 1,
+                pool_sizes: &[DescriptorPoolSize {
+                    descriptor_type: DescriptorType::StorageBuffer,
+                    descriptor_count: 4,
+                }],
+            },
+        )
+        .unwrap();
+
+        let descriptor_set = DescriptorSet::allocate(
+            device.clone(),
+            DescriptorSetAllocateInfo {
+                descriptor_pool: &descriptor_pool,
+                set_layouts: &[&set_layout],
+            },
+        )
+        .unwrap()
+        .remove(0);
+
+        let buffer_info = || DescriptorBufferInfo {
+            buffer: &buffer,
+            offset: 0,
+            range: 4,
+        };
+
+        let buffer_infos = [buffer_info(), buffer_info()];
+
+        let write = |dst_array_element| WriteDescriptorSet {
+            dst_set: &descriptor_set,
+            dst_binding: 0,
+            dst_array_element,
+            descriptor_count: 2,
+            descriptor_type: DescriptorType::StorageBuffer,
+            buffer_infos: &buffer_infos,
+            image_infos: &[],
+            texel_buffer_views: &[],
+        };
+
+        DescriptorSet::update(&[write(1)], &[]).unwrap();
+
+        assert!(matches!(
+            DescriptorSet::update(&[write(3)], &[]),
+            Err(Error::OutOfBounds)
+        ));
+    }
+
+    //`void main() {}` as a vertex and as a fragment shader, which needs an origin mode
+    const EMPTY_VERTEX: [u32; 29] = [
+        0x07230203, 0x00010000, 0, 5, 0, //header, bound 5
+        0x00020011, 1, //OpCapability Shader
+        0x0003000e, 0, 1, //OpMemoryModel Logical GLSL450
+        0x0005000f, 0, 1, 0x6e69616d, 0, //OpEntryPoint Vertex %1 "main"
+        0x00020013, 2, //%2 = OpTypeVoid
+        0x00030021, 3, 2, //%3 = OpTypeFunction %2
+        0x00050036, 2, 1, 0, 3, //%1 = OpFunction %2 None %3
+        0x000200f8, 4, //%4 = OpLabel
+        0x000100fd, //OpReturn
+        0x00010038, //OpFunctionEnd
+    ];
+
+    const EMPTY_FRAGMENT: [u32; 32] = [
+        0x07230203, 0x00010000, 0, 5, 0, //header, bound 5
+        0x00020011, 1, //OpCapability Shader
+        0x0003000e, 0, 1, //OpMemoryModel Logical GLSL450
+        0x0005000f, 4, 1, 0x6e69616d, 0, //OpEntryPoint Fragment %1 "main"
+        0x00030010, 1, 7, //OpExecutionMode %1 OriginUpperLeft
+        0x00020013, 2, //%2 = OpTypeVoid
+        0x00030021, 3, 2, //%3 = OpTypeFunction %2
+        0x00050036, 2, 1, 0, 3, //%1 = OpFunction %2 None %3
+        0x000200f8, 4, //%4 = OpLabel
+        0x000100fd, //OpReturn
+        0x00010038, //OpFunctionEnd
+    ];
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn pipeline_blends_each_color_attachment() {
+        let (_, device, _) = test_device(&[]);
+
+        let attachment = || AttachmentDescription {
+            format: Format::Rgba8Srgb,
+            samples: 1,
+            load_op: AttachmentLoadOp::Clear,
+            store_op: AttachmentStoreOp::Store,
+            stencil_load_op: AttachmentLoadOp::DontCare,
+            stencil_store_op: AttachmentStoreOp::DontCare,
+            initial_layout: ImageLayout::Undefined,
+            final_layout: ImageLayout::ColorAttachment,
+        };
+
+        let color_attachments = [0, 1].map(|attachment| AttachmentReference {
+            attachment,
+            layout: ImageLayout::ColorAttachment,
+        });
+
+        let render_pass = RenderPass::new(
+            device.clone(),
+            RenderPassCreateInfo {
+                attachments: &[attachment(), attachment()],
+                subpasses: &[SubpassDescription {
+                    pipeline_bind_point: PipelineBindPoint::Graphics,
+                    input_attachments: &[],
+                    color_attachments: &color_attachments,
+                    resolve_attachments: &[],
+                    depth_stencil_attachment: None,
+                    preserve_attachments: &[],
+                }],
+                dependencies: &[],
+            },
+        )
+        .unwrap();
+
+        assert_eq!(render_pass.color_attachment_count(0), Some(2));
+        assert_eq!(render_pass.color_attachment_count(1), None);
+
+        let vertex = ShaderModule::new(
+            device.clone(),
+            ShaderModuleCreateInfo {
+                code: &EMPTY_VERTEX,
+            },
+        )
+        .unwrap();
+
+        let fragment = ShaderModule::new(
+            device.clone(),
+            ShaderModuleCreateInfo {
+                code: &EMPTY_FRAGMENT,
+            },
+        )
+        .unwrap();
+
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineLayoutCreateInfo { set_layouts: &[] },
+        )
+        .unwrap();
+
+        let blend_attachment = || PipelineColorBlendAttachmentState {
+            color_write_mask: COLOR_COMPONENT_R
+                | COLOR_COMPONENT_G
+                | COLOR_COMPONENT_B
+                | COLOR_COMPONENT_A,
+            blend_enable: false,
+            src_color_blend_factor: BlendFactor::One,
+            dst_color_blend_factor: BlendFactor::Zero,
+            color_blend_op: BlendOp::Add,
+            src_alpha_blend_factor: BlendFactor::One,
+            dst_alpha_blend_factor: BlendFactor::Zero,
+            alpha_blend_op: BlendOp::Add,
+        };
+
+        let new_pipeline = |attachments: &[PipelineColorBlendAttachmentState]| {
+            Pipeline::new_graphics_pipelines(
+                device.clone(),
+                None,
+                &[GraphicsPipelineCreateInfo {
+                    flags: 0,
+                    stages: &[
+                        PipelineShaderStageCreateInfo {
+                            stage: SHADER_STAGE_VERTEX,
+                            module: &vertex,
+                            entry_point: "main",
+                        },
+                        PipelineShaderStageCreateInfo {
+                            stage: SHADER_STAGE_FRAGMENT,
+                            module: &fragment,
+                            entry_point: "main",
+                        },
+                    ],
+                    vertex_input_state: &PipelineVertexInputStateCreateInfo {
+                        bindings: &[],
+                        attributes: &[],
+                    },
+                    input_assembly_state: &PipelineInputAssemblyStateCreateInfo {
+                        topology: PrimitiveTopology::TriangleList,
+                        primitive_restart_enable: false,
+                    },
+                    tessellation_state: &PipelineTessellationStateCreateInfo {},
+                    viewport_state: &PipelineViewportStateCreateInfo {
+                        viewports: &[Viewport {
+                            x: 0.0,
+                            y: 0.0,
+                            width: 64.0,
+                            height: 64.0,
+                            min_depth: 0.0,
+                            max_depth: 1.0,
+                        }],
+                        scissors: &[Rect2d {
+                            offset: (0, 0),
+                            extent: (64, 64),
+                        }],
+                    },
+                    rasterization_state: &PipelineRasterizationStateCreateInfo {
+                        depth_clamp_enable: false,
+                        rasterizer_discard_enable: false,
+                        polygon_mode: PolygonMode::Fill,
+                        cull_mode: CullMode::None,
+                        front_face: FrontFace::CounterClockwise,
+                        depth_bias_enable: false,
+                        depth_bias_constant_factor: 0.0,
+                        depth_bias_clamp: 0.0,
+                        depth_bias_slope_factor: 0.0,
+                        line_width: 1.0,
+                    },
+                    multisample_state: &PipelineMultisampleStateCreateInfo {},
+                    depth_stencil_state: &PipelineDepthStencilStateCreateInfo {
+                        depth_test_enable: false,
+                        depth_write_enable: false,
+                        depth_compare_op: CompareOp::Always,
+                        depth_bounds_test_enable: false,
+                        min_depth_bounds: 0.0,
+                        max_depth_bounds: 1.0,
+                    },
+                    color_blend_state: &PipelineColorBlendStateCreateInfo {
+                        logic_op_enable: false,
+                        logic_op: LogicOp::Copy,
+                        attachments,
+                        blend_constants: &[0.0; 4],
+                    },
+                    dynamic_state: &PipelineDynamicStateCreateInfo {
+                        dynamic_states: &[],
+                    },
+                    layout: &layout,
+                    render_pass: Some(&render_pass),
+                    rendering: None,
+                    subpass: 0,
+                    base_pipeline: None,
+                    base_pipeline_index: -1,
+                }],
+            )
+        };
+
+        new_pipeline(&[blend_attachment(), blend_attachment()]).unwrap();
+
+        assert!(matches!(
+            new_pipeline(&[blend_attachment()]),
+            Err(Error::AttachmentCountMismatch)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn partially_bound_array_layout() {
+        let (_, device, _) = test_device(&[EXT_DESCRIPTOR_INDEXING]);
+
+        let bindings = [DescriptorSetLayoutBinding {
+            binding: 0,
+            descriptor_type: DescriptorType::CombinedImageSampler,
+            descriptor_count: 16,
+            stage: SHADER_STAGE_FRAGMENT,
+            flags: DESCRIPTOR_BINDING_PARTIALLY_BOUND | DESCRIPTOR_BINDING_UPDATE_AFTER_BIND,
+        }];
+
+        let create_info = || DescriptorSetLayoutCreateInfo {
+            bindings: &bindings,
+        };
+
+        DescriptorSetLayout::new(device, create_info()).unwrap();
+
+        //binding flags are refused without the extension
+        let (_, device, _) = test_device(&[]);
+
+        assert!(matches!(
+            DescriptorSetLayout::new(device, create_info()),
+            Err(Error::ExtensionNotPresent)
+        ));
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn set_depth_bias_records() {
+        let (_, device, queue_family_index) = test_device(&[]);
+
+        let command_pool =
+            CommandPool::new(device.clone(), CommandPoolCreateInfo { queue_family_index }).unwrap();
+
+        let mut command_buffer = CommandBuffer::allocate(
+            device,
+            CommandBufferAllocateInfo {
+                command_pool: &command_pool,
+                level: CommandBufferLevel::Primary,
+                count: 1,
+            },
+        )
+        .unwrap()
+        .remove(0);
+
+        //never submitted, so there is no pipeline the bias would apply to
+        command_buffer
+            .record(|mut commands| commands.set_depth_bias(1.25, 0.0, 1.75))
+            .unwrap();
+
+        CommandBuffer::free(&command_pool, vec![command_buffer]);
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn command_buffers_freed_before_pool() {
+        let (_, device, queue_family_index) = test_device(&[]);
+
+        let mut command_pool =
+            CommandPool::new(device.clone(), CommandPoolCreateInfo { queue_family_index }).unwrap();
+
+        let allocate = |command_pool: &CommandPool, count| {
+            CommandBuffer::allocate(
+                device.clone(),
+                CommandBufferAllocateInfo {
+                    command_pool,
+                    level: CommandBufferLevel::Primary,
+                    count,
+                },
+            )
+            .unwrap()
+        };
+
+        let mut command_buffers = allocate(&command_pool, 4);
+
+        let kept = command_buffers.split_off(2);
+
+        CommandBuffer::free(&command_pool, command_buffers);
+        CommandBuffer::free(&command_pool, vec![]);
+
+        //the pool keeps handing out buffers after some were freed
+        let command_buffers = allocate(&command_pool, 2);
+
+        CommandBuffer::free(&command_pool, command_buffers);
+        CommandBuffer::free(&command_pool, kept);
+
+        command_pool.trim();
+
+        CommandBuffer::free(&command_pool, allocate(&command_pool, 1));
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn shadow_sampler_compares() {
+        let (_, device, _) = test_device(&[]);
+
+        Sampler::new(
+            device,
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                mipmap_mode: SamplerMipmapMode::Nearest,
+                address_mode_u: SamplerAddressMode::ClampToBorder,
+                address_mode_v: SamplerAddressMode::ClampToBorder,
+                address_mode_w: SamplerAddressMode::ClampToBorder,
+                mip_lod_bias: 0.0,
+                anisotropy_enable: false,
+                max_anisotropy: 1.0,
+                compare_enable: true,
+                compare_op: CompareOp::Less,
+                min_lod: 0.0,
+                max_lod: 0.0,
+                //everything outside the shadow map counts as lit
+                border_color: BorderColor::FloatOpaqueWhite,
+                unnormalized_coordinates: false,
+                reduction_mode: None,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn image_memory_requirements_nonzero() {
+        let (physical_device, device, _) = test_device(&[]);
+
+        let image = Image::new(
+            device,
+            ImageCreateInfo {
+                flags: 0,
+                image_type: ImageType::TwoDim,
+                format: Format::D32Sfloat,
+                extent: (256, 256, 1),
+                mip_levels: 1,
+                array_layers: 1,
+                samples: 1,
+                tiling: ImageTiling::Optimal,
+                image_usage: IMAGE_USAGE_DEPTH_STENCIL_ATTACHMENT | IMAGE_USAGE_SAMPLED,
+                initial_layout: ImageLayout::Undefined,
+            },
+        )
+        .unwrap();
+
+        let requirements = image.memory_requirements();
+
+        //at least one 4 byte texel per pixel
+        assert!(requirements.size() >= 256 * 256 * 4);
+        assert!(requirements.alignment().is_power_of_two());
+        assert_ne!(requirements.memory_type_bits(), 0);
+
+        let memory_type = physical_device
+            .memory_properties()
+            .find_memory_type(requirements.memory_type_bits(), MEMORY_PROPERTY_DEVICE_LOCAL);
+
+        assert!(memory_type.is_some());
+    }
+
+    #[test]
+    #[ignore = "needs a vulkan device"]
+    fn submit_waits_on_binary_semaphore() {
+        let (_, device, queue_family_index) = test_device(&[]);
+
+        let mut queue = device.queue(queue_family_index);
+
+        let mut semaphore = Semaphore::new(device.clone(), SemaphoreCreateInfo {}).unwrap();
+
+        let mut fence = Fence::new(device, FenceCreateInfo {}).unwrap();
+
+        //fences are created signaled
+        Fence::reset(&[&mut fence]).unwrap();
+
+        queue
+            .submit(
+                &[SubmitInfo {
+                    wait_semaphores: &[],
+                    wait_stages: &[],
+                    signal_semaphores: &[&mut semaphore],
+                    command_buffers: &[],
+                }],
+                None,
+            )
+            .unwrap();
+
+        queue
+            .submit(
+                &[SubmitInfo {
+                    wait_semaphores: &[&semaphore],
+                    wait_stages: &[PIPELINE_STAGE_ALL_COMMANDS],
+                    signal_semaphores: &[],
+                    command_buffers: &[],
+                }],
+                Some(&mut fence),
+            )
+            .unwrap();
+
+        Fence::wait(&[&mut fence], true, u64::MAX).unwrap();
+    }
+}
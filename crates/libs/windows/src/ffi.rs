@@ -73,11 +73,13 @@ extern "system" {
     pub fn GetClientRect(hwnd: Hwnd, rect: *mut Rect) -> Bool;
     pub fn SetCursorPos(x: i32, y: i32) -> Bool;
     pub fn PeekMessageA(msg: *mut Msg, hwnd: Hwnd, msg_filter_min: u32, msg_filter_max: u32, remove_msg: u32) -> Bool;
+    pub fn WaitMessage() -> Bool;
     pub fn TranslateMessage(msg: *const Msg) -> Bool;
     pub fn DispatchMessageW(msg: *const Msg) -> LResult;
     pub fn SetCapture(hwnd: Hwnd) -> Hwnd;
     pub fn ReleaseCapture() -> Bool;
     pub fn ClientToScreen(hwnd: Hwnd, point: *mut Point) -> Bool;
+    pub fn GetDpiForWindow(hwnd: Hwnd) -> u32;
 }
 
 #[repr(C)]
@@ -21,6 +21,8 @@ const KILL_FOCUS: u32 = 0x0008;
 const SET_FOCUS: u32 = 0x0007;
 const SIZE: u32 = 0x0005;
 
+const SIZE_MINIMIZED: usize = 1;
+
 #[derive(Debug, Clone, Copy)]
 pub enum Keycode {
     W,
@@ -30,12 +32,77 @@ pub enum Keycode {
     Space,
     LeftShift,
     Escape,
+    F11,
+    B,
+    C,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    T,
+    U,
+    V,
+    X,
+    Y,
+    Z,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    CapsLock,
+    RightShift,
+    LeftControl,
+    RightControl,
+    LeftAlt,
+    RightAlt,
+    LeftSuper,
+    RightSuper,
+    Unknown(u32),
 }
 
-impl TryFrom<usize> for Keycode {
-    type Error = ();
-    fn try_from(bits: usize) -> Result<Keycode, Self::Error> {
-        Ok(match bits {
+impl From<usize> for Keycode {
+    fn from(bits: usize) -> Keycode {
+        match bits {
             0x57 => Keycode::W,
             0x41 => Keycode::A,
             0x53 => Keycode::S,
@@ -43,8 +110,73 @@ impl TryFrom<usize> for Keycode {
             0x20 => Keycode::Space,
             0x10 => Keycode::LeftShift,
             0x1B => Keycode::Escape,
-            _ => Err(())?,
-        })
+            0x7A => Keycode::F11,
+            0x42 => Keycode::B,
+            0x43 => Keycode::C,
+            0x45 => Keycode::E,
+            0x46 => Keycode::F,
+            0x47 => Keycode::G,
+            0x48 => Keycode::H,
+            0x49 => Keycode::I,
+            0x4A => Keycode::J,
+            0x4B => Keycode::K,
+            0x4C => Keycode::L,
+            0x4D => Keycode::M,
+            0x4E => Keycode::N,
+            0x4F => Keycode::O,
+            0x50 => Keycode::P,
+            0x51 => Keycode::Q,
+            0x52 => Keycode::R,
+            0x54 => Keycode::T,
+            0x55 => Keycode::U,
+            0x56 => Keycode::V,
+            0x58 => Keycode::X,
+            0x59 => Keycode::Y,
+            0x5A => Keycode::Z,
+            0x30 => Keycode::Key0,
+            0x31 => Keycode::Key1,
+            0x32 => Keycode::Key2,
+            0x33 => Keycode::Key3,
+            0x34 => Keycode::Key4,
+            0x35 => Keycode::Key5,
+            0x36 => Keycode::Key6,
+            0x37 => Keycode::Key7,
+            0x38 => Keycode::Key8,
+            0x39 => Keycode::Key9,
+            0x70 => Keycode::F1,
+            0x71 => Keycode::F2,
+            0x72 => Keycode::F3,
+            0x73 => Keycode::F4,
+            0x74 => Keycode::F5,
+            0x75 => Keycode::F6,
+            0x76 => Keycode::F7,
+            0x77 => Keycode::F8,
+            0x78 => Keycode::F9,
+            0x79 => Keycode::F10,
+            0x7B => Keycode::F12,
+            0x26 => Keycode::Up,
+            0x28 => Keycode::Down,
+            0x25 => Keycode::Left,
+            0x27 => Keycode::Right,
+            0x0D => Keycode::Enter,
+            0x09 => Keycode::Tab,
+            0x08 => Keycode::Backspace,
+            0x2E => Keycode::Delete,
+            0x2D => Keycode::Insert,
+            0x24 => Keycode::Home,
+            0x23 => Keycode::End,
+            0x21 => Keycode::PageUp,
+            0x22 => Keycode::PageDown,
+            0x14 => Keycode::CapsLock,
+            0xA1 => Keycode::RightShift,
+            0x11 => Keycode::LeftControl,
+            0xA3 => Keycode::RightControl,
+            0x12 => Keycode::LeftAlt,
+            0xA5 => Keycode::RightAlt,
+            0x5B => Keycode::LeftSuper,
+            0x5C => Keycode::RightSuper,
+            _ => Keycode::Unknown(bits as _),
+        }
     }
 }
 
@@ -57,6 +189,7 @@ pub enum Event {
     FocusIn,
     FocusOut,
     Resized { resolution: (u32, u32) },
+    Minimized,
 }
 
 pub extern "system" fn wnd_proc(
@@ -75,12 +208,12 @@ pub extern "system" fn wnd_proc(
         },
         KEY_DOWN => {
             Event::KeyPress {
-                keycode: Keycode::try_from(w_param as usize).ok()?
+                keycode: Keycode::from(w_param as usize)
             }
         },
         KEY_UP => {
             Event::KeyRelease {
-                keycode: Keycode::try_from(w_param as usize).ok()?
+                keycode: Keycode::from(w_param as usize)
             }
         },
         MOUSE_MOVE => {
@@ -97,6 +230,9 @@ pub extern "system" fn wnd_proc(
         KILL_FOCUS => {
             Event::FocusOut
         },
+        SIZE if w_param as usize == SIZE_MINIMIZED => {
+            Event::Minimized
+        },
         SIZE => {
             Event::Resized {
                 resolution: (
@@ -369,6 +505,11 @@ pub fn peek_message(hwnd: Hwnd, remove: bool) -> Option<Msg> {
     }
 }
 
+/// Blocks until a message is posted to the calling thread's queue.
+pub fn wait_message() {
+    unsafe { ffi::WaitMessage() };
+}
+
 pub fn translate_message(msg: &'_ Msg) {
     unsafe { ffi::TranslateMessage(msg as *const _ as _) };
 }
@@ -385,6 +526,10 @@ pub fn release_capture() {
     unsafe { ffi::ReleaseCapture() };
 }
 
+pub fn get_dpi_for_window(hwnd: Hwnd) -> u32 {
+    unsafe { ffi::GetDpiForWindow(hwnd as _) }
+}
+
 pub fn client_to_screen(hwnd: Hwnd, mut pt: Point) -> Point {
     unsafe { ffi::ClientToScreen(hwnd as _, &mut pt as *mut _ as _) };
     pt
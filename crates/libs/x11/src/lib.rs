@@ -1,8 +1,8 @@
 use std::ffi::CString;
-use std::mem::MaybeUninit;
+use std::mem::{self, MaybeUninit};
 
 mod ffi {
-    pub use libc::{c_char, c_int, c_long, c_uchar, c_uint, c_ulong};
+    pub use libc::{c_char, c_double, c_int, c_long, c_short, c_uchar, c_uint, c_ulong, c_void};
 
     type XID = c_ulong;
     type Window = XID;
@@ -13,18 +13,46 @@ mod ffi {
 
     pub const KEY_PRESS: c_int = 2;
     pub const KEY_RELEASE: c_int = 3;
+    pub const BUTTON_PRESS: c_int = 4;
+    pub const BUTTON_RELEASE: c_int = 5;
     pub const MOTION_NOTIFY: c_int = 6;
     pub const FOCUS_IN: c_int = 9;
     pub const FOCUS_OUT: c_int = 10;
     pub const EXPOSE: c_int = 12;
+    pub const UNMAP_NOTIFY: c_int = 18;
     pub const MAP_NOTIFY: c_int = 19;
     pub const REPARENT_NOTIFY: c_int = 21;
     pub const CONFIGURE_NOTIFY: c_int = 22;
+    pub const SELECTION_CLEAR: c_int = 29;
+    pub const SELECTION_REQUEST: c_int = 30;
+    pub const SELECTION_NOTIFY: c_int = 31;
     pub const CLIENT_MESSAGE: c_int = 33;
+    pub const GENERIC_EVENT: c_int = 35;
+
+    pub const CURRENT_TIME: Time = 0;
+    pub const ANY_PROPERTY_TYPE: Atom = 0;
+
+    pub const P_MIN_SIZE: c_long = 1 << 4;
+    pub const P_MAX_SIZE: c_long = 1 << 5;
+
+    pub const XIM_PREEDIT_NOTHING: c_long = 0x0008;
+    pub const XIM_STATUS_NOTHING: c_long = 0x0400;
+
+    pub const X_BUFFER_OVERFLOW: c_int = -1;
+
+    pub const XI_ALL_MASTER_DEVICES: c_int = 1;
+    pub const XI_RAW_MOTION: c_int = 17;
+    pub const XI_LAST_EVENT: c_int = 26;
 
     #[derive(Clone, Copy)]
     pub enum Display {}
 
+    pub enum XimRec {}
+    pub enum XicRec {}
+
+    pub type Xim = *mut XimRec;
+    pub type Xic = *mut XicRec;
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct KeyEvent {
@@ -45,6 +73,26 @@ mod ffi {
         pub same_screen: Bool,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct ButtonEvent {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub window: Window,
+        pub root: Window,
+        pub subwindow: Window,
+        pub time: Time,
+        pub x: c_int,
+        pub y: c_int,
+        pub x_root: c_int,
+        pub y_root: c_int,
+        pub state: c_uint,
+        pub button: c_uint,
+        pub same_screen: Bool,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub struct MotionEvent {
@@ -122,16 +170,137 @@ mod ffi {
         pub override_redirect: Bool,
     }
 
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SelectionRequestEvent {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub owner: Window,
+        pub requestor: Window,
+        pub selection: Atom,
+        pub target: Atom,
+        pub property: Atom,
+        pub time: Time,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SelectionEvent {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub requestor: Window,
+        pub selection: Atom,
+        pub target: Atom,
+        pub property: Atom,
+        pub time: Time,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SelectionClearEvent {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub window: Window,
+        pub selection: Atom,
+        pub time: Time,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct AspectRatio {
+        pub x: c_int,
+        pub y: c_int,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct SizeHints {
+        pub flags: c_long,
+        pub x: c_int,
+        pub y: c_int,
+        pub width: c_int,
+        pub height: c_int,
+        pub min_width: c_int,
+        pub min_height: c_int,
+        pub max_width: c_int,
+        pub max_height: c_int,
+        pub width_inc: c_int,
+        pub height_inc: c_int,
+        pub min_aspect: AspectRatio,
+        pub max_aspect: AspectRatio,
+        pub base_width: c_int,
+        pub base_height: c_int,
+        pub win_gravity: c_int,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct GenericEventCookie {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub extension: c_int,
+        pub evtype: c_int,
+        pub cookie: c_uint,
+        pub data: *mut c_void,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct XIValuatorState {
+        pub mask_len: c_int,
+        pub mask: *mut c_uchar,
+        pub values: *mut c_double,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct XIRawEvent {
+        pub ty: c_int,
+        pub serial: c_ulong,
+        pub send_event: Bool,
+        pub display: *mut Display,
+        pub extension: c_int,
+        pub evtype: c_int,
+        pub time: Time,
+        pub deviceid: c_int,
+        pub sourceid: c_int,
+        pub detail: c_int,
+        pub flags: c_int,
+        pub valuators: XIValuatorState,
+        pub raw_values: *mut c_double,
+    }
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    pub struct XIEventMask {
+        pub deviceid: c_int,
+        pub mask_len: c_int,
+        pub mask: *mut c_uchar,
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     pub union Event {
         pub ty: c_int,
         pub key: KeyEvent,
+        pub button: ButtonEvent,
         pub motion: MotionEvent,
         pub focus: FocusEvent,
         pub expose: ExposeEvent,
         pub client_message: ClientMessageEvent,
         pub configure: ConfigureEvent,
+        pub selection_request: SelectionRequestEvent,
+        pub selection: SelectionEvent,
+        pub selection_clear: SelectionClearEvent,
+        pub cookie: GenericEventCookie,
         //this is a hack because event is not the right size...
         //not all implemented
         //TODO
@@ -160,6 +329,8 @@ mod ffi {
         pub fn XWhitePixel(display: *mut Display, screen_number: c_int) -> c_ulong;
         pub fn XSelectInput(display: *mut Display, window: Window, event_mask: c_long) -> c_int;
         pub fn XMapWindow(display: *mut Display, window: Window);
+        pub fn XResizeWindow(display: *mut Display, window: Window, width: c_uint, height: c_uint);
+        pub fn XSetWMNormalHints(display: *mut Display, window: Window, hints: *const SizeHints);
         pub fn XUnmapWindow(display: *mut Display, window: Window);
         pub fn XNextEvent(display: *mut Display, event: *mut Event);
         pub fn XSendEvent(
@@ -183,6 +354,7 @@ mod ffi {
         ) -> Bool;
         pub fn XStoreName(display: *mut Display, window: Window, window_name: *const c_char);
         pub fn XPending(display: *mut Display) -> c_int;
+        pub fn XResourceManagerString(display: *mut Display) -> *const c_char;
         pub fn XWarpPointer(
             display: *mut Display,
             src_w: Window,
@@ -205,6 +377,79 @@ mod ffi {
             data: *const c_uchar,
             nelements: c_int,
         );
+        pub fn XQueryExtension(
+            display: *mut Display,
+            name: *const c_char,
+            major_opcode: *mut c_int,
+            first_event: *mut c_int,
+            first_error: *mut c_int,
+        ) -> Bool;
+        pub fn XSetSelectionOwner(
+            display: *mut Display,
+            selection: Atom,
+            owner: Window,
+            time: Time,
+        );
+        pub fn XGetSelectionOwner(display: *mut Display, selection: Atom) -> Window;
+        pub fn XConvertSelection(
+            display: *mut Display,
+            selection: Atom,
+            target: Atom,
+            property: Atom,
+            requestor: Window,
+            time: Time,
+        );
+        pub fn XGetWindowProperty(
+            display: *mut Display,
+            window: Window,
+            property: Atom,
+            long_offset: c_long,
+            long_length: c_long,
+            delete: Bool,
+            req_type: Atom,
+            actual_type_return: *mut Atom,
+            actual_format_return: *mut c_int,
+            nitems_return: *mut c_ulong,
+            bytes_after_return: *mut c_ulong,
+            prop_return: *mut *mut c_uchar,
+        ) -> c_int;
+        pub fn XFree(data: *mut c_void);
+        pub fn XSetLocaleModifiers(modifier_list: *const c_char) -> *mut c_char;
+        pub fn XOpenIM(
+            display: *mut Display,
+            db: *mut c_void,
+            res_name: *mut c_char,
+            res_class: *mut c_char,
+        ) -> Xim;
+        pub fn XCreateIC(im: Xim, ...) -> Xic;
+        pub fn XSetICFocus(ic: Xic);
+        pub fn XUnsetICFocus(ic: Xic);
+        pub fn XFilterEvent(event: *mut Event, window: Window) -> Bool;
+        pub fn Xutf8LookupString(
+            ic: Xic,
+            event: *mut KeyEvent,
+            buffer_return: *mut c_char,
+            bytes_buffer: c_int,
+            keysym_return: *mut c_ulong,
+            status_return: *mut c_int,
+        ) -> c_int;
+        pub fn XGetEventData(display: *mut Display, cookie: *mut GenericEventCookie) -> Bool;
+        pub fn XFreeEventData(display: *mut Display, cookie: *mut GenericEventCookie);
+    }
+    #[link(name = "Xi")]
+    #[allow(non_snake_case)]
+    extern "C" {
+        pub fn XIQueryVersion(
+            display: *mut Display,
+            major_version: *mut c_int,
+            minor_version: *mut c_int,
+        ) -> Status;
+        pub fn XISelectEvents(
+            display: *mut Display,
+            window: Window,
+            masks: *mut XIEventMask,
+            num_masks: c_int,
+        ) -> Status;
     }
     #[link(name = "Xfixes")]
     #[allow(non_snake_case)]
@@ -216,6 +461,8 @@ mod ffi {
 
 pub const KEY_PRESS_MASK: i64 = 0x0000_0001;
 pub const KEY_RELEASE_MASK: i64 = 0x0000_0002;
+pub const BUTTON_PRESS_MASK: i64 = 0x0000_0004;
+pub const BUTTON_RELEASE_MASK: i64 = 0x0000_0008;
 pub const POINTER_MOTION_MASK: i64 = 0x0000_0040;
 pub const EXPOSURE_MASK: i64 = 0x0000_8000;
 pub const STRUCTURE_NOTIFY_MASK: i64 = 0x0002_0000;
@@ -224,6 +471,7 @@ pub const SUBSTRUCTURE_NOTIFY_MASK: i64 = 0x0008_0000;
 pub const SUBSTRUCTURE_REDIRECT_MASK: i64 = 0x0010_0000;
 
 pub const XA_ATOM: Atom = 4;
+pub const XA_STRING: Atom = 31;
 
 pub const PROP_MODE_REPLACE: i32 = 0;
 
@@ -231,6 +479,7 @@ pub type Display = *mut ffi::Display;
 pub type Screen = i32;
 pub type Window = u64;
 pub type Atom = u64;
+pub type InputContext = ffi::Xic;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Event {
@@ -243,10 +492,24 @@ pub enum Event {
         serial: u64,
         keycode: Keycode,
     },
+    ButtonPress {
+        x: i32,
+        y: i32,
+        button: u32,
+    },
+    ButtonRelease {
+        x: i32,
+        y: i32,
+        button: u32,
+    },
     MotionNotify {
         x: i32,
         y: i32,
     },
+    RawMotion {
+        dx: f64,
+        dy: f64,
+    },
     FocusIn {},
     FocusOut {},
     ClientMessage {
@@ -266,6 +529,26 @@ pub enum Event {
     },
     ReparentNotify {},
     MapNotify {},
+    UnmapNotify {},
+    //another client wants the contents of a selection this window owns
+    SelectionRequest {
+        requestor: Window,
+        selection: Atom,
+        target: Atom,
+        property: Atom,
+        time: u64,
+    },
+    //reply to `convert_selection`, a property of 0 means the owner refused
+    SelectionNotify {
+        requestor: Window,
+        selection: Atom,
+        target: Atom,
+        property: Atom,
+        time: u64,
+    },
+    SelectionClear {
+        selection: Atom,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -277,25 +560,156 @@ pub enum Keycode {
     Space,
     LeftShift,
     Escape,
+    F11,
+    B,
+    C,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    T,
+    U,
+    V,
+    X,
+    Y,
+    Z,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F12,
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    CapsLock,
+    RightShift,
+    LeftControl,
+    RightControl,
+    LeftAlt,
+    RightAlt,
+    LeftSuper,
+    RightSuper,
+    Unknown(u32),
 }
 
 pub enum Error {
     Invalid,
+    //consumed by the input method, for example the first half of a dead key
+    Filtered,
 }
 
-impl TryFrom<u32> for Keycode {
-    type Error = ();
-
-    fn try_from(code: u32) -> Result<Self, Self::Error> {
+impl From<u32> for Keycode {
+    fn from(code: u32) -> Self {
         match code {
-            25 => Ok(Self::W),
-            38 => Ok(Self::A),
-            39 => Ok(Self::S),
-            40 => Ok(Self::D),
-            65 => Ok(Self::Space),
-            50 => Ok(Self::LeftShift),
-            9 => Ok(Self::Escape),
-            _ => Err(()),
+            25 => Self::W,
+            38 => Self::A,
+            39 => Self::S,
+            40 => Self::D,
+            65 => Self::Space,
+            50 => Self::LeftShift,
+            9 => Self::Escape,
+            95 => Self::F11,
+            56 => Self::B,
+            54 => Self::C,
+            26 => Self::E,
+            41 => Self::F,
+            42 => Self::G,
+            43 => Self::H,
+            31 => Self::I,
+            44 => Self::J,
+            45 => Self::K,
+            46 => Self::L,
+            58 => Self::M,
+            57 => Self::N,
+            32 => Self::O,
+            33 => Self::P,
+            24 => Self::Q,
+            27 => Self::R,
+            28 => Self::T,
+            30 => Self::U,
+            55 => Self::V,
+            53 => Self::X,
+            29 => Self::Y,
+            52 => Self::Z,
+            19 => Self::Key0,
+            10 => Self::Key1,
+            11 => Self::Key2,
+            12 => Self::Key3,
+            13 => Self::Key4,
+            14 => Self::Key5,
+            15 => Self::Key6,
+            16 => Self::Key7,
+            17 => Self::Key8,
+            18 => Self::Key9,
+            67 => Self::F1,
+            68 => Self::F2,
+            69 => Self::F3,
+            70 => Self::F4,
+            71 => Self::F5,
+            72 => Self::F6,
+            73 => Self::F7,
+            74 => Self::F8,
+            75 => Self::F9,
+            76 => Self::F10,
+            96 => Self::F12,
+            111 => Self::Up,
+            116 => Self::Down,
+            113 => Self::Left,
+            114 => Self::Right,
+            36 => Self::Enter,
+            23 => Self::Tab,
+            22 => Self::Backspace,
+            119 => Self::Delete,
+            118 => Self::Insert,
+            110 => Self::Home,
+            115 => Self::End,
+            112 => Self::PageUp,
+            117 => Self::PageDown,
+            66 => Self::CapsLock,
+            62 => Self::RightShift,
+            37 => Self::LeftControl,
+            105 => Self::RightControl,
+            64 => Self::LeftAlt,
+            108 => Self::RightAlt,
+            133 => Self::LeftSuper,
+            134 => Self::RightSuper,
+            _ => Self::Unknown(code),
         }
     }
 }
@@ -375,12 +789,149 @@ pub fn next_event(display: Display) -> Result<Event, Error> {
 
     let event = unsafe { event.assume_init() };
 
+    translate_event(display, event, None)
+}
+
+/// Like `next_event`, but runs events through the input method first. Text composed
+/// by a key press is appended to `text`, the key press itself is still returned.
+/// Generic events are only translated when they come from `xinput_opcode`.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet, and
+/// `input_context` one created on it by `create_input_context`.
+pub unsafe fn next_event_with_input(
+    display: Display,
+    input_context: Option<InputContext>,
+    xinput_opcode: Option<i32>,
+    text: &mut String,
+) -> Result<Event, Error> {
+    let mut event = MaybeUninit::<ffi::Event>::uninit();
+
+    ffi::XNextEvent(display, event.as_mut_ptr());
+
+    let mut event = event.assume_init();
+
+    if ffi::XFilterEvent(&mut event, 0) != 0 {
+        return Err(Error::Filtered);
+    }
+
+    if let Some(input_context) = input_context {
+        if event.ty == ffi::KEY_PRESS {
+            lookup_string(input_context, &mut event, text);
+        }
+    }
+
+    translate_event(display, event, xinput_opcode)
+}
+
+fn lookup_string(input_context: InputContext, event: &mut ffi::Event, text: &mut String) {
+    let mut buffer = vec![0_u8; 64];
+    let mut keysym = 0;
+    let mut status = 0;
+
+    loop {
+        let len = unsafe {
+            ffi::Xutf8LookupString(
+                input_context,
+                &mut event.key,
+                buffer.as_mut_ptr().cast(),
+                buffer.len() as _,
+                &mut keysym,
+                &mut status,
+            )
+        };
+
+        //the length needed is returned when the buffer is too small
+        if status == ffi::X_BUFFER_OVERFLOW {
+            buffer.resize(len as usize, 0);
+            continue;
+        }
+
+        text.push_str(&String::from_utf8_lossy(&buffer[..len.max(0) as usize]));
+
+        return;
+    }
+}
+
+/// Opens the input method chosen by the locale and `XMODIFIERS`. This sets the
+/// process' `LC_CTYPE` from the environment, which the input method depends on.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn create_input_context(display: Display, window: Window) -> Option<InputContext> {
+    let empty = CString::new("").unwrap();
+
+    libc::setlocale(libc::LC_CTYPE, empty.as_ptr());
+    ffi::XSetLocaleModifiers(empty.as_ptr());
+
+    let input_method = ffi::XOpenIM(
+        display,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    );
+
+    if input_method.is_null() {
+        return None;
+    }
+
+    let input_style = CString::new("inputStyle").unwrap();
+    let client_window = CString::new("clientWindow").unwrap();
+    let focus_window = CString::new("focusWindow").unwrap();
+
+    let input_context = ffi::XCreateIC(
+        input_method,
+        input_style.as_ptr(),
+        ffi::XIM_PREEDIT_NOTHING | ffi::XIM_STATUS_NOTHING,
+        client_window.as_ptr(),
+        window,
+        focus_window.as_ptr(),
+        window,
+        std::ptr::null::<ffi::c_char>(),
+    );
+
+    if input_context.is_null() {
+        None
+    } else {
+        Some(input_context)
+    }
+}
+
+/// # Safety
+///
+/// `input_context` must be created by `create_input_context` on a display that is
+/// not closed yet.
+pub unsafe fn set_input_focus(input_context: InputContext, focus: bool) {
+    if focus {
+        ffi::XSetICFocus(input_context);
+    } else {
+        ffi::XUnsetICFocus(input_context);
+    }
+}
+
+fn translate_event(
+    display: Display,
+    event: ffi::Event,
+    xinput_opcode: Option<i32>,
+) -> Result<Event, Error> {
     let event = unsafe {
         match event.ty {
             ffi::EXPOSE => Event::Expose {},
             ffi::KEY_PRESS => Event::KeyPress {
                 serial: event.key.serial,
-                keycode: event.key.keycode.try_into().map_err(|_| Error::Invalid)?,
+                keycode: event.key.keycode.into(),
+            },
+            ffi::BUTTON_PRESS => Event::ButtonPress {
+                x: event.button.x,
+                y: event.button.y,
+                button: event.button.button,
+            },
+            ffi::BUTTON_RELEASE => Event::ButtonRelease {
+                x: event.button.x,
+                y: event.button.y,
+                button: event.button.button,
             },
             ffi::MOTION_NOTIFY => Event::MotionNotify {
                 x: event.motion.x,
@@ -388,7 +939,7 @@ pub fn next_event(display: Display) -> Result<Event, Error> {
             },
             ffi::KEY_RELEASE => Event::KeyRelease {
                 serial: event.key.serial,
-                keycode: event.key.keycode.try_into().map_err(|_| Error::Invalid)?,
+                keycode: event.key.keycode.into(),
             },
             ffi::FOCUS_IN => Event::FocusIn {},
             ffi::FOCUS_OUT => Event::FocusOut {},
@@ -407,8 +958,48 @@ pub fn next_event(display: Display) -> Result<Event, Error> {
                 width: event.configure.width,
                 height: event.configure.height,
             },
+            ffi::GENERIC_EVENT => {
+                let mut cookie = event.cookie;
+
+                //other extensions reuse the same event types for unrelated events
+                if Some(cookie.extension) != xinput_opcode {
+                    return Err(Error::Invalid);
+                }
+
+                if ffi::XGetEventData(display, &mut cookie) == 0 {
+                    return Err(Error::Invalid);
+                }
+
+                let event = if cookie.evtype == ffi::XI_RAW_MOTION {
+                    Ok(raw_motion(&*cookie.data.cast::<ffi::XIRawEvent>()))
+                } else {
+                    Err(Error::Invalid)
+                };
+
+                ffi::XFreeEventData(display, &mut cookie);
+
+                event?
+            }
             ffi::REPARENT_NOTIFY => Event::ReparentNotify {},
             ffi::MAP_NOTIFY => Event::MapNotify {},
+            ffi::UNMAP_NOTIFY => Event::UnmapNotify {},
+            ffi::SELECTION_REQUEST => Event::SelectionRequest {
+                requestor: event.selection_request.requestor,
+                selection: event.selection_request.selection,
+                target: event.selection_request.target,
+                property: event.selection_request.property,
+                time: event.selection_request.time,
+            },
+            ffi::SELECTION_NOTIFY => Event::SelectionNotify {
+                requestor: event.selection.requestor,
+                selection: event.selection.selection,
+                target: event.selection.target,
+                property: event.selection.property,
+                time: event.selection.time,
+            },
+            ffi::SELECTION_CLEAR => Event::SelectionClear {
+                selection: event.selection_clear.selection,
+            },
             _ => {
                 unimplemented!("x11 event: {}", event.ty);
             }
@@ -418,6 +1009,79 @@ pub fn next_event(display: Display) -> Result<Event, Error> {
     Ok(event)
 }
 
+//raw values are packed, holding only the axes set in the valuator mask
+unsafe fn raw_motion(raw: &ffi::XIRawEvent) -> Event {
+    let mask = std::slice::from_raw_parts(raw.valuators.mask, raw.valuators.mask_len as _);
+
+    let mut delta = [0.0; 2];
+    let mut value = raw.raw_values;
+
+    for axis in 0..mask.len() * 8 {
+        if mask[axis / 8] & (1 << (axis % 8)) == 0 {
+            continue;
+        }
+
+        if axis < delta.len() {
+            delta[axis] = *value;
+        }
+
+        value = value.add(1);
+    }
+
+    Event::RawMotion {
+        dx: delta[0],
+        dy: delta[1],
+    }
+}
+
+/// Returns the major opcode of XInput if the server supports version 2.0, required
+/// for raw motion.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn query_xinput2(display: Display) -> Option<i32> {
+    let name = CString::new("XInputExtension").unwrap();
+
+    let (mut opcode, mut event, mut error) = (0, 0, 0);
+
+    let present =
+        ffi::XQueryExtension(display, name.as_ptr(), &mut opcode, &mut event, &mut error);
+
+    if present == 0 {
+        return None;
+    }
+
+    let (mut major, mut minor) = (2, 0);
+
+    if ffi::XIQueryVersion(display, &mut major, &mut minor) != 0 {
+        return None;
+    }
+
+    Some(opcode)
+}
+
+/// Raw motion is only delivered to the root window.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn select_raw_motion(display: Display, root: Window, enable: bool) {
+    let mut mask = [0u8; (ffi::XI_LAST_EVENT as usize >> 3) + 1];
+
+    if enable {
+        mask[ffi::XI_RAW_MOTION as usize >> 3] |= 1 << (ffi::XI_RAW_MOTION & 7);
+    }
+
+    let mut event_mask = ffi::XIEventMask {
+        deviceid: ffi::XI_ALL_MASTER_DEVICES,
+        mask_len: mask.len() as _,
+        mask: mask.as_mut_ptr(),
+    };
+
+    ffi::XISelectEvents(display, root, &mut event_mask, 1);
+}
+
 pub fn send_event(
     display: Display,
     window: Window,
@@ -448,6 +1112,27 @@ pub fn send_event(
 
             ffi::Event { client_message }
         }
+        Event::SelectionNotify {
+            requestor,
+            selection,
+            target,
+            property,
+            time,
+        } => {
+            let selection = ffi::SelectionEvent {
+                ty: ffi::SELECTION_NOTIFY,
+                serial: 0,
+                send_event: 1,
+                display,
+                requestor,
+                selection,
+                target,
+                property,
+                time,
+            };
+
+            ffi::Event { selection }
+        }
         _ => todo!("sending event not implemented"),
     };
 
@@ -471,6 +1156,94 @@ pub fn change_property(
     unsafe { ffi::XChangeProperty(display, window, property, ty, format, mode, data, nelements) };
 }
 
+/// Reads a whole property, returning its type and raw bytes. Items of format 32 are
+/// `c_long` sized, so they take 8 bytes each on 64 bit targets.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn get_window_property(
+    display: Display,
+    window: Window,
+    property: Atom,
+    delete: bool,
+) -> Option<(Atom, Vec<u8>)> {
+    let mut ty = 0;
+    let mut format = 0;
+    let mut nitems = 0;
+    let mut bytes_after = 0;
+    let mut data = std::ptr::null_mut();
+
+    let result = ffi::XGetWindowProperty(
+        display,
+        window,
+        property,
+        0,
+        //in 32 bit units, large enough to read everything in one go
+        i32::MAX as _,
+        delete as _,
+        ffi::ANY_PROPERTY_TYPE,
+        &mut ty,
+        &mut format,
+        &mut nitems,
+        &mut bytes_after,
+        &mut data,
+    );
+
+    if result != 0 || data.is_null() {
+        return None;
+    }
+
+    let item_size = match format {
+        8 => 1,
+        16 => mem::size_of::<ffi::c_short>(),
+        _ => mem::size_of::<ffi::c_long>(),
+    };
+
+    let bytes = std::slice::from_raw_parts(data, nitems as usize * item_size).to_vec();
+
+    ffi::XFree(data.cast());
+
+    Some((ty, bytes))
+}
+
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn set_selection_owner(display: Display, selection: Atom, owner: Window) {
+    ffi::XSetSelectionOwner(display, selection, owner, ffi::CURRENT_TIME);
+}
+
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn get_selection_owner(display: Display, selection: Atom) -> Window {
+    ffi::XGetSelectionOwner(display, selection)
+}
+
+/// Asks the owner of `selection` to store it as `target` in `property` on `requestor`.
+/// The owner answers later with a `SelectionNotify`.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn convert_selection(
+    display: Display,
+    selection: Atom,
+    target: Atom,
+    property: Atom,
+    requestor: Window,
+) {
+    ffi::XConvertSelection(
+        display,
+        selection,
+        target,
+        property,
+        requestor,
+        ffi::CURRENT_TIME,
+    );
+}
+
 pub fn intern_atom(display: Display, atom_name: &str, only_if_exists: bool) -> Atom {
     let atom_name = CString::new(atom_name).unwrap();
 
@@ -494,6 +1267,58 @@ pub fn store_name(display: Display, window: Window, window_name: &str) {
     unsafe { ffi::XStoreName(display, window, window_name.as_c_str().as_ptr()) };
 }
 
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn resize_window(display: Display, window: Window, width: u32, height: u32) {
+    ffi::XResizeWindow(display, window, width, height);
+}
+
+/// Size limits the window manager enforces when the user resizes the window.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SizeHints {
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
+}
+
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn set_wm_normal_hints(display: Display, window: Window, hints: SizeHints) {
+    let mut size_hints = mem::zeroed::<ffi::SizeHints>();
+
+    if let Some((width, height)) = hints.min_size {
+        size_hints.flags |= ffi::P_MIN_SIZE;
+        size_hints.min_width = width as _;
+        size_hints.min_height = height as _;
+    }
+
+    if let Some((width, height)) = hints.max_size {
+        size_hints.flags |= ffi::P_MAX_SIZE;
+        size_hints.max_width = width as _;
+        size_hints.max_height = height as _;
+    }
+
+    ffi::XSetWMNormalHints(display, window, &size_hints);
+}
+
+/// The `RESOURCE_MANAGER` property of the root window, as loaded by `xrdb`.
+///
+/// # Safety
+///
+/// `display` must be a display returned by `open_display` that is not closed yet.
+pub unsafe fn resource_manager_string(display: Display) -> Option<String> {
+    let resources = ffi::XResourceManagerString(display);
+
+    if resources.is_null() {
+        return None;
+    }
+
+    let resources = std::ffi::CStr::from_ptr(resources);
+
+    Some(resources.to_string_lossy().into_owned())
+}
+
 pub fn pending(display: Display) -> i32 {
     unsafe { ffi::XPending(display) }
 }